@@ -0,0 +1,2 @@
+DROP INDEX idx_denylist_expires_at;
+ALTER TABLE denylist DROP COLUMN expires_at;
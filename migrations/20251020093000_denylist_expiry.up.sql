@@ -0,0 +1,5 @@
+-- Optional expiry for temporary denylist entries
+-- NULL expires_at = permanent entry
+ALTER TABLE denylist ADD COLUMN expires_at DATETIME;
+
+CREATE INDEX idx_denylist_expires_at ON denylist(expires_at);
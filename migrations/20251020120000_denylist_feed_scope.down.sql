@@ -0,0 +1,16 @@
+-- Feed-scoped entries cannot be represented globally and are dropped
+CREATE TABLE denylist_global (
+    subject TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT (datetime('now')),
+    expires_at DATETIME,
+    PRIMARY KEY (subject)
+);
+
+INSERT INTO denylist_global (subject, reason, updated_at, expires_at)
+SELECT subject, reason, updated_at, expires_at FROM denylist WHERE feed_id IS NULL;
+
+DROP TABLE denylist;
+ALTER TABLE denylist_global RENAME TO denylist;
+
+CREATE INDEX idx_denylist_expires_at ON denylist(expires_at);
@@ -0,0 +1,18 @@
+-- Scope denylist entries to a single feed
+-- NULL feed_id = global entry applied to every feed
+CREATE TABLE denylist_scoped (
+    subject TEXT NOT NULL,
+    feed_id TEXT,
+    reason TEXT NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT (datetime('now')),
+    expires_at DATETIME
+);
+
+INSERT INTO denylist_scoped (subject, feed_id, reason, updated_at, expires_at)
+SELECT subject, NULL, reason, updated_at, expires_at FROM denylist;
+
+DROP TABLE denylist;
+ALTER TABLE denylist_scoped RENAME TO denylist;
+
+CREATE UNIQUE INDEX idx_denylist_subject_feed ON denylist(subject, COALESCE(feed_id, ''));
+CREATE INDEX idx_denylist_expires_at ON denylist(expires_at);
@@ -0,0 +1,24 @@
+-- Variant filters cannot be represented per user and are dropped
+CREATE TABLE timeline_user_filters_by_user (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_did TEXT NOT NULL,
+  filter_type TEXT NOT NULL,
+  filter_value TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (user_did) REFERENCES timeline_user_config(did) ON DELETE CASCADE,
+  UNIQUE(user_did, filter_type, filter_value)
+);
+
+INSERT INTO timeline_user_filters_by_user (id, user_did, filter_type, filter_value, created_at)
+SELECT f.id, f.user_did, f.filter_type, f.filter_value, f.created_at
+FROM timeline_user_filters f
+JOIN timeline_user_config c ON c.did = f.user_did AND c.feed_uri = f.feed_uri;
+
+DROP TABLE timeline_user_filters;
+ALTER TABLE timeline_user_filters_by_user RENAME TO timeline_user_filters;
+
+CREATE INDEX idx_timeline_user_filters_user ON timeline_user_filters(user_did);
+CREATE INDEX idx_timeline_user_filters_type ON timeline_user_filters(filter_type, filter_value);
+
+DROP TABLE timeline_user_feeds;
@@ -0,0 +1,39 @@
+-- Feed variants: additional feed URIs served from one user's timeline poll,
+-- each with its own filter set
+CREATE TABLE timeline_user_feeds (
+  feed_uri TEXT PRIMARY KEY,
+  user_did TEXT NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (user_did) REFERENCES timeline_user_config(did) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_timeline_user_feeds_user ON timeline_user_feeds(user_did);
+
+-- Filters are keyed by feed so every variant carries its own rules
+CREATE TABLE timeline_user_filters_by_feed (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_did TEXT NOT NULL,
+  feed_uri TEXT NOT NULL,
+  filter_type TEXT NOT NULL,
+  filter_value TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (user_did) REFERENCES timeline_user_config(did) ON DELETE CASCADE,
+  UNIQUE(feed_uri, filter_type, filter_value)
+);
+
+INSERT INTO timeline_user_filters_by_feed (id, user_did, feed_uri, filter_type, filter_value, created_at)
+SELECT f.id, f.user_did, c.feed_uri, f.filter_type, f.filter_value, f.created_at
+FROM timeline_user_filters f
+JOIN timeline_user_config c ON c.did = f.user_did;
+
+DROP TABLE timeline_user_filters;
+ALTER TABLE timeline_user_filters_by_feed RENAME TO timeline_user_filters;
+
+CREATE INDEX idx_timeline_user_filters_user ON timeline_user_filters(user_did);
+CREATE INDEX idx_timeline_user_filters_feed ON timeline_user_filters(feed_uri);
+CREATE INDEX idx_timeline_user_filters_type ON timeline_user_filters(filter_type, filter_value);
@@ -0,0 +1,2 @@
+DROP TABLE web_sessions;
+ALTER TABLE timeline_user_config DROP COLUMN managed_by;
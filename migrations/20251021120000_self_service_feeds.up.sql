@@ -0,0 +1,15 @@
+-- Feeds created through the web login flow are owned by the database rather
+-- than the YAML config ('config' or 'web')
+ALTER TABLE timeline_user_config ADD COLUMN managed_by TEXT NOT NULL DEFAULT 'config';
+
+-- Browser sessions for the self-service settings pages
+CREATE TABLE web_sessions (
+  session_id TEXT PRIMARY KEY,
+  did TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  expires_at TEXT NOT NULL,
+
+  FOREIGN KEY (did) REFERENCES timeline_user_config(did) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_web_sessions_expires_at ON web_sessions(expires_at);
@@ -0,0 +1,4 @@
+DROP INDEX feed_content_idx_updated_at;
+
+DROP INDEX feed_content_idx_feed;
+CREATE INDEX feed_content_idx_feed ON feed_content(feed_id, indexed_at DESC);
@@ -0,0 +1,6 @@
+-- Covering index for getFeedSkeleton: the page is read from the index alone
+DROP INDEX feed_content_idx_feed;
+CREATE INDEX feed_content_idx_feed ON feed_content(feed_id, indexed_at DESC, uri, repost_uri);
+
+-- Cleanup deletes by updated_at
+CREATE INDEX feed_content_idx_updated_at ON feed_content(updated_at);
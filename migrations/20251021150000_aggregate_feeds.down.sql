@@ -0,0 +1,4 @@
+ALTER TABLE feed_content DROP COLUMN source_did;
+
+DROP TABLE timeline_aggregate_sources;
+DROP TABLE timeline_aggregate_feeds;
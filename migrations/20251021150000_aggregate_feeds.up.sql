@@ -0,0 +1,21 @@
+-- Aggregate feeds merge the primary feeds of several accounts into one feed URI
+CREATE TABLE timeline_aggregate_feeds (
+  feed_uri TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  description TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE timeline_aggregate_sources (
+  feed_uri TEXT NOT NULL,
+  source_did TEXT NOT NULL,
+
+  PRIMARY KEY (feed_uri, source_did),
+  FOREIGN KEY (feed_uri) REFERENCES timeline_aggregate_feeds(feed_uri) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_timeline_aggregate_sources_did ON timeline_aggregate_sources(source_did);
+
+-- Account whose timeline a post was indexed from
+ALTER TABLE feed_content ADD COLUMN source_did TEXT;
@@ -0,0 +1,8 @@
+-- Posts pinned to the top of a feed, in display order
+CREATE TABLE feed_pins (
+  feed_id TEXT NOT NULL,
+  uri TEXT NOT NULL,
+  position INTEGER NOT NULL,
+  created_at DATETIME NOT NULL DEFAULT (datetime('now')),
+  PRIMARY KEY (feed_id, uri)
+);
@@ -0,0 +1 @@
+DROP TABLE timeline_feed_diversity;
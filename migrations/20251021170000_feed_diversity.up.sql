@@ -0,0 +1,6 @@
+-- Per-feed author diversity limits from the YAML config, applied by getFeedSkeleton
+CREATE TABLE timeline_feed_diversity (
+  feed_uri TEXT PRIMARY KEY,
+  max_consecutive_per_author INTEGER,
+  max_per_author_per_page INTEGER
+);
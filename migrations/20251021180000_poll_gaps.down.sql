@@ -0,0 +1 @@
+DROP TABLE timeline_poll_gaps;
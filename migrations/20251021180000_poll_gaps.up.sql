@@ -0,0 +1,11 @@
+-- Stretches of a timeline that a new-posts poll could not reach, filled in by
+-- following the saved cursor until it passes the newest post indexed before
+CREATE TABLE timeline_poll_gaps (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_did TEXT NOT NULL,
+  cursor TEXT NOT NULL,
+  until_indexed_at INTEGER NOT NULL,
+  created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_timeline_poll_gaps_user ON timeline_poll_gaps(user_did);
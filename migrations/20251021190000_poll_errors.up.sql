@@ -0,0 +1,9 @@
+-- Consecutive poll failures per user, driving retry backoff and suspension.
+-- The row is removed on the next successful poll or when the user's tokens are replaced.
+CREATE TABLE timeline_poll_errors (
+  user_did TEXT PRIMARY KEY NOT NULL,
+  consecutive_failures INTEGER NOT NULL,
+  last_error TEXT NOT NULL,
+  last_failure_at TEXT NOT NULL,
+  retry_at TEXT NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Webhook batches that failed every delivery attempt, kept for inspection and replay
+CREATE TABLE webhook_dead_letters (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  url TEXT NOT NULL,
+  payload TEXT NOT NULL,
+  error TEXT NOT NULL,
+  created_at TEXT NOT NULL
+);
@@ -0,0 +1,5 @@
+DROP TRIGGER post_metadata_au;
+DROP TRIGGER post_metadata_ad;
+DROP TRIGGER post_metadata_ai;
+DROP TABLE post_metadata_fts;
+DROP TABLE post_metadata;
@@ -0,0 +1,22 @@
+-- Text of indexed posts, stored when POST_METADATA_ENABLE is set
+CREATE TABLE post_metadata (
+  uri TEXT PRIMARY KEY,
+  text TEXT NOT NULL,
+  created_at DATETIME NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Full-text index over post_metadata.text, kept in sync by the triggers below
+CREATE VIRTUAL TABLE post_metadata_fts USING fts5(text, content='post_metadata', content_rowid='rowid');
+
+CREATE TRIGGER post_metadata_ai AFTER INSERT ON post_metadata BEGIN
+  INSERT INTO post_metadata_fts(rowid, text) VALUES (new.rowid, new.text);
+END;
+
+CREATE TRIGGER post_metadata_ad AFTER DELETE ON post_metadata BEGIN
+  INSERT INTO post_metadata_fts(post_metadata_fts, rowid, text) VALUES ('delete', old.rowid, old.text);
+END;
+
+CREATE TRIGGER post_metadata_au AFTER UPDATE ON post_metadata BEGIN
+  INSERT INTO post_metadata_fts(post_metadata_fts, rowid, text) VALUES ('delete', old.rowid, old.text);
+  INSERT INTO post_metadata_fts(rowid, text) VALUES (new.rowid, new.text);
+END;
@@ -0,0 +1,4 @@
+DROP INDEX idx_post_metadata_author;
+ALTER TABLE post_metadata DROP COLUMN embed_type;
+ALTER TABLE post_metadata DROP COLUMN author_handle;
+ALTER TABLE post_metadata DROP COLUMN author_did;
@@ -0,0 +1,6 @@
+-- Author and embed of indexed posts, stored alongside their text
+ALTER TABLE post_metadata ADD COLUMN author_did TEXT;
+ALTER TABLE post_metadata ADD COLUMN author_handle TEXT;
+ALTER TABLE post_metadata ADD COLUMN embed_type TEXT;
+
+CREATE INDEX idx_post_metadata_author ON post_metadata(author_did);
@@ -0,0 +1 @@
+ALTER TABLE timeline_user_config DROP COLUMN private;
@@ -0,0 +1,2 @@
+-- Timelines whose feeds are only served to their owner
+ALTER TABLE timeline_user_config ADD COLUMN private INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+DROP TABLE timeline_config_errors;
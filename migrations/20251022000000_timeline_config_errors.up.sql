@@ -0,0 +1,5 @@
+-- Timeline feed config entries skipped on the last startup, see TIMELINE_FEEDS_LENIENT
+CREATE TABLE timeline_config_errors (
+    error TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
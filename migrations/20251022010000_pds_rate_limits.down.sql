@@ -0,0 +1 @@
+DROP TABLE pds_rate_limits;
@@ -0,0 +1,9 @@
+-- Rate limit budget last reported by each PDS in ratelimit-* response headers
+CREATE TABLE pds_rate_limits (
+    pds_host TEXT NOT NULL PRIMARY KEY,
+    rate_limit INTEGER NOT NULL,
+    remaining INTEGER NOT NULL,
+    -- Unix time in seconds when the window resets, if reported
+    reset_at INTEGER,
+    updated_at TEXT NOT NULL
+);
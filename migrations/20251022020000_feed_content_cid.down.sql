@@ -0,0 +1 @@
+ALTER TABLE feed_content DROP COLUMN cid;
@@ -0,0 +1,2 @@
+-- CID of the post record, so a post edited in place (same uri, new cid) can be told apart
+ALTER TABLE feed_content ADD COLUMN cid TEXT;
@@ -0,0 +1,3 @@
+DROP INDEX idx_feed_serve_log_served_at;
+DROP INDEX idx_feed_serve_log_feed;
+DROP TABLE feed_serve_log;
@@ -0,0 +1,13 @@
+-- Posts served by getFeedSkeleton, one row per post and page, when SERVE_LOG=database
+CREATE TABLE feed_serve_log (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  feed_uri TEXT NOT NULL,
+  viewer_did TEXT,
+  cursor TEXT,
+  position INTEGER NOT NULL,
+  uri TEXT NOT NULL,
+  served_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_feed_serve_log_feed ON feed_serve_log(feed_uri, served_at);
+CREATE INDEX idx_feed_serve_log_served_at ON feed_serve_log(served_at);
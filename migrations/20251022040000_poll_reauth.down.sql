@@ -0,0 +1 @@
+ALTER TABLE timeline_poll_errors DROP COLUMN reauth_required;
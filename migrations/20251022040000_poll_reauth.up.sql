@@ -0,0 +1,2 @@
+-- Set when refreshSession rejects the refresh token; polling stops until new tokens arrive
+ALTER TABLE timeline_poll_errors ADD COLUMN reauth_required INTEGER NOT NULL DEFAULT 0;
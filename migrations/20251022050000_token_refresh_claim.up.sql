@@ -0,0 +1,3 @@
+-- Set while a poller refreshes the user's tokens, so concurrent pollers and
+-- instances sharing the database do not rotate the refresh token twice
+ALTER TABLE timeline_user_config ADD COLUMN refresh_claimed_at TEXT;
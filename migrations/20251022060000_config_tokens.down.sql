@@ -0,0 +1,2 @@
+ALTER TABLE timeline_user_config DROP COLUMN config_refresh_token;
+ALTER TABLE timeline_user_config DROP COLUMN config_access_token;
@@ -0,0 +1,5 @@
+-- Tokens last synced from the config file. The live tokens in access_token and
+-- refresh_token are rotated on every refresh, so a restart compares the config
+-- against these to tell whether the operator actually supplied new tokens.
+ALTER TABLE timeline_user_config ADD COLUMN config_access_token TEXT;
+ALTER TABLE timeline_user_config ADD COLUMN config_refresh_token TEXT;
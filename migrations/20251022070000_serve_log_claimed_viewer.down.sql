@@ -0,0 +1 @@
+ALTER TABLE feed_serve_log DROP COLUMN viewer_did_claimed;
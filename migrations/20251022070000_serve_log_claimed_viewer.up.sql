@@ -0,0 +1,3 @@
+-- Issuer of the request's service JWT, checked for audience, method and expiry
+-- but not for its signature, so any client can claim any DID here
+ALTER TABLE feed_serve_log ADD COLUMN viewer_did_claimed TEXT;
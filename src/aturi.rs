@@ -0,0 +1,216 @@
+//! AT-URI parsing and validation
+//!
+//! Post, repost and feed URIs come from PDS responses, config files and admin
+//! imports. They are parsed here before they are stored, so a malformed URI is
+//! rejected at the edge instead of ending up in feed skeletons.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::identity::is_handle;
+
+/// Collection of post records
+pub const POST_COLLECTION: &str = "app.bsky.feed.post";
+
+/// Collection of feed generator records
+pub const FEED_GENERATOR_COLLECTION: &str = "app.bsky.feed.generator";
+
+/// Collection of starter pack records
+pub const STARTER_PACK_COLLECTION: &str = "app.bsky.graph.starterpack";
+
+/// Longest AT-URI accepted, in bytes
+const MAX_LENGTH: usize = 8 * 1024;
+
+/// Longest record key accepted
+const MAX_RKEY_LENGTH: usize = 512;
+
+/// A validated `at://<authority>[/<collection>[/<rkey>]]` URI
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AtUri {
+    authority: String,
+    collection: Option<String>,
+    rkey: Option<String>,
+}
+
+impl AtUri {
+    /// Parse and validate an AT-URI
+    pub fn parse(uri: &str) -> Result<Self> {
+        if uri.len() > MAX_LENGTH {
+            anyhow::bail!("AT-URI is longer than {} bytes", MAX_LENGTH);
+        }
+        let path = uri
+            .strip_prefix("at://")
+            .ok_or_else(|| anyhow!("AT-URI must start with at://: {}", uri))?;
+
+        let mut parts = path.split('/');
+        let authority = parts.next().unwrap_or_default();
+        let collection = parts.next();
+        let rkey = parts.next();
+        if parts.next().is_some() {
+            anyhow::bail!("AT-URI has too many path segments: {}", uri);
+        }
+
+        Self::from_parts(authority, collection, rkey)
+    }
+
+    /// A record URI built from its components
+    pub fn record(authority: &str, collection: &str, rkey: &str) -> Result<Self> {
+        Self::from_parts(authority, Some(collection), Some(rkey))
+    }
+
+    fn from_parts(authority: &str, collection: Option<&str>, rkey: Option<&str>) -> Result<Self> {
+        if !is_did(authority) && (authority.starts_with('@') || !is_handle(authority)) {
+            anyhow::bail!("AT-URI authority must be a DID or handle: {}", authority);
+        }
+        if let Some(collection) = collection {
+            if !is_nsid(collection) {
+                anyhow::bail!("AT-URI collection is not a valid NSID: {}", collection);
+            }
+        }
+        if let Some(rkey) = rkey {
+            if !is_record_key(rkey) {
+                anyhow::bail!("AT-URI record key is invalid: {}", rkey);
+            }
+        }
+
+        Ok(Self {
+            authority: authority.to_string(),
+            collection: collection.map(str::to_string),
+            rkey: rkey.map(str::to_string),
+        })
+    }
+
+    /// The DID or handle of the repository
+    pub fn authority(&self) -> &str {
+        &self.authority
+    }
+
+    pub fn collection(&self) -> Option<&str> {
+        self.collection.as_deref()
+    }
+
+    pub fn rkey(&self) -> Option<&str> {
+        self.rkey.as_deref()
+    }
+
+    /// Whether this URI names a record in `collection`
+    pub fn is_record_in(&self, collection: &str) -> bool {
+        self.collection() == Some(collection) && self.rkey.is_some()
+    }
+}
+
+impl FromStr for AtUri {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        Self::parse(value)
+    }
+}
+
+impl fmt::Display for AtUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at://{}", self.authority)?;
+        if let Some(collection) = &self.collection {
+            write!(f, "/{}", collection)?;
+        }
+        if let Some(rkey) = &self.rkey {
+            write!(f, "/{}", rkey)?;
+        }
+        Ok(())
+    }
+}
+
+/// `did:<method>:<identifier>` with the characters allowed by the DID syntax
+fn is_did(value: &str) -> bool {
+    let Some((method, identifier)) = value
+        .strip_prefix("did:")
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return false;
+    };
+
+    !method.is_empty()
+        && method.chars().all(|c| c.is_ascii_lowercase())
+        && !identifier.is_empty()
+        && !identifier.ends_with(':')
+        && identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | ':' | '%' | '-'))
+}
+
+/// Reverse-domain NSID like `app.bsky.feed.post`
+fn is_nsid(value: &str) -> bool {
+    let segments: Vec<&str> = value.split('.').collect();
+    let Some((name, authority)) = segments.split_last() else {
+        return false;
+    };
+
+    segments.len() >= 3
+        && value.len() <= 317
+        && authority.iter().all(|segment| {
+            !segment.is_empty()
+                && segment.len() <= 63
+                && !segment.starts_with('-')
+                && !segment.ends_with('-')
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && !authority[0].starts_with(|c: char| c.is_ascii_digit())
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.len() <= 63
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_record_key(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_RKEY_LENGTH
+        && value != "."
+        && value != ".."
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | ':' | '~' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_at_uri() {
+        let uri = AtUri::parse("at://did:plc:abc123/app.bsky.feed.post/3kabc").unwrap();
+        assert_eq!(uri.authority(), "did:plc:abc123");
+        assert_eq!(uri.collection(), Some(POST_COLLECTION));
+        assert_eq!(uri.rkey(), Some("3kabc"));
+        assert!(uri.is_record_in(POST_COLLECTION));
+        assert_eq!(uri.to_string(), "at://did:plc:abc123/app.bsky.feed.post/3kabc");
+
+        let uri: AtUri = "at://alice.example.com".parse().unwrap();
+        assert_eq!(uri.authority(), "alice.example.com");
+        assert_eq!(uri.collection(), None);
+        assert_eq!(uri.to_string(), "at://alice.example.com");
+
+        assert_eq!(
+            AtUri::record("did:web:feeds.example.com", FEED_GENERATOR_COLLECTION, "timeline")
+                .unwrap()
+                .to_string(),
+            "at://did:web:feeds.example.com/app.bsky.feed.generator/timeline"
+        );
+
+        for invalid in [
+            "did:plc:abc123/app.bsky.feed.post/3kabc",
+            "at://",
+            "at://did:plc:abc123/",
+            "at://did:plc:abc123/post/1",
+            "at://did:PLC:abc123/app.bsky.feed.post/1",
+            "at://@alice.example.com/app.bsky.feed.post/1",
+            "at://did:plc:abc123/app.bsky.feed.post/",
+            "at://did:plc:abc123/app.bsky.feed.post/..",
+            "at://did:plc:abc123/app.bsky.feed.post/a b",
+            "at://did:plc:abc123/app.bsky.feed.post/1/extra",
+            "at://did:plc:abc123/app.bsky.feed.post/1?query",
+        ] {
+            assert!(AtUri::parse(invalid).is_err(), "{} should be rejected", invalid);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use tokio_util::sync::CancellationToken;
+
+use crate::feed_storage::StoragePool;
+
+/// File name prefix of backups written by the task
+const BACKUP_PREFIX: &str = "timeline-filter-";
+
+/// Free pages above this share of the database trigger a VACUUM
+const VACUUM_FREE_RATIO: f64 = 0.25;
+
+/// Periodic database maintenance: hot backups and compaction
+///
+/// Each run writes a consistent copy with `VACUUM INTO` when a backup directory
+/// is set, keeping the newest `retain` copies, then runs `PRAGMA optimize`,
+/// truncates the WAL and vacuums the database once cleanup deletes have left a
+/// large share of it free.
+pub struct BackupTask {
+    pool: StoragePool,
+    backup_dir: Option<PathBuf>,
+    retain: usize,
+    cancellation_token: CancellationToken,
+}
+
+impl BackupTask {
+    pub fn new(
+        pool: StoragePool,
+        backup_dir: Option<PathBuf>,
+        retain: usize,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            pool,
+            backup_dir,
+            retain,
+            cancellation_token,
+        }
+    }
+
+    pub async fn run_background(&self, interval: chrono::Duration) -> Result<()> {
+        let interval = interval.to_std()?;
+
+        let sleeper = tokio::time::sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                if let Err(err) = self.main().await {
+                    tracing::error!("BackupTask task failed: {}", err);
+                }
+
+                sleeper.as_mut().reset(tokio::time::Instant::now() + interval);
+            }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn main(&self) -> Result<()> {
+        if let Some(backup_dir) = &self.backup_dir {
+            let path = self.backup(backup_dir).await?;
+            tracing::info!(path = %path.display(), "Database backup written");
+            prune_backups(backup_dir, self.retain)?;
+        }
+
+        self.compact().await
+    }
+
+    async fn backup(&self, backup_dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(backup_dir).with_context(|| {
+            format!("failed to create backup directory: {}", backup_dir.display())
+        })?;
+
+        let path = backup_dir.join(format!(
+            "{}{}.db",
+            BACKUP_PREFIX,
+            Utc::now().format("%Y%m%d%H%M%S%3f")
+        ));
+
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().as_ref())
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("failed to write backup: {}", path.display()))?;
+
+        Ok(path)
+    }
+
+    async fn compact(&self) -> Result<()> {
+        sqlx::query("PRAGMA optimize")
+            .execute(&self.pool)
+            .await
+            .context("failed to optimize database")?;
+
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+            .fetch_one(&self.pool)
+            .await?;
+        let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(&self.pool)
+            .await?;
+
+        if page_count > 0 && freelist_count as f64 / page_count as f64 > VACUUM_FREE_RATIO {
+            tracing::info!(page_count, freelist_count, "Vacuuming database");
+            sqlx::query("VACUUM")
+                .execute(&self.pool)
+                .await
+                .context("failed to vacuum database")?;
+        }
+
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&self.pool)
+            .await
+            .context("failed to checkpoint WAL")?;
+
+        Ok(())
+    }
+}
+
+/// Delete all but the newest `retain` backups in a directory
+fn prune_backups(backup_dir: &Path, retain: usize) -> Result<()> {
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(backup_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(BACKUP_PREFIX) && name.ends_with(".db"))
+        })
+        .collect();
+
+    // Timestamped names sort oldest first
+    backups.sort();
+    let excess = backups.len().saturating_sub(retain);
+    for path in backups.into_iter().take(excess) {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("failed to remove old backup: {}", path.display()))?;
+        tracing::debug!(path = %path.display(), "Removed old database backup");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+
+    #[tokio::test]
+    async fn test_backup_retention() {
+        // VACUUM INTO from an in-memory database writes in-memory too, so use a file
+        let dir = std::env::temp_dir().join(format!(
+            "timeline-filter-backup-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = SqlitePool::connect(&format!("sqlite://{}/source.db?mode=rwc", dir.display()))
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let backup_dir = dir.join("backups");
+        let task = BackupTask::new(pool, Some(backup_dir.clone()), 2, CancellationToken::new());
+
+        for _ in 0..3 {
+            task.main().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let backups = std::fs::read_dir(&backup_dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(backups, 2);
+    }
+}
@@ -1,60 +1,209 @@
 use anyhow::Result;
-use sqlx::SqlitePool;
-use std::env;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use timeline_filter::backup::BackupTask;
 use timeline_filter::cleanup::CleanTask;
-use tokio::net::TcpListener;
 use tokio::signal;
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use tracing_subscriber::prelude::*;
 
-use timeline_filter::http::context::WebContext;
-use timeline_filter::http::server::build_router;
-use timeline_filter::feed_builder::{TimelineConsumerTask, TimelineConsumerConfig};
+use timeline_filter::feed_storage::{
+    connect_pools, connect_replica_pools, denylist_list, migrate, DatabasePools,
+};
+use timeline_filter::http::handle_admin_denylist::denylist_csv;
+use timeline_filter::http::handle_admin_feed_content::feed_content_export;
+use timeline_filter::http::rate_limit::{LoginLimiter, RateLimiter};
+use timeline_filter::http::listener::{serve, HttpListener};
+use timeline_filter::http::tls::load_tls_config;
+use timeline_filter::feed_records::verify_feed_records;
+use timeline_filter::redact::RedactingMakeWriter;
+use timeline_filter::serve_log::ServeLogSink;
+use timeline_filter::server_config::{Config, ServeLogTarget};
+use timeline_filter::vmc::VerificationMethodCacheTask;
+use timeline_filter::Server;
+
+/// Timeline Filter feed generator
+#[derive(Parser)]
+#[command(name = "timeline-filter", disable_version_flag = true)]
+struct Cli {
+    /// Print the version and exit
+    #[arg(long)]
+    version: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the feed generator (the default)
+    Serve,
+    /// Validate the environment and TIMELINE_FEEDS file, then exit
+    CheckConfig,
+    /// Print a feed's indexed posts as NDJSON, as accepted by /admin/feeds/import
+    Export {
+        /// Feed URI to export
+        #[arg(long)]
+        feed: String,
+    },
+    /// Print active denylist entries as CSV, as accepted by /admin/denylist/import
+    Denylist,
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Logs go to stderr so export output on stdout can be redirected
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "timeline_filter=debug,info".into()),
         ))
-        .with(tracing_subscriber::fmt::layer().pretty())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .pretty()
+                .with_writer(RedactingMakeWriter(std::io::stderr)),
+        )
         .init();
 
-    let version = timeline_filter::server_config::version()?;
+    let cli = Cli::parse();
+    if cli.version {
+        println!("{}", timeline_filter::server_config::version()?);
+        return Ok(());
+    }
 
-    env::args().for_each(|arg| {
-        if arg == "--version" {
-            println!("{}", version);
-            std::process::exit(0);
-        }
-    });
+    let config = Config::new()?;
 
-    let config = timeline_filter::server_config::Config::new()?;
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve_feeds(config).await,
+        Command::CheckConfig => check_config(&config),
+        Command::Export { feed } => export_feed(&config, &feed).await,
+        Command::Denylist => export_denylist(&config).await,
+    }
+}
+
+/// Open the database pools and apply pending migrations
+async fn connect(config: &Config) -> Result<DatabasePools> {
+    let pools = connect_pools(
+        &config.database_url,
+        *config.database_max_connections.as_ref(),
+        *config.database_read_connections.as_ref(),
+        config.database_busy_timeout.as_ref().to_std()?,
+    )
+    .await?;
+    migrate(&pools).await?;
+    Ok(pools)
+}
 
-    let mut client_builder = reqwest::Client::builder();
-    for ca_certificate in config.certificate_bundles.as_ref() {
-        tracing::info!("Loading CA certificate: {:?}", ca_certificate);
-        let cert = std::fs::read(ca_certificate)?;
-        let cert = reqwest::Certificate::from_pem(&cert)?;
-        client_builder = client_builder.add_root_certificate(cert);
+fn check_config(config: &Config) -> Result<()> {
+    println!("Service DID: {}", config.service.service_did);
+    match &config.timeline_feeds {
+        Some(timeline_feeds) => {
+            println!("Feeds: {}", timeline_feeds.len());
+            for feed_uri in timeline_feeds.feed_uris() {
+                println!("  {}", feed_uri);
+            }
+        }
+        None => println!("Feeds: none, TIMELINE_FEEDS is not set"),
+    }
+    if config.timeline_feed_errors.is_empty() {
+        println!("Configuration is valid");
+        return Ok(());
+    }
+    for error in &config.timeline_feed_errors {
+        println!("Skipped: {}", error);
     }
+    anyhow::bail!("{} timeline feed entries are invalid", config.timeline_feed_errors.len())
+}
 
-    client_builder = client_builder.user_agent(config.user_agent.clone());
-    let _http_client = client_builder.build()?;
+async fn export_feed(config: &Config, feed_uri: &str) -> Result<()> {
+    let pool = connect(config).await?.read;
+    if !timeline_filter::user_storage::feed_exists(&pool, feed_uri).await? {
+        anyhow::bail!("Feed is not served by this feed generator: {}", feed_uri);
+    }
+    print!("{}", feed_content_export(&pool, feed_uri).await?);
+    Ok(())
+}
 
-    let pool = SqlitePool::connect(&config.database_url).await?;
-    sqlx::migrate!().run(&pool).await?;
+async fn export_denylist(config: &Config) -> Result<()> {
+    let pool = connect(config).await?.read;
+    let entries = denylist_list(&pool).await?;
+    print!("{}", denylist_csv(&entries));
+    Ok(())
+}
 
-    let web_context = WebContext::new(
-        pool.clone(),
-        config.external_base.as_str(),
-    );
+async fn serve_feeds(config: Config) -> Result<()> {
+    let http_client = config.http_client_builder()?.build()?;
 
-    let app = build_router(web_context.clone());
+    // A read replica serves feeds from a database written by another instance
+    let read_replica = *config.read_replica.as_ref();
+    let pools = if read_replica {
+        tracing::info!("Read replica mode: serving feeds only, background tasks are disabled");
+        connect_replica_pools(
+            &config.database_url,
+            *config.database_read_connections.as_ref(),
+            config.database_busy_timeout.as_ref().to_std()?,
+        )
+        .await?
+    } else {
+        connect(&config).await?
+    };
+    let pool = pools.write.clone();
+    if !read_replica {
+        timeline_filter::user_storage::replace_config_errors(&pool, &config.timeline_feed_errors)
+            .await?;
+    }
 
     let tracker = TaskTracker::new();
     let token = CancellationToken::new();
 
+    let mut builder = Server::builder()
+        .with_database(pools)
+        .with_service(config.service.clone())
+        .with_http_client(http_client.clone())
+        .with_consumer_client_builder(config.http_client_builder()?)
+        .with_consumer(*config.timeline_consumer_enable.as_ref())
+        .with_poll_interval(*config.poll_interval.as_ref())
+        .with_handle_refresh_interval(*config.handle_refresh_interval.as_ref())
+        .with_post_metadata(*config.post_metadata_enable.as_ref())
+        .with_cid_validation(*config.post_cid_validation.as_ref())
+        .with_feed_cache(*config.feed_cache_size.as_ref(), *config.feed_cache_ttl.as_ref())
+        .with_plc_hostname(config.plc_hostname.clone())
+        .with_vmc_cache_max_age(*config.vmc_cache_max_age.as_ref())
+        .with_read_replica(read_replica);
+    if let Some(timeline_feeds) = &config.timeline_feeds {
+        builder = builder.with_feeds(timeline_feeds.clone());
+    }
+    if let Some(admin_token) = &config.admin_token {
+        builder = builder.with_admin_token(admin_token.clone());
+    }
+    builder = builder.with_login_limiter(LoginLimiter::new(config.rate_limit_forwarded_header.clone()));
+    if *config.rate_limit_enable.as_ref() {
+        builder = builder.with_rate_limiter(RateLimiter::new(
+            *config.rate_limit_per_minute.as_ref(),
+            config.rate_limit_forwarded_header.clone(),
+        ));
+    }
+    // Record served feed pages when SERVE_LOG is set
+    match &config.serve_log {
+        ServeLogTarget::Disabled => {}
+        ServeLogTarget::Database => {
+            builder = builder.with_serve_log(ServeLogSink::Database(pool.clone()));
+        }
+        ServeLogTarget::File(path) => {
+            builder = builder.with_serve_log(ServeLogSink::File(PathBuf::from(path)));
+        }
+    }
+
+    let server = builder.build(token.clone())?;
+    let verification_method_cache = server.verification_method_cache().clone();
+    let web_context = server.web_context().clone();
+    if server.has_consumer() {
+        tracing::info!(
+            feed_count = config.timeline_feeds.as_ref().map_or(0, |feeds| feeds.len()),
+            "Starting timeline consumer task"
+        );
+    }
+    let app = server.start(&tracker);
+
     {
         let tracker = tracker.clone();
         let inner_token = token.clone();
@@ -86,13 +235,26 @@ async fn main() -> Result<()> {
 
     // Jetstream consumer removed - Timeline Filter uses TimelineConsumerTask instead
 
-    // VerificationMethodCacheTask removed - not needed for Timeline Filter
-
-    // CacheTask removed - Timeline Filter doesn't use feed caching
+    {
+        let inner_config = config.clone();
+        let task_enable = *inner_config.vmc_task_enable.as_ref() && !read_replica;
+        if task_enable {
+            let task =
+                VerificationMethodCacheTask::new(verification_method_cache.clone(), token.clone());
+            let inner_token = token.clone();
+            let interval = *inner_config.vmc_task_interval.as_ref();
+            tracker.spawn(async move {
+                if let Err(err) = task.run_background(interval).await {
+                    tracing::warn!(error = ?err, "verification method cache task error");
+                }
+                inner_token.cancel();
+            });
+        }
+    }
 
     {
         let inner_config = config.clone();
-        let task_enable = *inner_config.cleanup_task_enable.as_ref();
+        let task_enable = *inner_config.cleanup_task_enable.as_ref() && !read_replica;
         let max_age = *inner_config.cleanup_task_max_age.as_ref();
         if task_enable {
             let task = CleanTask::new(pool.clone(), max_age, token.clone());
@@ -108,66 +270,48 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Timeline Consumer Task
     {
         let inner_config = config.clone();
-        let task_enable = *inner_config.timeline_consumer_enable.as_ref();
-
+        let task_enable = *inner_config.backup_task_enable.as_ref() && !read_replica;
         if task_enable {
-            if let Some(timeline_feeds) = inner_config.timeline_feeds {
-                if timeline_feeds.is_empty() {
-                    tracing::warn!("Timeline consumer enabled but no timeline feeds configured");
-                } else {
-                    tracing::info!(
-                        feed_count = timeline_feeds.len(),
-                        "Starting timeline consumer task"
-                    );
-
-                    let consumer_config = TimelineConsumerConfig {
-                        timeline_feeds,
-                        default_poll_interval: *inner_config.poll_interval.as_ref(),
-                        user_agent: inner_config.user_agent.clone(),
-                    };
-
-                    let task = TimelineConsumerTask::new(
-                        pool.clone(),
-                        consumer_config,
-                        token.clone(),
-                    )?;
-
-                    let inner_token = token.clone();
-                    tracker.spawn(async move {
-                        if let Err(err) = task.run_background().await {
-                            tracing::warn!(error = ?err, "timeline consumer task error");
-                        }
-                        inner_token.cancel();
-                    });
+            let task = BackupTask::new(
+                pool.clone(),
+                inner_config.backup_dir.map(PathBuf::from),
+                *inner_config.backup_retain.as_ref(),
+                token.clone(),
+            );
+            let inner_token = token.clone();
+            let interval = *inner_config.backup_task_interval.as_ref();
+            tracker.spawn(async move {
+                if let Err(err) = task.run_background(interval).await {
+                    tracing::warn!(error = ?err, "backup task error");
                 }
-            } else {
-                tracing::warn!("Timeline consumer enabled but TIMELINE_FEEDS env var not set");
-            }
+                inner_token.cancel();
+            });
         }
     }
 
+    // Warn about configured feed URIs that Bluesky won't route to this service
+    if let Some(timeline_feeds) = &config.timeline_feeds {
+        let feed_uris = timeline_feeds.feed_uris();
+        let http_client = http_client.clone();
+        let plc_hostname = config.plc_hostname.clone();
+        let service_did = web_context.service_did();
+        tracker.spawn(async move {
+            verify_feed_records(&http_client, &plc_hostname, &service_did, &feed_uris).await;
+        });
+    }
+
+    let tls_config = match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => Some(load_tls_config(cert_path, key_path)?),
+        _ => None,
+    };
+
     {
-        let inner_config = config.clone();
-        let http_port = *inner_config.http_port.as_ref();
+        let listener = HttpListener::bind(&config).await?;
         let inner_token = token.clone();
         tracker.spawn(async move {
-            let listener = TcpListener::bind(&format!("0.0.0.0:{}", http_port))
-                .await
-                .unwrap();
-
-            let shutdown_token = inner_token.clone();
-            let result = axum::serve(listener, app)
-                .with_graceful_shutdown(async move {
-                    tokio::select! {
-                        () = shutdown_token.cancelled() => { }
-                    }
-                    tracing::info!("axum graceful shutdown complete");
-                })
-                .await;
-            if let Err(err) = result {
+            if let Err(err) = serve(listener, app, tls_config, inner_token.clone()).await {
                 tracing::error!("axum task failed: {}", err);
             }
 
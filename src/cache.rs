@@ -0,0 +1,262 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::RwLock;
+
+use crate::feed_config::AuthorDiversity;
+use crate::feed_storage::{feed_pins_list, StoragePool};
+use crate::user_storage::{
+    feed_exists, get_feed_diversity, get_feed_posts, get_private_feed_owner, FeedCursor, FeedPost,
+};
+
+/// In-memory cache of the newest posts of each timeline feed
+///
+/// getFeedSkeleton pages are served from here when possible. The consumer
+/// refreshes a feed after each poll that indexed new posts; entries older than
+/// the TTL are treated as missing and requests fall back to the database.
+/// A size of 0 disables the cache.
+#[derive(Clone)]
+pub struct FeedCache(Arc<InnerFeedCache>);
+
+struct InnerFeedCache {
+    /// Read pool the cache is loaded from
+    pool: StoragePool,
+    size: usize,
+    ttl: Duration,
+    feeds: RwLock<HashMap<String, CachedFeed>>,
+    /// Only served feeds are kept, so unknown URIs can't grow the map
+    metadata: RwLock<HashMap<String, (DateTime<Utc>, FeedMetadata)>>,
+}
+
+/// What getFeedSkeleton needs to know about a served feed besides its posts
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeedMetadata {
+    /// Owner DID when the feed is private
+    pub private_owner: Option<String>,
+    pub diversity: AuthorDiversity,
+    /// Pinned post URIs, in order
+    pub pins: Vec<String>,
+}
+
+struct CachedFeed {
+    posts: Vec<FeedPost>,
+    /// The feed had fewer posts than the cache size, so the cache holds all of it
+    complete: bool,
+    refreshed_at: DateTime<Utc>,
+}
+
+impl FeedCache {
+    pub fn new(pool: StoragePool, size: usize, ttl: Duration) -> Self {
+        Self(Arc::new(InnerFeedCache {
+            pool,
+            size,
+            ttl,
+            feeds: RwLock::new(HashMap::new()),
+            metadata: RwLock::new(HashMap::new()),
+        }))
+    }
+
+    /// A cache that never holds anything
+    pub fn disabled(pool: StoragePool) -> Self {
+        Self::new(pool, 0, Duration::zero())
+    }
+
+    /// Number of posts cached per feed
+    pub fn size(&self) -> usize {
+        self.0.size
+    }
+
+    /// Get a page of a feed, or None when it has to come from the database
+    pub async fn page(
+        &self,
+        feed_uri: &str,
+        cursor: Option<&FeedCursor>,
+        limit: usize,
+    ) -> Option<Vec<FeedPost>> {
+        if self.0.size == 0 {
+            return None;
+        }
+
+        let feeds = self.0.feeds.read().await;
+        let cached = feeds.get(feed_uri)?;
+
+        if Utc::now() - cached.refreshed_at > self.0.ttl {
+            return None;
+        }
+        let offset = match cursor {
+            Some(cursor) => cached
+                .posts
+                .iter()
+                .position(|post| post.is_after(cursor))
+                .unwrap_or(cached.posts.len()),
+            None => 0,
+        };
+        if offset + limit > cached.posts.len() && !cached.complete {
+            return None;
+        }
+
+        Some(
+            cached
+                .posts
+                .iter()
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Reload the newest posts of a feed from the database
+    pub async fn refresh(&self, feed_uri: &str) -> Result<()> {
+        if self.0.size == 0 {
+            return Ok(());
+        }
+
+        let posts = get_feed_posts(&self.0.pool, feed_uri, self.0.size as u32, None).await?;
+        let cached = CachedFeed {
+            complete: posts.len() < self.0.size,
+            posts,
+            refreshed_at: Utc::now(),
+        };
+
+        self.0
+            .feeds
+            .write()
+            .await
+            .insert(feed_uri.to_string(), cached);
+
+        Ok(())
+    }
+
+    /// Metadata of a feed, or None when the feed is not served here
+    ///
+    /// Cached for the same TTL as posts and dropped by the same invalidations.
+    pub async fn metadata(&self, feed_uri: &str) -> Result<Option<FeedMetadata>> {
+        if self.0.size > 0 {
+            if let Some((loaded_at, metadata)) = self.0.metadata.read().await.get(feed_uri) {
+                if Utc::now() - *loaded_at <= self.0.ttl {
+                    return Ok(Some(metadata.clone()));
+                }
+            }
+        }
+
+        let pool = &self.0.pool;
+        if !feed_exists(pool, feed_uri).await? {
+            return Ok(None);
+        }
+        let metadata = FeedMetadata {
+            private_owner: get_private_feed_owner(pool, feed_uri).await?,
+            diversity: get_feed_diversity(pool, feed_uri).await?,
+            pins: feed_pins_list(pool, feed_uri).await?,
+        };
+
+        if self.0.size > 0 {
+            self.0
+                .metadata
+                .write()
+                .await
+                .insert(feed_uri.to_string(), (Utc::now(), metadata.clone()));
+        }
+        Ok(Some(metadata))
+    }
+
+    /// Number of posts currently cached for a feed
+    pub async fn cached_posts(&self, feed_uri: &str) -> usize {
+        self.0
+            .feeds
+            .read()
+            .await
+            .get(feed_uri)
+            .map(|cached| cached.posts.len())
+            .unwrap_or(0)
+    }
+
+    /// Drop a feed from the cache
+    pub async fn invalidate(&self, feed_uri: &str) {
+        self.0.feeds.write().await.remove(feed_uri);
+        self.0.metadata.write().await.remove(feed_uri);
+    }
+
+    /// Drop every feed from the cache
+    pub async fn invalidate_all(&self) {
+        self.0.feeds.write().await.clear();
+        self.0.metadata.write().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed_storage::{feed_content_upsert, model::FeedContent};
+    use sqlx::SqlitePool;
+
+    #[tokio::test]
+    async fn test_feed_cache_pages() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let feed_uri = "at://did:plc:feedgen/app.bsky.feed.generator/test";
+        for i in 0..5 {
+            feed_content_upsert(
+                &pool,
+                &FeedContent {
+                    feed_id: feed_uri.to_string(),
+                    uri: format!("at://did:plc:author/app.bsky.feed.post/{}", i),
+                    indexed_at: i,
+                    score: 1,
+                    is_repost: false,
+                    repost_uri: None,
+                    source_did: None,
+                    cid: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let cache = FeedCache::new(pool.clone(), 3, Duration::minutes(5));
+        assert!(cache.page(feed_uri, None, 2).await.is_none());
+
+        cache.refresh(feed_uri).await.unwrap();
+        let page = cache.page(feed_uri, None, 2).await.unwrap();
+        assert_eq!(page[0].uri, "at://did:plc:author/app.bsky.feed.post/4");
+        assert_eq!(page.len(), 2);
+
+        // Pages reaching past the cached window come from the database
+        let cursor = FeedCursor::parse(&page[1].cursor()).unwrap();
+        assert!(cache.page(feed_uri, Some(&cursor), 2).await.is_none());
+        let next = get_feed_posts(&pool, feed_uri, 2, Some(&cursor)).await.unwrap();
+        assert_eq!(next[0].uri, "at://did:plc:author/app.bsky.feed.post/2");
+
+        cache.invalidate(feed_uri).await;
+        assert!(cache.page(feed_uri, None, 2).await.is_none());
+
+        assert!(FeedCache::disabled(pool).page(feed_uri, None, 2).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_feed_metadata_is_cached() {
+        use crate::feed_storage::feed_pins_replace;
+
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let feed_uri = "at://did:plc:feedgen/app.bsky.feed.generator/test";
+        let pin = "at://did:plc:author/app.bsky.feed.post/1".to_string();
+        let cache = FeedCache::new(pool.clone(), 3, Duration::minutes(5));
+
+        assert!(cache.metadata(feed_uri).await.unwrap().is_none());
+        sqlx::query("INSERT INTO timeline_aggregate_feeds (feed_uri, name, description) VALUES (?, 'All', '')")
+            .bind(feed_uri)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(cache.metadata(feed_uri).await.unwrap(), Some(FeedMetadata::default()));
+
+        // Served from memory until invalidated
+        feed_pins_replace(&pool, feed_uri, std::slice::from_ref(&pin)).await.unwrap();
+        assert!(cache.metadata(feed_uri).await.unwrap().unwrap().pins.is_empty());
+        cache.invalidate(feed_uri).await;
+        assert_eq!(cache.metadata(feed_uri).await.unwrap().unwrap().pins, vec![pin]);
+    }
+}
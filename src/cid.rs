@@ -0,0 +1,61 @@
+//! Record CID validation
+//!
+//! Records in atproto repositories are addressed by CIDv1 of their DAG-CBOR
+//! encoding with a SHA-256 hash, written in base32.
+
+use anyhow::{anyhow, Context, Result};
+
+/// CID version 1
+const CID_VERSION: u8 = 0x01;
+
+/// Multicodec of DAG-CBOR, the encoding of repository records
+const CODEC_DAG_CBOR: u8 = 0x71;
+
+/// Multihash code of SHA-256
+const HASH_SHA256: u8 = 0x12;
+
+/// Length of a SHA-256 digest
+const SHA256_LENGTH: u8 = 32;
+
+/// Check that `cid` is a base32 CIDv1 of a DAG-CBOR record hashed with SHA-256
+pub fn validate_record_cid(cid: &str) -> Result<()> {
+    if !cid.starts_with('b') {
+        anyhow::bail!("CID must be base32 encoded: {}", cid);
+    }
+    let (_, bytes) = multibase::decode(cid).with_context(|| format!("invalid CID: {}", cid))?;
+
+    match bytes.as_slice() {
+        [CID_VERSION, CODEC_DAG_CBOR, HASH_SHA256, SHA256_LENGTH, digest @ ..]
+            if digest.len() == SHA256_LENGTH as usize =>
+        {
+            Ok(())
+        }
+        _ => Err(anyhow!("CID is not a SHA-256 DAG-CBOR CIDv1: {}", cid)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_record_cid() {
+        let cid = |bytes: Vec<u8>| multibase::encode(multibase::Base::Base32Lower, bytes);
+        let record = [vec![0x01, 0x71, 0x12, 0x20], vec![7; 32]].concat();
+
+        assert!(validate_record_cid(&cid(record.clone())).is_ok());
+        assert!(validate_record_cid(
+            "bafyreie5737gdxlw5i64vzichcalba3z2v5n6icifvx5xytvske7mr3hpm"
+        )
+        .is_ok());
+
+        // Raw codec, truncated digest, other bases and garbage are rejected
+        let mut raw = record.clone();
+        raw[1] = 0x55;
+        assert!(validate_record_cid(&cid(raw)).is_err());
+        assert!(validate_record_cid(&cid(record[..20].to_vec())).is_err());
+        assert!(validate_record_cid(&multibase::encode(multibase::Base::Base58Btc, &record)).is_err());
+        assert!(validate_record_cid("cid1").is_err());
+        assert!(validate_record_cid("").is_err());
+    }
+}
@@ -2,7 +2,12 @@ use anyhow::Result;
 use chrono::Utc;
 use tokio_util::sync::CancellationToken;
 
-use crate::feed_storage::{feed_content_truncate_oldest, StoragePool};
+use crate::feed_storage::{
+    denylist_remove_expired, feed_content_truncate_oldest, post_metadata_remove_orphaned,
+    StoragePool,
+};
+use crate::serve_log::serve_log_remove_before;
+use crate::user_storage::session_remove_expired;
 
 pub struct CleanTask {
     pool: StoragePool,
@@ -51,6 +56,10 @@ impl CleanTask {
     pub async fn main(&self) -> Result<()> {
         let now = Utc::now();
         let max_age = now - self.max_age;
-        feed_content_truncate_oldest(&self.pool, max_age).await
+        feed_content_truncate_oldest(&self.pool, max_age).await?;
+        post_metadata_remove_orphaned(&self.pool).await?;
+        serve_log_remove_before(&self.pool, max_age).await?;
+        session_remove_expired(&self.pool, now).await?;
+        denylist_remove_expired(&self.pool, now).await
     }
 }
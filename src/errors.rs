@@ -1,25 +1,85 @@
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
+    Json,
 };
+use serde_json::json;
 
+/// Errors returned by HTTP handlers, rendered as XRPC error bodies
+///
+/// `{"error": "<name>", "message": "<message>"}` with a matching status code.
+/// Any other error converts into `Internal` with `?`, which logs the cause and
+/// hides it from the client.
 #[derive(Debug)]
-pub struct TimelineFilterError(pub anyhow::Error);
+pub enum TimelineFilterError {
+    /// 400: the request parameters or body are invalid
+    InvalidRequest(String),
+    /// 401: missing or wrong credentials
+    AuthRequired(String),
+    /// 403: authenticated, but not allowed to act on this resource
+    Forbidden(String),
+    /// 404: the requested feed is not served here
+    UnknownFeed(String),
+    /// 404: the requested resource does not exist
+    NotFound(String),
+    /// 409: the resource is managed elsewhere, e.g. by the config file
+    Conflict(String),
+    /// 429: the client sent too many requests
+    RateLimitExceeded(String),
+    /// 500: anything else
+    Internal(anyhow::Error),
+}
 
 impl<E> From<E> for TimelineFilterError
 where
     E: Into<anyhow::Error>,
 {
     fn from(err: E) -> Self {
-        Self(err.into())
+        Self::Internal(err.into())
     }
 }
 
 impl IntoResponse for TimelineFilterError {
     fn into_response(self) -> Response {
-        {
-            tracing::error!(error = ?self.0, "internal server error");
-            (StatusCode::INTERNAL_SERVER_ERROR).into_response()
+        let (status, error, message) = match self {
+            Self::InvalidRequest(message) => (StatusCode::BAD_REQUEST, "InvalidRequest", message),
+            Self::AuthRequired(message) => (StatusCode::UNAUTHORIZED, "AuthRequired", message),
+            Self::Forbidden(message) => (StatusCode::FORBIDDEN, "Forbidden", message),
+            Self::UnknownFeed(message) => (StatusCode::NOT_FOUND, "UnknownFeed", message),
+            Self::NotFound(message) => (StatusCode::NOT_FOUND, "NotFound", message),
+            Self::Conflict(message) => (StatusCode::CONFLICT, "Conflict", message),
+            Self::RateLimitExceeded(message) => {
+                (StatusCode::TOO_MANY_REQUESTS, "RateLimitExceeded", message)
+            }
+            Self::Internal(err) => {
+                tracing::error!(error = ?err, "internal server error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "InternalError",
+                    "internal server error".to_string(),
+                )
+            }
+        };
+
+        (status, Json(json!({"error": error, "message": message}))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_status_codes() {
+        let cases = [
+            (TimelineFilterError::InvalidRequest("bad".into()), StatusCode::BAD_REQUEST),
+            (TimelineFilterError::AuthRequired("who".into()), StatusCode::UNAUTHORIZED),
+            (TimelineFilterError::UnknownFeed("where".into()), StatusCode::NOT_FOUND),
+            (anyhow::anyhow!("boom").into(), StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+
+        for (err, status) in cases {
+            assert_eq!(err.into_response().status(), status);
         }
     }
 }
@@ -20,15 +20,43 @@
 //! Posts with missing critical fields (like `indexedAt`) are logged and skipped during
 //! indexing rather than causing the entire poll cycle to fail.
 
+use std::collections::{HashMap, HashSet};
+
 use anyhow::{Context, Result};
 use chrono::Duration;
+use rand::Rng;
 use serde::Deserialize;
 use tokio_util::sync::CancellationToken;
 use tracing;
 
-use crate::feed_storage::{feed_content_upsert, model::FeedContent, StoragePool};
-use crate::feed_config::{FilterConfig, TimelineFeed, TimelineFeeds};
-use crate::user_storage;
+use crate::aturi::AtUri;
+use crate::cache::FeedCache;
+use crate::cid::validate_record_cid;
+use crate::feed_storage::{
+    denylist_matching, feed_content_reposts_since, feed_content_upsert,
+    model::{FeedContent, PostMetadata},
+    post_metadata_upsert, StoragePool, Upserted,
+};
+use crate::feed_config::{AggregateFeed, FilterConfig, TimelineFeed, TimelineFeeds};
+use crate::feed_events::{FeedEvents, IndexedPost};
+use crate::identity::{normalize_handle, resolve_handle};
+use crate::redact::redact_tokens;
+use crate::starter_pack::starter_pack_members;
+use crate::user_storage::{self, PdsRateLimit};
+use crate::webhook::{WebhookPost, WebhookQueue};
+
+/// Access tokens are refreshed once they expire within this
+const TOKEN_REFRESH_BUFFER: Duration = Duration::minutes(5);
+
+/// How long a token refresh claim blocks other pollers
+const TOKEN_REFRESH_LEASE: Duration = Duration::seconds(30);
+
+/// Pause between checks for tokens refreshed by another poller, plus jitter
+const TOKEN_REFRESH_WAIT: std::time::Duration = std::time::Duration::from_millis(500);
+const TOKEN_REFRESH_WAIT_JITTER_MS: u64 = 500;
+
+/// Pause between poll cycles; each cycle reloads feeds and filters from the database
+const POLL_CYCLE_TICK: std::time::Duration = std::time::Duration::from_secs(1);
 
 /// Timeline Consumer Task
 /// Polls getTimeline() for each configured user and indexes filtered posts
@@ -37,34 +65,44 @@ pub struct TimelineConsumerTask {
     config: TimelineConsumerConfig,
     http_client: reqwest::Client,
     cancellation_token: CancellationToken,
+    /// Skeleton cache refreshed after polls that index new posts
+    feed_cache: FeedCache,
+    /// Last successful handle -> DID resolutions, kept across refresh failures
+    resolved_handles: HashMap<String, String>,
+    /// Handles that failed to resolve since the last refresh, not retried until then
+    failed_handles: HashSet<String>,
+    /// Last successful starter pack member lookups, kept across refresh failures
+    starter_packs: HashMap<String, HashSet<String>>,
+    /// Newly indexed posts of feeds with a webhook_url are queued here
+    webhooks: WebhookQueue,
+    /// Newly indexed posts for /api/v1/feeds/:feed/stream
+    feed_events: FeedEvents,
 }
 
 /// Configuration for the Timeline Consumer
 pub struct TimelineConsumerConfig {
     pub timeline_feeds: TimelineFeeds,
     pub default_poll_interval: Duration,
-    pub user_agent: String,
+    /// How often handles in feed filters are re-resolved to DIDs
+    pub handle_refresh_interval: Duration,
+    /// Store the text, author and embed type of newly indexed posts
+    pub store_post_metadata: bool,
+    /// Skip posts whose CID is not a valid record CID
+    pub validate_cids: bool,
 }
 
 impl TimelineConsumerTask {
     /// Create a new Timeline Consumer Task
     pub fn new(
         pool: StoragePool,
+        client_builder: reqwest::ClientBuilder,
         config: TimelineConsumerConfig,
+        feed_cache: FeedCache,
+        webhooks: WebhookQueue,
+        feed_events: FeedEvents,
         cancellation_token: CancellationToken,
     ) -> Result<Self> {
-        // Sync config to database on startup
-        let feeds_clone = config.timeline_feeds.clone();
-        let pool_clone = pool.clone();
-
-        tokio::spawn(async move {
-            if let Err(e) = user_storage::sync_config_to_db(&pool_clone, &feeds_clone).await {
-                tracing::error!(error = ?e, "Failed to sync timeline config to database");
-            }
-        });
-
-        let http_client = reqwest::Client::builder()
-            .user_agent(&config.user_agent)
+        let http_client = client_builder
             .timeout(std::time::Duration::from_secs(30))
             .build()
             .context("Failed to build HTTP client")?;
@@ -74,6 +112,12 @@ impl TimelineConsumerTask {
             config,
             http_client,
             cancellation_token,
+            feed_cache,
+            resolved_handles: HashMap::new(),
+            failed_handles: HashSet::new(),
+            starter_packs: HashMap::new(),
+            webhooks,
+            feed_events,
         })
     }
 
@@ -85,41 +129,202 @@ impl TimelineConsumerTask {
         );
 
         if self.config.timeline_feeds.is_empty() {
-            tracing::warn!("No timeline feeds configured, consumer will idle");
+            tracing::info!("No timeline feeds configured, polling self-service feeds only");
+        }
+
+        // Resolve handles before syncing, since the database is keyed by DID
+        self.resolve_handles().await;
+        let mut last_handle_refresh = chrono::Utc::now();
+
+        if let Err(e) =
+            user_storage::sync_config_to_db(&self.pool, &self.config.timeline_feeds).await
+        {
+            tracing::error!(error = ?e, "Failed to sync timeline config to database");
         }
 
         loop {
-            // Check for cancellation
-            if self.cancellation_token.is_cancelled() {
-                tracing::info!("TimelineConsumerTask cancelled");
-                break;
+            if chrono::Utc::now() - last_handle_refresh >= self.config.handle_refresh_interval {
+                self.resolve_handles().await;
+                last_handle_refresh = chrono::Utc::now();
             }
 
             // Run poll cycle
             self.poll_cycle().await;
+
+            tokio::select! {
+                () = self.cancellation_token.cancelled() => {
+                    tracing::info!("TimelineConsumerTask cancelled");
+                    break;
+                }
+                () = tokio::time::sleep(POLL_CYCLE_TICK) => {}
+            }
         }
 
         Ok(())
     }
 
+    /// Resolve handles used as feed owners or in blocked_reposters to DIDs
+    ///
+    /// Feed owners given as handles are resolved once and replaced by their DID;
+    /// feeds whose owner cannot be resolved are dropped. Reposter handles and
+    /// starter pack members fall back to their last successful lookup when a
+    /// refresh fails.
+    async fn resolve_handles(&mut self) {
+        let feeds = std::mem::take(&mut self.config.timeline_feeds.timeline_feeds);
+        let mut resolved_feeds = Vec::with_capacity(feeds.len());
+        let mut refreshed: HashSet<String> = HashSet::new();
+        let mut refreshed_packs: HashSet<String> = HashSet::new();
+        self.failed_handles.clear();
+
+        for mut feed in feeds {
+            if !feed.did.starts_with("did:") {
+                match resolve_handle(&self.http_client, &feed.oauth.pds_url, &feed.did).await {
+                    Ok(did) => {
+                        tracing::info!(handle = %feed.did, did = %did, "Resolved feed owner handle");
+                        feed.did = did;
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            handle = %feed.did,
+                            feed_uri = %feed.feed_uri,
+                            error = ?e,
+                            "Failed to resolve feed owner handle, skipping feed"
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            let pds_url = feed.oauth.pds_url.clone();
+            let user_did = feed.did.clone();
+            for (_, filters) in feed.feed_targets_mut() {
+                let handles: Vec<String> = filters
+                    .reposter_handles()
+                    .map(|handle| normalize_handle(handle))
+                    .collect();
+
+                let mut resolved_reposters = HashSet::new();
+                for handle in handles {
+                    if refreshed.insert(handle.clone()) {
+                        match resolve_handle(&self.http_client, &pds_url, &handle).await {
+                            Ok(did) => {
+                                self.resolved_handles.insert(handle.clone(), did);
+                            }
+                            Err(e) => {
+                                self.failed_handles.insert(handle.clone());
+                                tracing::warn!(
+                                    handle = %handle,
+                                    user_did = %user_did,
+                                    error = ?e,
+                                    "Failed to resolve blocked_reposters handle"
+                                );
+                            }
+                        }
+                    }
+
+                    if let Some(did) = self.resolved_handles.get(&handle) {
+                        resolved_reposters.insert(did.clone());
+                    }
+                }
+                filters.resolved_reposters = resolved_reposters;
+            }
+
+            let starter_packs: HashSet<String> = feed
+                .feed_targets()
+                .flat_map(|(_, filters)| filters.blocked_reposter_starter_packs.iter().cloned())
+                .collect();
+            for starter_pack in starter_packs {
+                if !refreshed_packs.insert(starter_pack.clone()) {
+                    continue;
+                }
+                match starter_pack_members(
+                    &self.http_client,
+                    &pds_url,
+                    &feed.oauth.access_token,
+                    &starter_pack,
+                )
+                .await
+                {
+                    Ok(members) => {
+                        tracing::info!(
+                            starter_pack = %starter_pack,
+                            members = members.len(),
+                            "Loaded starter pack members"
+                        );
+                        self.starter_packs
+                            .insert(starter_pack, members.into_iter().collect());
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            starter_pack = %starter_pack,
+                            user_did = %user_did,
+                            error = ?e,
+                            "Failed to load starter pack members"
+                        );
+                    }
+                }
+            }
+            for (_, filters) in feed.feed_targets_mut() {
+                filters.starter_pack_members = filters
+                    .blocked_reposter_starter_packs
+                    .iter()
+                    .filter_map(|starter_pack| self.starter_packs.get(starter_pack))
+                    .flatten()
+                    .cloned()
+                    .collect();
+            }
+
+            resolved_feeds.push(feed);
+        }
+
+        self.config.timeline_feeds.timeline_feeds = resolved_feeds;
+    }
+
     /// Execute one polling cycle for all users IN PARALLEL
     /// Uses dual-track polling like Bluesky's Following feed:
-    /// - Track 1: New posts (60s interval, no cursor) - always runs
+    /// - Track 1: New posts (feed poll_interval, else POLL_INTERVAL, no cursor) - always runs
     /// - Track 2: Backfill (10s interval, with cursor) - runs until backfill_limit reached
     async fn poll_cycle(&mut self) {
-        let feeds = self.config.timeline_feeds.timeline_feeds.clone();
+        let mut feeds = self.config.timeline_feeds.timeline_feeds.clone();
+
+        // Self-service feeds live in the database and are picked up without a restart
+        match user_storage::get_web_feeds(&self.pool).await {
+            Ok(web_feeds) => {
+                for web_feed in web_feeds {
+                    if !feeds.iter().any(|feed| feed.did == web_feed.did) {
+                        feeds.push(web_feed);
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "Failed to load self-service feeds");
+            }
+        }
+
+        // Tokens and filters may have changed in the database since the last cycle
+        for feed in feeds.iter_mut() {
+            if let Err(e) = self.reload_tokens(feed).await {
+                tracing::warn!(
+                    user_did = %feed.did,
+                    error = ?e,
+                    "Failed to reload tokens from database, using previous tokens"
+                );
+            }
+            if let Err(e) = self.reload_filters(feed).await {
+                tracing::warn!(
+                    user_did = %feed.did,
+                    error = ?e,
+                    "Failed to reload filters from database, using previous filters"
+                );
+            }
+        }
 
         // Poll all users in parallel using tokio::spawn
         let mut tasks = Vec::new();
 
         for feed in feeds {
-            let pool = self.pool.clone();
-            let http_client = self.http_client.clone();
-            let user_agent = self.config.user_agent.clone();
-
-            let task = tokio::spawn(async move {
-                Self::poll_single_user(pool, feed, http_client, user_agent).await
-            });
+            let user_task = self.for_user(&feed);
+            let task = tokio::spawn(Self::poll_single_user(user_task, feed));
 
             tasks.push(task);
         }
@@ -136,28 +341,111 @@ impl TimelineConsumerTask {
         }
     }
 
-    /// Poll a single user's timeline (both new posts and backfill)
-    async fn poll_single_user(
-        pool: StoragePool,
-        mut feed: TimelineFeed,
-        http_client: reqwest::Client,
-        user_agent: String,
-    ) {
-        // Create a temporary task instance for this user
+    /// Use the tokens stored in the database, which every refresh rotates
+    ///
+    /// The config file only seeds them, so its tokens go stale after the first
+    /// refresh.
+    async fn reload_tokens(&self, feed: &mut TimelineFeed) -> Result<()> {
+        if let Some(stored) = user_storage::get_user_config(&self.pool, &feed.did).await? {
+            feed.oauth.access_token = stored.access_token;
+            feed.oauth.refresh_token = stored.refresh_token;
+            feed.oauth.expires_at = stored.token_expires_at;
+        }
+        Ok(())
+    }
+
+    /// Replace a feed's filters with the ones stored in the database
+    ///
+    /// Handles are mapped to DIDs using the last handle resolution. Handles seen
+    /// for the first time (e.g. added by SQL or admin tooling) are resolved right
+    /// away instead of waiting for the next handle refresh.
+    async fn reload_filters(&mut self, feed: &mut TimelineFeed) -> Result<()> {
+        let pds_url = feed.oauth.pds_url.clone();
+        let user_did = feed.did.clone();
+
+        for (feed_uri, filters) in feed.feed_targets_mut() {
+            let stored = user_storage::get_feed_filters(&self.pool, feed_uri).await?;
+            let blocked_reposters: HashSet<String> = stored.blocked_reposters.into_iter().collect();
+            if blocked_reposters != filters.blocked_reposters {
+                tracing::debug!(
+                    user_did = %user_did,
+                    feed_uri = %feed_uri,
+                    blocked_reposters = blocked_reposters.len(),
+                    "Filters changed in database"
+                );
+            }
+            filters.blocked_reposters = blocked_reposters;
+            filters.muted_threads = stored.muted_threads.into_iter().collect();
+            filters.hide_all_reposts = stored.hide_all_reposts;
+            filters.max_reposts_per_day = stored.max_reposts_per_day;
+
+            let mut resolved_reposters = HashSet::new();
+            let handles: Vec<String> = filters.reposter_handles().map(|h| normalize_handle(h)).collect();
+            for handle in handles {
+                if !self.resolved_handles.contains_key(&handle)
+                    && !self.failed_handles.contains(&handle)
+                {
+                    match resolve_handle(&self.http_client, &pds_url, &handle).await {
+                        Ok(did) => {
+                            self.resolved_handles.insert(handle.clone(), did);
+                        }
+                        Err(e) => {
+                            self.failed_handles.insert(handle.clone());
+                            tracing::warn!(
+                                handle = %handle,
+                                user_did = %user_did,
+                                error = ?e,
+                                "Failed to resolve blocked_reposters handle"
+                            );
+                        }
+                    }
+                }
+
+                if let Some(did) = self.resolved_handles.get(&handle) {
+                    resolved_reposters.insert(did.clone());
+                }
+            }
+            filters.resolved_reposters = resolved_reposters;
+        }
+
+        Ok(())
+    }
+
+    /// A task polling only this user's timeline, sharing this task's pool and clients
+    fn for_user(&self, feed: &TimelineFeed) -> TimelineConsumerTask {
+        let aggregate_feeds: Vec<AggregateFeed> = self
+            .config
+            .timeline_feeds
+            .aggregates_for(&feed.did)
+            .cloned()
+            .collect();
+
         // Note: We pass a dummy cancellation token since we don't need it here
-        let mut task = TimelineConsumerTask {
-            pool,
+        TimelineConsumerTask {
+            pool: self.pool.clone(),
             config: TimelineConsumerConfig {
                 timeline_feeds: TimelineFeeds {
                     timeline_feeds: vec![feed.clone()],
+                    aggregate_feeds,
                 },
-                default_poll_interval: Duration::seconds(10),
-                user_agent,
+                default_poll_interval: self.config.default_poll_interval,
+                handle_refresh_interval: Duration::zero(),
+                store_post_metadata: self.config.store_post_metadata,
+                validate_cids: self.config.validate_cids,
             },
-            http_client,
-            cancellation_token: tokio_util::sync::CancellationToken::new(),
-        };
+            http_client: self.http_client.clone(),
+            cancellation_token: CancellationToken::new(),
+            feed_cache: self.feed_cache.clone(),
+            resolved_handles: HashMap::new(),
+            failed_handles: HashSet::new(),
+            starter_packs: HashMap::new(),
+            webhooks: self.webhooks.clone(),
+            feed_events: self.feed_events.clone(),
+        }
+    }
 
+    /// Poll a single user's timeline (both new posts and backfill)
+    async fn poll_single_user(mut task: TimelineConsumerTask, mut feed: TimelineFeed) {
         // Check if backfill is still needed
         let needs_backfill = match user_storage::needs_backfill(&task.pool, &feed.did, feed.backfill_limit).await {
             Ok(needs) => needs,
@@ -171,8 +459,36 @@ impl TimelineConsumerTask {
             }
         };
 
-        // TRACK 1: New posts polling (60s interval, always active)
-        let new_posts_interval = Duration::seconds(60);
+        // Users failing to poll back off, and stop entirely until their tokens change
+        let poll_error = match user_storage::get_poll_error(&task.pool, &feed.did).await {
+            Ok(poll_error) => poll_error,
+            Err(e) => {
+                tracing::error!(
+                    user_did = %feed.did,
+                    error = ?e,
+                    "Failed to check poll error state"
+                );
+                return;
+            }
+        };
+        if let Some(poll_error) = &poll_error {
+            if poll_error.is_suspended() {
+                tracing::trace!(user_did = %feed.did, "Skipping suspended user");
+                return;
+            }
+            if !poll_error.can_retry(chrono::Utc::now()) {
+                tracing::trace!(
+                    user_did = %feed.did,
+                    retry_at = %poll_error.retry_at,
+                    "Skipping user - backing off after failed polls"
+                );
+                return;
+            }
+        }
+        let mut failing = poll_error.is_some();
+
+        // TRACK 1: New posts polling (always active)
+        let new_posts_interval = new_posts_interval(&feed, task.config.default_poll_interval);
         match user_storage::should_poll(&task.pool, &feed.did, new_posts_interval).await {
             Ok(true) => {
                 // Poll WITHOUT cursor to get newest posts
@@ -182,6 +498,17 @@ impl TimelineConsumerTask {
                         error = ?e,
                         "Failed to poll new posts"
                     );
+                    task.record_poll_failure(&feed.did, &e).await;
+                    return;
+                }
+                task.clear_poll_failures(&feed.did, &mut failing).await;
+
+                if let Err(e) = task.repair_gap(&mut feed).await {
+                    tracing::error!(
+                        user_did = %feed.did,
+                        error = ?e,
+                        "Failed to repair timeline gap"
+                    );
                 }
             }
             Ok(false) => {
@@ -216,7 +543,10 @@ impl TimelineConsumerTask {
                             error = ?e,
                             "Failed to poll backfill"
                         );
+                        task.record_poll_failure(&feed.did, &e).await;
+                        return;
                     }
+                    task.clear_poll_failures(&feed.did, &mut failing).await;
                 }
                 Ok(false) => {
                     tracing::trace!(
@@ -235,6 +565,61 @@ impl TimelineConsumerTask {
         }
     }
 
+    /// Count a failed poll towards the user's backoff and suspension
+    async fn record_poll_failure(&self, user_did: &str, error: &anyhow::Error) {
+        // Retrying a revoked session only spams the PDS
+        if error.downcast_ref::<SessionRevoked>().is_some() {
+            match user_storage::mark_reauth_required(&self.pool, user_did, &format!("{:#}", error)).await {
+                Ok(()) => tracing::warn!(
+                    user_did = %user_did,
+                    error = %error,
+                    "Session revoked, polling stopped until the user logs in again"
+                ),
+                Err(e) => {
+                    tracing::error!(user_did = %user_did, error = ?e, "Failed to record revoked session");
+                }
+            }
+            return;
+        }
+
+        match user_storage::record_poll_failure(
+            &self.pool,
+            user_did,
+            &format!("{:#}", error),
+            poll_failure_backoff,
+        )
+        .await
+        {
+            Ok(failures) if failures == user_storage::POLL_SUSPEND_AFTER => {
+                tracing::warn!(
+                    user_did = %user_did,
+                    failures = failures,
+                    "Suspending polls for user until their tokens are updated"
+                );
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!(user_did = %user_did, error = ?e, "Failed to record poll failure");
+            }
+        }
+    }
+
+    /// Reset the failure count after a successful poll of a failing user
+    async fn clear_poll_failures(&self, user_did: &str, failing: &mut bool) {
+        if !*failing {
+            return;
+        }
+        match user_storage::clear_poll_failures(&self.pool, user_did).await {
+            Ok(()) => {
+                tracing::info!(user_did = %user_did, "Polling recovered");
+                *failing = false;
+            }
+            Err(e) => {
+                tracing::error!(user_did = %user_did, error = ?e, "Failed to clear poll failures");
+            }
+        }
+    }
+
     /// Poll timeline in specific mode (backfill=true uses cursor, backfill=false gets newest)
     async fn poll_timeline_mode(&mut self, feed: &mut TimelineFeed, is_backfill: bool) -> Result<()> {
         tracing::debug!(
@@ -272,16 +657,170 @@ impl TimelineConsumerTask {
         };
 
         // 3. Fetch timeline from AT Protocol
-        let timeline = self
-            .fetch_timeline(feed, cursor, feed.max_posts_per_poll)
+        let timeline = if is_backfill {
+            self.fetch_timeline(feed, cursor, feed.max_posts_per_poll).await
+        } else {
+            self.fetch_new_posts(feed).await
+        }
+        .context("Failed to fetch timeline")?;
+
+        // 3. Filter and index posts into every feed built from this timeline
+        let (blocked_count, IndexCounts { new_posts, updated_posts, reposts }) =
+            self.index_timeline(feed, &timeline.feed).await?;
+
+        let total_processed = new_posts + updated_posts;
+
+        // 5. Update poll state in database (separate for each mode)
+        if is_backfill {
+            // BACKFILL MODE: Save cursor and update backfill state
+            user_storage::update_poll_state(
+                &self.pool,
+                &feed.did,
+                timeline.cursor.as_deref(),
+                new_posts, // Only count NEW posts, not duplicates
+                blocked_count as i32,
+            )
             .await
-            .context("Failed to fetch timeline")?;
+            .context("Failed to update backfill poll state")?;
 
-        // 3. Filter posts based on user's filter config
-        let filtered = self.filter_posts(&timeline.feed, &feed.filters);
-        let blocked_count = timeline.feed.len() - filtered.len();
+            user_storage::update_poll_state_backfill(
+                &self.pool,
+                &feed.did,
+                new_posts, // Only count NEW posts, not duplicates
+            )
+            .await
+            .context("Failed to update backfill tracking")?;
+        } else {
+            // NEW POSTS MODE: Update new posts state (no cursor saved)
+            user_storage::update_poll_state(
+                &self.pool,
+                &feed.did,
+                None, // Never save cursor in new posts mode
+                new_posts, // Only count NEW posts, not duplicates
+                blocked_count as i32,
+            )
+            .await
+            .context("Failed to update new posts poll state")?;
+        }
+
+        // Get feed stats for logging
+        let stats = user_storage::get_feed_stats(&self.pool, &feed.feed_uri)
+            .await
+            .unwrap_or(user_storage::FeedStats {
+                total_posts: 0,
+                total_reposts: 0,
+                total_blocked: 0,
+            });
 
-        // 4. Index filtered posts into feed_content table
+        tracing::info!(
+            user_did = %feed.did,
+            mode = if is_backfill { "backfill" } else { "new_posts" },
+            "Poll: fetched={}, blocked={}, indexed={} (new={}, reposts={}, dupes={}), total_db={} (reposts={}, blocked={})",
+            timeline.feed.len(),
+            blocked_count,
+            total_processed,
+            new_posts,
+            reposts,
+            updated_posts,
+            stats.total_posts,
+            stats.total_reposts,
+            stats.total_blocked,
+        );
+
+        Ok(())
+    }
+
+    /// Filter and index timeline items into the user's feeds and aggregate feeds
+    ///
+    /// The primary feed comes first; its blocked and indexed counts are returned
+    /// and drive the poll state.
+    async fn index_timeline(
+        &self,
+        feed: &TimelineFeed,
+        items: &[FeedViewPost],
+    ) -> Result<(usize, IndexCounts)> {
+        let mut primary = None;
+        for (feed_uri, filters) in feed.feed_targets() {
+            let filtered = self.filter_posts(items, filters);
+            let filtered = self
+                .apply_denylist(filtered, feed_uri)
+                .await
+                .context("Failed to apply denylist")?;
+            let filtered = self
+                .apply_repost_limit(filtered, feed_uri, filters)
+                .await
+                .context("Failed to apply repost limit")?;
+            let blocked = items.len() - filtered.len();
+
+            let counts = self
+                .index_posts(feed_uri, &feed.did, filtered, feed.webhook_for(feed_uri))
+                .await;
+            if counts.new_posts > 0 {
+                if let Err(e) = self.feed_cache.refresh(feed_uri).await {
+                    tracing::warn!(feed_uri = %feed_uri, error = ?e, "Failed to refresh feed cache");
+                }
+            }
+
+            if primary.is_none() {
+                primary = Some((blocked, counts));
+            } else {
+                tracing::debug!(
+                    user_did = %feed.did,
+                    feed_uri = %feed_uri,
+                    blocked = blocked,
+                    new = counts.new_posts,
+                    "Indexed feed variant"
+                );
+            }
+        }
+        let primary = primary.unwrap_or_default();
+
+        // Aggregate feeds get this user's primary feed; posts already merged from
+        // another source are kept once
+        for aggregate in &self.config.timeline_feeds.aggregate_feeds {
+            let filtered = self.filter_posts(items, &feed.filters);
+            let filtered = self
+                .apply_denylist(filtered, &aggregate.feed_uri)
+                .await
+                .context("Failed to apply denylist")?;
+            let filtered = self
+                .apply_repost_limit(filtered, &aggregate.feed_uri, &feed.filters)
+                .await
+                .context("Failed to apply repost limit")?;
+
+            let counts = self
+                .index_posts(
+                    &aggregate.feed_uri,
+                    &feed.did,
+                    filtered,
+                    aggregate.webhook_url.as_deref(),
+                )
+                .await;
+            if counts.new_posts > 0 {
+                if let Err(e) = self.feed_cache.refresh(&aggregate.feed_uri).await {
+                    tracing::warn!(feed_uri = %aggregate.feed_uri, error = ?e, "Failed to refresh feed cache");
+                }
+            }
+            tracing::debug!(
+                user_did = %feed.did,
+                feed_uri = %aggregate.feed_uri,
+                new = counts.new_posts,
+                "Indexed into aggregate feed"
+            );
+        }
+
+        Ok(primary)
+    }
+
+    /// Index filtered posts into one feed, counting new posts, duplicates and reposts
+    /// New posts are also queued for the feed's webhook, if it has one
+    async fn index_posts(
+        &self,
+        feed_uri: &str,
+        source_did: &str,
+        filtered: Vec<&FeedViewPost>,
+        webhook_url: Option<&str>,
+    ) -> IndexCounts {
         let mut new_posts = 0;
         let mut updated_posts = 0;
         let mut reposts = 0;
@@ -351,21 +890,66 @@ impl TimelineConsumerTask {
                 }
             };
 
-            match feed_content_upsert(
-                &self.pool,
-                &FeedContent {
-                    feed_id: feed.feed_uri.clone(),
-                    uri,
-                    indexed_at,
-                    score: 1,
-                    is_repost,
-                    repost_uri,
-                },
-            )
-            .await
-            {
-                Ok(true) => new_posts += 1,      // New post inserted
-                Ok(false) => updated_posts += 1, // Duplicate post skipped
+            let checked = AtUri::parse(&uri)
+                .and_then(|_| repost_uri.as_deref().map(AtUri::parse).transpose());
+            if let Err(e) = checked {
+                tracing::warn!(
+                    uri = %uri,
+                    error = ?e,
+                    "Malformed AT-URI, skipping post"
+                );
+                continue;
+            }
+
+            let cid = post_view.post.cid.clone();
+            if self.config.validate_cids {
+                if let Some(Err(e)) = cid.as_deref().map(validate_record_cid) {
+                    tracing::warn!(
+                        uri = %uri,
+                        error = ?e,
+                        "Malformed CID, skipping post"
+                    );
+                    continue;
+                }
+            }
+
+            let content = FeedContent {
+                feed_id: feed_uri.to_string(),
+                uri,
+                indexed_at,
+                score: 1,
+                is_repost,
+                repost_uri,
+                source_did: Some(source_did.to_string()),
+                cid,
+            };
+            match feed_content_upsert(&self.pool, &content).await {
+                Ok(Upserted::Inserted) => {
+                    // New post inserted
+                    new_posts += 1;
+                    if self.config.store_post_metadata {
+                        self.store_post_metadata(post_view).await;
+                    }
+                    if let Some(webhook_url) = webhook_url {
+                        self.webhooks.push(webhook_url, webhook_post(&content, post_view));
+                    }
+                    self.feed_events.publish(IndexedPost {
+                        feed: content.feed_id.clone(),
+                        uri: content.uri.clone(),
+                        repost_uri: content.repost_uri.clone(),
+                        indexed_at: content.indexed_at,
+                    });
+                }
+                Ok(Upserted::Duplicate) => updated_posts += 1, // Duplicate post skipped
+                Ok(Upserted::Edited) => {
+                    tracing::info!(
+                        uri = %content.uri,
+                        feed_uri = %feed_uri,
+                        cid = ?content.cid,
+                        "Post was edited, stored its new CID"
+                    );
+                    updated_posts += 1;
+                }
                 Err(e) => {
                     tracing::error!(
                         uri = %post_view.post.uri,
@@ -376,63 +960,122 @@ impl TimelineConsumerTask {
             }
         }
 
-        let total_processed = new_posts + updated_posts;
 
-        // 5. Update poll state in database (separate for each mode)
-        if is_backfill {
-            // BACKFILL MODE: Save cursor and update backfill state
-            user_storage::update_poll_state(
-                &self.pool,
-                &feed.did,
-                timeline.cursor.as_deref(),
-                new_posts, // Only count NEW posts, not duplicates
-                blocked_count as i32,
-            )
-            .await
-            .context("Failed to update backfill poll state")?;
+        IndexCounts {
+            new_posts,
+            updated_posts,
+            reposts,
+        }
+    }
+
+    /// Store the text, author and embed type of a post, logging failures
+    async fn store_post_metadata(&self, post_view: &FeedViewPost) {
+        let author = post_view.post.author.as_ref();
+        let embed_type = post_view
+            .post
+            .record
+            .as_ref()
+            .and_then(|record| record.get("embed"))
+            .and_then(|embed| embed.get("$type"))
+            .and_then(|embed_type| embed_type.as_str());
+
+        let metadata = PostMetadata {
+            uri: post_view.post.uri.clone(),
+            text: post_text(post_view).unwrap_or_default().to_string(),
+            author_did: author.map(|author| author.did.clone()),
+            author_handle: author.and_then(|author| author.handle.clone()),
+            embed_type: embed_type.map(str::to_string),
+        };
+        if let Err(e) = post_metadata_upsert(&self.pool, &metadata).await {
+            tracing::warn!(uri = %post_view.post.uri, error = ?e, "Failed to store post metadata");
+        }
+    }
+
+    /// Fetch the newest posts, following the cursor while every post is still unseen
+    ///
+    /// A burst of more than max_posts_per_poll posts between two polls would
+    /// otherwise leave a gap. Paging stops at the newest post already in the
+    /// primary feed or after NEW_POSTS_MAX_PAGES pages, in which case the rest is
+    /// saved as a gap for repair_gap. A feed with no posts yet gets one page and
+    /// leaves the rest to backfill.
+    async fn fetch_new_posts(&self, feed: &TimelineFeed) -> Result<TimelineResponse> {
+        let mut timeline = self
+            .fetch_timeline(feed, None, feed.max_posts_per_poll)
+            .await?;
+
+        let Some(newest_indexed) =
+            user_storage::get_newest_indexed_at(&self.pool, &feed.feed_uri).await?
+        else {
+            return Ok(timeline);
+        };
+
+        let mut pages = 1;
+        while let Some(cursor) = timeline.cursor.take() {
+            let oldest = timeline.feed.iter().filter_map(item_indexed_at).min();
+            if oldest.is_none_or(|oldest| oldest <= newest_indexed) {
+                break;
+            }
+            if pages >= NEW_POSTS_MAX_PAGES {
+                tracing::info!(
+                    user_did = %feed.did,
+                    pages = pages,
+                    "New posts still unseen after page cap, saving gap for repair"
+                );
+                user_storage::save_poll_gap(&self.pool, &feed.did, &cursor, newest_indexed)
+                    .await?;
+                break;
+            }
+
+            let page = self
+                .fetch_timeline(feed, Some(cursor), feed.max_posts_per_poll)
+                .await?;
+            pages += 1;
+            if page.feed.is_empty() {
+                break;
+            }
+            timeline.feed.extend(page.feed);
+            timeline.cursor = page.cursor;
+        }
+
+        if pages > 1 {
+            tracing::debug!(
+                user_did = %feed.did,
+                pages = pages,
+                fetched = timeline.feed.len(),
+                "Followed cursor to catch up on new posts"
+            );
+        }
+
+        Ok(timeline)
+    }
+
+    /// Fill one page of the oldest open gap in a user's timeline
+    ///
+    /// The gap closes once a page reaches the newest post indexed before it
+    /// opened, or the timeline runs out.
+    async fn repair_gap(&mut self, feed: &mut TimelineFeed) -> Result<()> {
+        let Some(gap) = user_storage::get_poll_gap(&self.pool, &feed.did).await? else {
+            return Ok(());
+        };
 
-            user_storage::update_poll_state_backfill(
-                &self.pool,
-                &feed.did,
-                new_posts, // Only count NEW posts, not duplicates
-            )
-            .await
-            .context("Failed to update backfill tracking")?;
-        } else {
-            // NEW POSTS MODE: Update new posts state (no cursor saved)
-            user_storage::update_poll_state(
-                &self.pool,
-                &feed.did,
-                None, // Never save cursor in new posts mode
-                new_posts, // Only count NEW posts, not duplicates
-                blocked_count as i32,
-            )
+        self.ensure_valid_token(feed).await?;
+        let timeline = self
+            .fetch_timeline(feed, Some(gap.cursor.clone()), feed.max_posts_per_poll)
             .await
-            .context("Failed to update new posts poll state")?;
-        }
+            .context("Failed to fetch timeline gap")?;
 
-        // Get feed stats for logging
-        let stats = user_storage::get_feed_stats(&self.pool, &feed.feed_uri)
-            .await
-            .unwrap_or(user_storage::FeedStats {
-                total_posts: 0,
-                total_reposts: 0,
-                total_blocked: 0,
-            });
+        let (_, counts) = self.index_timeline(feed, &timeline.feed).await?;
+
+        let oldest = timeline.feed.iter().filter_map(item_indexed_at).min();
+        let closed = oldest.is_none_or(|oldest| oldest <= gap.until_indexed_at);
+        let next_cursor = if closed { None } else { timeline.cursor.as_deref() };
+        user_storage::update_poll_gap(&self.pool, gap.id, next_cursor).await?;
 
         tracing::info!(
             user_did = %feed.did,
-            mode = if is_backfill { "backfill" } else { "new_posts" },
-            "Poll: fetched={}, blocked={}, indexed={} (new={}, reposts={}, dupes={}), total_db={} (reposts={}, blocked={})",
-            timeline.feed.len(),
-            blocked_count,
-            total_processed,
-            new_posts,
-            reposts,
-            updated_posts,
-            stats.total_posts,
-            stats.total_reposts,
-            stats.total_blocked,
+            new = counts.new_posts,
+            closed = next_cursor.is_none(),
+            "Repaired timeline gap"
         );
 
         Ok(())
@@ -471,13 +1114,27 @@ impl TimelineConsumerTask {
             .await
             .context("Failed to send getTimeline request")?;
 
+        if let Some(rate_limit) = parse_rate_limit(&feed.oauth.pds_url, response.headers()) {
+            if rate_limit.remaining * 10 < rate_limit.rate_limit {
+                tracing::warn!(
+                    pds = %rate_limit.pds_host,
+                    remaining = rate_limit.remaining,
+                    limit = rate_limit.rate_limit,
+                    "PDS rate limit almost used up"
+                );
+            }
+            if let Err(err) = user_storage::pds_rate_limit_upsert(&self.pool, &rate_limit).await {
+                tracing::warn!(error = ?err, "Failed to store PDS rate limit");
+            }
+        }
+
         let status = response.status();
         if !status.is_success() {
             let body = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "(failed to read body)".to_string());
-            anyhow::bail!("getTimeline failed: {} - {}", status, body);
+            anyhow::bail!("getTimeline failed: {} - {}", status, redact_tokens(&body));
         }
 
         // Get body as text first for better error messages
@@ -496,7 +1153,7 @@ impl TimelineConsumerTask {
                 };
                 tracing::error!(
                     error = %e,
-                    response_preview = %preview,
+                    response_preview = %redact_tokens(&preview),
                     "Failed to parse getTimeline response"
                 );
                 e
@@ -519,9 +1176,8 @@ impl TimelineConsumerTask {
             let expires = chrono::DateTime::parse_from_rfc3339(expires_at)
                 .context("Failed to parse token expiration")?;
             let now = chrono::Utc::now();
-            let buffer = chrono::Duration::minutes(5);
 
-            if expires.signed_duration_since(now) < buffer {
+            if expires.signed_duration_since(now) < TOKEN_REFRESH_BUFFER {
                 tracing::info!(
                     user_did = %feed.did,
                     expires_at = %expires_at,
@@ -543,8 +1199,80 @@ impl TimelineConsumerTask {
         Ok(())
     }
 
-    /// Refresh the OAuth access token using the refresh token
+    /// Refresh the OAuth access token, unless another poller is already doing so
+    ///
+    /// refreshSession rotates the refresh token, so two concurrent refreshes
+    /// invalidate each other. The claim in the database lets one poller refresh
+    /// while the others wait for its tokens.
     async fn refresh_token(&self, feed: &mut TimelineFeed) -> Result<()> {
+        let claimed = user_storage::claim_token_refresh(
+            &self.pool,
+            &feed.did,
+            chrono::Utc::now(),
+            TOKEN_REFRESH_LEASE,
+        )
+        .await?;
+        if !claimed {
+            return self.wait_for_token_refresh(feed).await;
+        }
+
+        // Tokens may have been refreshed since this poller loaded them
+        if self.adopt_stored_tokens(feed).await? {
+            user_storage::release_token_refresh(&self.pool, &feed.did).await?;
+            return Ok(());
+        }
+
+        let result = self.refresh_session(feed).await;
+        if result.is_err() {
+            if let Err(e) = user_storage::release_token_refresh(&self.pool, &feed.did).await {
+                tracing::error!(user_did = %feed.did, error = ?e, "Failed to release token refresh");
+            }
+        }
+        result
+    }
+
+    /// Wait for the poller holding the refresh claim to store new tokens
+    async fn wait_for_token_refresh(&self, feed: &mut TimelineFeed) -> Result<()> {
+        tracing::debug!(user_did = %feed.did, "Token refresh in progress elsewhere, waiting");
+
+        let deadline = chrono::Utc::now() + TOKEN_REFRESH_LEASE;
+        while chrono::Utc::now() < deadline {
+            // Jitter keeps waiting pollers from checking in lockstep
+            let jitter = rand::thread_rng().gen_range(0..TOKEN_REFRESH_WAIT_JITTER_MS);
+            tokio::time::sleep(TOKEN_REFRESH_WAIT + std::time::Duration::from_millis(jitter)).await;
+
+            if self.adopt_stored_tokens(feed).await? {
+                return Ok(());
+            }
+        }
+
+        anyhow::bail!("Timed out waiting for another poller to refresh the token")
+    }
+
+    /// Take over fresh tokens another poller stored, returning whether there were any
+    async fn adopt_stored_tokens(&self, feed: &mut TimelineFeed) -> Result<bool> {
+        let Some(stored) = user_storage::get_user_config(&self.pool, &feed.did).await? else {
+            return Ok(false);
+        };
+        let fresh = stored
+            .token_expires_at
+            .as_deref()
+            .and_then(|expires_at| chrono::DateTime::parse_from_rfc3339(expires_at).ok())
+            .is_some_and(|expires| expires.signed_duration_since(chrono::Utc::now()) >= TOKEN_REFRESH_BUFFER);
+        if !fresh || stored.access_token == feed.oauth.access_token {
+            return Ok(false);
+        }
+
+        tracing::debug!(user_did = %feed.did, "Using tokens refreshed by another poller");
+        feed.oauth.access_token = stored.access_token;
+        feed.oauth.refresh_token = stored.refresh_token;
+        feed.oauth.expires_at = stored.token_expires_at;
+        feed.oauth.pds_url = stored.pds_url;
+        Ok(true)
+    }
+
+    /// Exchange the refresh token for new tokens with refreshSession
+    async fn refresh_session(&self, feed: &mut TimelineFeed) -> Result<()> {
         let refresh_token = feed.oauth.refresh_token.as_ref()
             .ok_or_else(|| anyhow::anyhow!("No refresh token available"))?;
 
@@ -567,6 +1295,10 @@ impl TimelineConsumerTask {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_else(|_| "(failed to read body)".to_string());
+            let body = redact_tokens(&body).into_owned();
+            if status == reqwest::StatusCode::BAD_REQUEST || status == reqwest::StatusCode::UNAUTHORIZED {
+                return Err(SessionRevoked { status, body }.into());
+            }
             anyhow::bail!("Token refresh failed: {} - {}", status, body);
         }
 
@@ -654,6 +1386,75 @@ impl TimelineConsumerTask {
         Self::filter_posts_static(posts, filters)
     }
 
+    /// Drop posts whose uri, author, or reposter is denylisted globally or for this feed
+    async fn apply_denylist<'a>(
+        &self,
+        posts: Vec<&'a FeedViewPost>,
+        feed_uri: &str,
+    ) -> Result<Vec<&'a FeedViewPost>> {
+        let mut subjects: Vec<&str> = Vec::new();
+        for post in &posts {
+            subjects.push(&post.post.uri);
+            if let Some(author) = &post.post.author {
+                subjects.push(&author.did);
+            }
+            if let Some(reason) = &post.reason {
+                subjects.push(&reason.by.did);
+            }
+        }
+        subjects.sort_unstable();
+        subjects.dedup();
+
+        let denied = denylist_matching(&self.pool, &subjects, &Some(feed_uri.to_string())).await?;
+        if denied.is_empty() {
+            return Ok(posts);
+        }
+
+        Ok(posts
+            .into_iter()
+            .filter(|post| {
+                let author_denied = post
+                    .post
+                    .author
+                    .as_ref()
+                    .is_some_and(|author| denied.contains(&author.did));
+                let reposter_denied = post
+                    .reason
+                    .as_ref()
+                    .is_some_and(|reason| denied.contains(&reason.by.did));
+                if author_denied || reposter_denied || denied.contains(&post.post.uri) {
+                    tracing::trace!(post_uri = %post.post.uri, "Filtered out denylisted post");
+                    return false;
+                }
+                true
+            })
+            .collect())
+    }
+
+    /// Drop reposts by accounts that already reached max_reposts_per_day in this feed
+    async fn apply_repost_limit<'a>(
+        &self,
+        posts: Vec<&'a FeedViewPost>,
+        feed_uri: &str,
+        filters: &FilterConfig,
+    ) -> Result<Vec<&'a FeedViewPost>> {
+        let Some(max) = filters.max_reposts_per_day else {
+            return Ok(posts);
+        };
+        let Some(oldest) = posts
+            .iter()
+            .filter(|post| post.reason.is_some())
+            .filter_map(|post| item_indexed_at(post))
+            .min()
+        else {
+            return Ok(posts);
+        };
+
+        let since = oldest - REPOST_LIMIT_WINDOW.num_microseconds().unwrap_or(i64::MAX);
+        let indexed = feed_content_reposts_since(&self.pool, feed_uri, since).await?;
+        Ok(limit_reposts(posts, indexed, max))
+    }
+
     /// Static version of filter_posts for testing
     fn filter_posts_static<'a>(
         posts: &'a [FeedViewPost],
@@ -662,6 +1463,20 @@ impl TimelineConsumerTask {
         posts
             .iter()
             .filter(|post| {
+                // Drop muted threads: the root post and every reply to it
+                let root_uri = post
+                    .reply
+                    .as_ref()
+                    .map_or(post.post.uri.as_str(), |reply| reply.root.uri.as_str());
+                if filters.is_thread_muted(root_uri) {
+                    tracing::trace!(
+                        post_uri = %post.post.uri,
+                        root_uri = %root_uri,
+                        "Filtered out post in muted thread"
+                    );
+                    return false;
+                }
+
                 // Check if it's a repost
                 if let Some(reason) = &post.reason {
                     // Parse the reason type
@@ -687,7 +1502,7 @@ impl TimelineConsumerTask {
 
 /// Extract PDS endpoint URL from DID document
 /// Follows the same logic as Bluesky's getPdsEndpoint() function
-fn extract_pds_endpoint(did_doc: &serde_json::Value) -> Option<String> {
+pub(crate) fn extract_pds_endpoint(did_doc: &serde_json::Value) -> Option<String> {
     // Look for service with id "#atproto_pds" and type "AtprotoPersonalDataServer"
     let services = did_doc.get("service")?.as_array()?;
 
@@ -709,6 +1524,22 @@ fn extract_pds_endpoint(did_doc: &serde_json::Value) -> Option<String> {
     None
 }
 
+/// refreshSession rejected the refresh token, e.g. because the app password was revoked
+#[derive(Debug, thiserror::Error)]
+#[error("Token refresh rejected: {status} - {body}")]
+pub struct SessionRevoked {
+    pub status: reqwest::StatusCode,
+    pub body: String,
+}
+
+/// Post counts from indexing one poll into a feed
+#[derive(Debug, Default, Clone, Copy)]
+struct IndexCounts {
+    new_posts: i32,
+    updated_posts: i32,
+    reposts: i32,
+}
+
 // AT Protocol Response Types
 
 /// Response from app.bsky.feed.getTimeline
@@ -815,6 +1646,153 @@ pub struct ReplyRef {
 
 // Helper functions
 
+/// Text of a post's record, if it has any
+fn post_text(post_view: &FeedViewPost) -> Option<&str> {
+    post_view
+        .post
+        .record
+        .as_ref()
+        .and_then(|record| record.get("text"))
+        .and_then(|text| text.as_str())
+}
+
+/// Webhook payload for a post just indexed into a feed
+fn webhook_post(content: &FeedContent, post_view: &FeedViewPost) -> WebhookPost {
+    let text = post_text(post_view).map(WebhookPost::snippet);
+
+    WebhookPost {
+        feed: content.feed_id.clone(),
+        uri: content.uri.clone(),
+        repost_uri: content.repost_uri.clone(),
+        author: post_view
+            .post
+            .author
+            .as_ref()
+            .map(|author| author.did.clone())
+            .unwrap_or_default(),
+        source: content.source_did.clone().unwrap_or_default(),
+        text,
+        indexed_at: content.indexed_at,
+    }
+}
+
+/// Wait after the first failed poll, doubled for each further failure
+const POLL_FAILURE_BACKOFF_BASE: Duration = Duration::seconds(30);
+
+/// Longest wait between retries of a failing user
+const POLL_FAILURE_BACKOFF_MAX: Duration = Duration::hours(1);
+
+/// Backoff after the given number of consecutive failed polls
+fn poll_failure_backoff(failures: i64) -> Duration {
+    let doublings = failures.saturating_sub(1).clamp(0, 16) as u32;
+    (POLL_FAILURE_BACKOFF_BASE * 2_i32.pow(doublings)).min(POLL_FAILURE_BACKOFF_MAX)
+}
+
+/// Rate limit budget from a PDS response's ratelimit-* headers, if it sent them
+fn parse_rate_limit(pds_url: &str, headers: &reqwest::header::HeaderMap) -> Option<PdsRateLimit> {
+    let header = |name: &str| -> Option<i64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+
+    Some(PdsRateLimit {
+        pds_host: reqwest::Url::parse(pds_url).ok()?.host_str()?.to_string(),
+        rate_limit: header("ratelimit-limit")?,
+        remaining: header("ratelimit-remaining")?,
+        reset_at: header("ratelimit-reset"),
+    })
+}
+
+/// Window over which max_reposts_per_day counts an account's reposts
+const REPOST_LIMIT_WINDOW: Duration = Duration::hours(24);
+
+/// Keep at most `max` reposts per reposter in any window ending at a repost
+///
+/// `indexed` holds the repost URIs already in the feed with their timestamps.
+/// Reposts already indexed always pass, so repeated polls don't use up the
+/// limit; new ones are counted oldest first.
+fn limit_reposts(
+    posts: Vec<&FeedViewPost>,
+    indexed: Vec<(String, i64)>,
+    max: u32,
+) -> Vec<&FeedViewPost> {
+    let window = REPOST_LIMIT_WINDOW.num_microseconds().unwrap_or(i64::MAX);
+    let known: HashSet<&str> = indexed.iter().map(|(uri, _)| uri.as_str()).collect();
+    let mut reposted_at: HashMap<String, Vec<i64>> = HashMap::new();
+    for (uri, indexed_at) in &indexed {
+        reposted_at.entry(uri_did(uri)).or_default().push(*indexed_at);
+    }
+
+    let mut keep = vec![true; posts.len()];
+    for (idx, post) in posts.iter().enumerate().rev() {
+        let Some(repost_uri) = post
+            .reason
+            .as_ref()
+            .filter(|reason| reason.reason_type == "app.bsky.feed.defs#reasonRepost")
+            .and_then(|reason| reason.uri.as_deref())
+        else {
+            continue;
+        };
+        let Some(indexed_at) = item_indexed_at(post) else {
+            continue;
+        };
+        if known.contains(repost_uri) {
+            continue;
+        }
+
+        let times = reposted_at.entry(uri_did(repost_uri)).or_default();
+        let in_window = times
+            .iter()
+            .filter(|time| **time > indexed_at - window && **time <= indexed_at)
+            .count();
+        if in_window >= max as usize {
+            tracing::trace!(repost_uri = %repost_uri, "Filtered out repost over daily limit");
+            keep[idx] = false;
+        } else {
+            times.push(indexed_at);
+        }
+    }
+
+    posts
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(post, keep)| keep.then_some(post))
+        .collect()
+}
+
+/// Repository DID of an at:// URI, empty if the URI is malformed
+fn uri_did(uri: &str) -> String {
+    AtUri::parse(uri)
+        .map(|uri| uri.authority().to_string())
+        .unwrap_or_default()
+}
+
+/// Most getTimeline pages fetched by one new-posts poll
+const NEW_POSTS_MAX_PAGES: u32 = 5;
+
+/// Timestamp a timeline item is indexed under: the repost time for reposts,
+/// else the post's indexedAt
+fn item_indexed_at(item: &FeedViewPost) -> Option<i64> {
+    let indexed_at = match &item.reason {
+        Some(reason)
+            if reason.reason_type == "app.bsky.feed.defs#reasonRepost" && reason.uri.is_some() =>
+        {
+            &reason.indexed_at
+        }
+        _ => item.post.indexed_at.as_ref()?,
+    };
+    parse_indexed_at(indexed_at).ok()
+}
+
+/// Track 1 interval when neither the feed nor POLL_INTERVAL sets a usable one
+const FALLBACK_NEW_POSTS_INTERVAL: Duration = Duration::seconds(60);
+
+/// How often to poll a feed for new posts: the feed's poll_interval, else the
+/// global POLL_INTERVAL, else 60s
+fn new_posts_interval(feed: &TimelineFeed, default_poll_interval: Duration) -> Duration {
+    feed.poll_interval_duration()
+        .or(Some(default_poll_interval))
+        .filter(|interval| *interval > Duration::zero())
+        .unwrap_or(FALLBACK_NEW_POSTS_INTERVAL)
+}
+
 /// Parse ISO 8601 timestamp into microseconds since epoch
 fn parse_indexed_at(indexed_at: &str) -> Result<i64> {
     let dt = chrono::DateTime::parse_from_rfc3339(indexed_at)
@@ -825,6 +1803,7 @@ fn parse_indexed_at(indexed_at: &str) -> Result<i64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock_pds::{self, MockPds};
 
     #[test]
     fn test_parse_indexed_at() {
@@ -835,6 +1814,106 @@ mod tests {
         assert!(micros > 0);
     }
 
+    #[test]
+    fn test_new_posts_interval() {
+        let mut feed: TimelineFeed = serde_yaml::from_str(
+            r#"
+            did: "did:plc:test123"
+            feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/test"
+            name: "Test"
+            description: "Test"
+            oauth:
+              access_token: "token"
+              pds_url: "https://bsky.social"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(new_posts_interval(&feed, Duration::seconds(30)), Duration::seconds(30));
+        assert_eq!(new_posts_interval(&feed, Duration::zero()), FALLBACK_NEW_POSTS_INTERVAL);
+
+        feed.poll_interval = Some("5m".to_string());
+        assert_eq!(new_posts_interval(&feed, Duration::seconds(30)), Duration::minutes(5));
+    }
+
+    #[test]
+    fn test_poll_failure_backoff() {
+        assert_eq!(poll_failure_backoff(1), Duration::seconds(30));
+        assert_eq!(poll_failure_backoff(3), Duration::minutes(2));
+        assert_eq!(poll_failure_backoff(100), POLL_FAILURE_BACKOFF_MAX);
+    }
+
+    #[test]
+    fn test_parse_rate_limit() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert!(parse_rate_limit("https://bsky.social", &headers).is_none());
+
+        headers.insert("ratelimit-limit", "3000".parse().unwrap());
+        headers.insert("ratelimit-remaining", "2950".parse().unwrap());
+        headers.insert("ratelimit-reset", "1760000000".parse().unwrap());
+        assert_eq!(
+            parse_rate_limit("https://bsky.social", &headers),
+            Some(PdsRateLimit {
+                pds_host: "bsky.social".to_string(),
+                rate_limit: 3000,
+                remaining: 2950,
+                reset_at: Some(1760000000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_limit_reposts() {
+        let repost = |n: u32, time: &str| FeedViewPost {
+            post: PostView {
+                uri: format!("at://did:plc:author/app.bsky.feed.post/{}", n),
+                cid: None,
+                author: None,
+                record: None,
+                indexed_at: Some(time.to_string()),
+            },
+            reason: Some(ReasonRepost {
+                reason_type: "app.bsky.feed.defs#reasonRepost".to_string(),
+                by: ProfileViewBasic {
+                    did: "did:plc:busy".to_string(),
+                    handle: None,
+                    display_name: None,
+                    avatar: None,
+                },
+                uri: Some(format!("at://did:plc:busy/app.bsky.feed.repost/{}", n)),
+                cid: None,
+                indexed_at: time.to_string(),
+            }),
+            reply: None,
+        };
+
+        // Newest first, as getTimeline returns them
+        let posts = [
+            repost(4, "2025-10-18T12:00:00Z"),
+            repost(3, "2025-10-17T03:00:00Z"),
+            repost(2, "2025-10-17T02:00:00Z"),
+            repost(1, "2025-10-17T01:00:00Z"),
+        ];
+        let indexed = vec![(
+            "at://did:plc:busy/app.bsky.feed.repost/1".to_string(),
+            parse_indexed_at("2025-10-17T01:00:00Z").unwrap(),
+        )];
+
+        // Repost 1 is already indexed, 2 fills the limit, 3 is over it and 4 is a day later
+        let kept: Vec<&str> = limit_reposts(posts.iter().collect(), indexed, 2)
+            .into_iter()
+            .map(|post| post.post.uri.as_str())
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                "at://did:plc:author/app.bsky.feed.post/4",
+                "at://did:plc:author/app.bsky.feed.post/2",
+                "at://did:plc:author/app.bsky.feed.post/1",
+            ]
+        );
+    }
+
     #[test]
     fn test_filter_posts() {
         use crate::feed_config::FilterConfig;
@@ -843,6 +1922,17 @@ mod tests {
         filters
             .blocked_reposters
             .insert("did:plc:blocked".to_string());
+        filters
+            .muted_threads
+            .insert("at://did:plc:op/post/root".to_string());
+
+        let post_view = |uri: &str| PostView {
+            uri: uri.to_string(),
+            cid: None,
+            author: None,
+            record: None,
+            indexed_at: Some("2025-10-17T00:00:00Z".to_string()),
+        };
 
         let posts = vec![
             // Regular post (should pass)
@@ -918,6 +2008,21 @@ mod tests {
                 }),
                 reply: None,
             },
+            // Root of a muted thread (should be filtered)
+            FeedViewPost {
+                post: post_view("at://did:plc:op/post/root"),
+                reason: None,
+                reply: None,
+            },
+            // Reply in a muted thread (should be filtered)
+            FeedViewPost {
+                post: post_view("at://did:plc:author4/post/4"),
+                reason: None,
+                reply: Some(ReplyRef {
+                    root: post_view("at://did:plc:op/post/root"),
+                    parent: post_view("at://did:plc:author5/post/5"),
+                }),
+            },
         ];
 
         // Use static filter function (no need for task instance)
@@ -927,5 +2032,318 @@ mod tests {
         assert_eq!(filtered.len(), 2);
         assert_eq!(filtered[0].post.uri, "at://did:plc:author1/post/1");
         assert_eq!(filtered[1].post.uri, "at://did:plc:author3/post/3");
+
+        // Hiding all reposts leaves only the regular post
+        filters.hide_all_reposts = true;
+        let filtered = TimelineConsumerTask::filter_posts_static(&posts, &filters);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].post.uri, "at://did:plc:author1/post/1");
+    }
+
+    const MOCK_DID: &str = "did:plc:owner";
+    const MOCK_FEED: &str = "at://did:plc:feedgen/app.bsky.feed.generator/mock";
+
+    /// A consumer for one feed polling `pds`, with the feed synced to a fresh database
+    async fn mock_consumer(pds: &MockPds, extra_yaml: &str) -> (TimelineConsumerTask, TimelineFeed) {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let feed: TimelineFeed = serde_yaml::from_str(&format!(
+            r#"
+            did: "{}"
+            feed_uri: "{}"
+            name: "Mock"
+            description: "Mock"
+            oauth:
+              access_token: "access-0"
+              refresh_token: "refresh-0"
+              expires_at: "{}"
+              pds_url: "{}"
+            {}
+            "#,
+            MOCK_DID,
+            MOCK_FEED,
+            (chrono::Utc::now() + Duration::hours(1)).to_rfc3339(),
+            pds.url,
+            extra_yaml,
+        ))
+        .unwrap();
+        let feeds = TimelineFeeds {
+            timeline_feeds: vec![feed.clone()],
+            aggregate_feeds: Vec::new(),
+        };
+        user_storage::sync_config_to_db(&pool, &feeds).await.unwrap();
+
+        let task = TimelineConsumerTask::new(
+            pool.clone(),
+            reqwest::Client::builder(),
+            TimelineConsumerConfig {
+                timeline_feeds: feeds,
+                default_poll_interval: Duration::seconds(30),
+                handle_refresh_interval: Duration::hours(1),
+                store_post_metadata: false,
+                validate_cids: false,
+            },
+            FeedCache::disabled(pool),
+            WebhookQueue::disabled(),
+            FeedEvents::new(CancellationToken::new()),
+            CancellationToken::new(),
+        )
+        .unwrap();
+        (task, feed)
+    }
+
+    async fn indexed_uris(task: &TimelineConsumerTask) -> Vec<String> {
+        crate::feed_storage::feed_content_page(&task.pool, MOCK_FEED, None, None, None, 100)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|content| content.uri)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reload_filters_from_database() {
+        let pds = MockPds::start(MOCK_DID, vec![]).await;
+        let (mut task, mut feed) =
+            mock_consumer(&pds, "filters:\n              max_reposts_per_author_per_day: 3").await;
+        assert_eq!(feed.filters.max_reposts_per_day, Some(3));
+
+        let filters = FilterConfig {
+            hide_all_reposts: true,
+            max_reposts_per_day: Some(1),
+            ..Default::default()
+        };
+        user_storage::sync_feed_filters(&task.pool, MOCK_DID, MOCK_FEED, &filters)
+            .await
+            .unwrap();
+        task.reload_filters(&mut feed).await.unwrap();
+        assert!(feed.filters.hide_all_reposts);
+        assert_eq!(feed.filters.max_reposts_per_day, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_user_is_not_polled() {
+        let pds = MockPds::start(
+            MOCK_DID,
+            vec![vec![mock_pds::timeline_post("did:plc:author", "1", "2025-10-17T01:00:00Z")]],
+        )
+        .await;
+        let (mut task, _) = mock_consumer(&pds, "").await;
+        // A self-service user, loaded from the database every cycle
+        task.config.timeline_feeds.timeline_feeds.clear();
+        sqlx::query("UPDATE timeline_user_config SET managed_by = 'web'")
+            .execute(&task.pool)
+            .await
+            .unwrap();
+
+        task.poll_cycle().await;
+        let requests = pds.timeline_requests().len();
+        assert!(requests > 0);
+        assert_eq!(indexed_uris(&task).await.len(), 1);
+
+        user_storage::delete_user_data(&task.pool, MOCK_DID).await.unwrap().unwrap();
+        task.poll_cycle().await;
+        assert_eq!(pds.timeline_requests().len(), requests);
+        assert!(indexed_uris(&task).await.is_empty());
+        assert!(user_storage::get_user_config(&task.pool, MOCK_DID).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_poll_refreshes_expired_token() {
+        let pds = MockPds::start(
+            MOCK_DID,
+            vec![vec![mock_pds::timeline_post("did:plc:author", "1", "2025-10-17T01:00:00Z")]],
+        )
+        .await;
+        let (mut task, mut feed) = mock_consumer(&pds, "").await;
+        feed.oauth.expires_at = Some("2020-01-01T00:00:00Z".to_string());
+        let mut indexed = task.feed_events.subscribe();
+
+        task.poll_timeline_mode(&mut feed, false).await.unwrap();
+
+        assert_eq!(pds.refreshes(), 1);
+        assert_eq!(feed.oauth.access_token, pds.access_token());
+        assert_eq!(feed.oauth.refresh_token.as_deref(), Some("refresh-1"));
+        let stored = user_storage::get_user_config(&task.pool, MOCK_DID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.access_token, "access-1");
+        assert_eq!(indexed_uris(&task).await.len(), 1);
+        assert_eq!(
+            indexed.recv().await.unwrap().unwrap().uri,
+            "at://did:plc:author/app.bsky.feed.post/1"
+        );
+
+        // A rejected refresh token fails the poll without touching the timeline
+        feed.oauth.expires_at = Some("2020-01-01T00:00:00Z".to_string());
+        feed.oauth.refresh_token = Some("revoked".to_string());
+        let err = task.poll_timeline_mode(&mut feed, false).await.unwrap_err();
+        assert!(err.downcast_ref::<SessionRevoked>().is_some());
+        assert_eq!(pds.timeline_requests().len(), 1);
+
+        // and suspends the user instead of backing off
+        task.record_poll_failure(MOCK_DID, &err).await;
+        let poll_error = user_storage::get_poll_error(&task.pool, MOCK_DID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(poll_error.reauth_required);
+        assert!(poll_error.is_suspended());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_waits_for_claimed_refresh() {
+        let pds = MockPds::start(MOCK_DID, vec![vec![]]).await;
+        let (task, mut feed) = mock_consumer(&pds, "").await;
+        let now = chrono::Utc::now();
+
+        // Another poller holds the claim and stores its tokens shortly after
+        assert!(user_storage::claim_token_refresh(&task.pool, MOCK_DID, now, TOKEN_REFRESH_LEASE)
+            .await
+            .unwrap());
+        let pool = task.pool.clone();
+        let expires_at = (now + Duration::hours(2)).to_rfc3339();
+        let other = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            user_storage::update_tokens(&pool, MOCK_DID, "access-other", Some("refresh-other"), Some(&expires_at))
+                .await
+                .unwrap();
+        });
+
+        task.refresh_token(&mut feed).await.unwrap();
+        other.await.unwrap();
+
+        assert_eq!(pds.refreshes(), 0);
+        assert_eq!(feed.oauth.access_token, "access-other");
+        assert_eq!(feed.oauth.refresh_token.as_deref(), Some("refresh-other"));
+
+        // With the claim released, a stale poller picks up the stored tokens too
+        feed.oauth.access_token = "access-0".to_string();
+        task.refresh_token(&mut feed).await.unwrap();
+        assert_eq!(pds.refreshes(), 0);
+        assert_eq!(feed.oauth.access_token, "access-other");
+    }
+
+    #[tokio::test]
+    async fn test_backfill_follows_cursor_until_timeline_ends() {
+        let pages = (0..3)
+            .map(|page| {
+                vec![mock_pds::timeline_post(
+                    "did:plc:author",
+                    &page.to_string(),
+                    &format!("2025-10-17T0{}:00:00Z", 9 - page),
+                )]
+            })
+            .collect();
+        let pds = MockPds::start(MOCK_DID, pages).await;
+        let (mut task, mut feed) = mock_consumer(&pds, "backfill_limit: null").await;
+
+        let mut polls = 0;
+        while user_storage::needs_backfill(&task.pool, MOCK_DID, feed.backfill_limit)
+            .await
+            .unwrap()
+        {
+            task.poll_timeline_mode(&mut feed, true).await.unwrap();
+            polls += 1;
+            assert!(polls <= 3, "backfill did not stop at the end of the timeline");
+        }
+
+        assert_eq!(
+            pds.timeline_requests(),
+            vec![None, Some("1".to_string()), Some("2".to_string())]
+        );
+        assert_eq!(indexed_uris(&task).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_stops_at_limit() {
+        let pages = (0..3)
+            .map(|page| {
+                vec![
+                    mock_pds::timeline_post("did:plc:author", &format!("{}a", page), "2025-10-17T01:00:00Z"),
+                    mock_pds::timeline_post("did:plc:author", &format!("{}b", page), "2025-10-17T00:00:00Z"),
+                ]
+            })
+            .collect();
+        let pds = MockPds::start(MOCK_DID, pages).await;
+        let (mut task, mut feed) = mock_consumer(&pds, "backfill_limit: 2").await;
+
+        task.poll_timeline_mode(&mut feed, true).await.unwrap();
+
+        assert!(!user_storage::needs_backfill(&task.pool, MOCK_DID, feed.backfill_limit)
+            .await
+            .unwrap());
+        assert_eq!(pds.timeline_requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_poll_filters_and_records_rate_limit() {
+        let pds = MockPds::start(
+            MOCK_DID,
+            vec![vec![
+                mock_pds::timeline_post("did:plc:author", "1", "2025-10-17T03:00:00Z"),
+                mock_pds::timeline_repost("did:plc:author", "2", "did:plc:blocked", "2025-10-17T02:00:00Z"),
+                mock_pds::timeline_repost("did:plc:author", "3", "did:plc:friend", "2025-10-17T01:00:00Z"),
+            ]],
+        )
+        .await;
+        pds.set_rate_limit(3000, 100);
+        let (mut task, mut feed) = mock_consumer(
+            &pds,
+            r#"filters:
+              blocked_reposters: ["did:plc:blocked"]"#,
+        )
+        .await;
+
+        task.poll_timeline_mode(&mut feed, false).await.unwrap();
+
+        let mut uris = indexed_uris(&task).await;
+        uris.sort();
+        assert_eq!(
+            uris,
+            vec![
+                "at://did:plc:author/app.bsky.feed.post/1",
+                "at://did:plc:author/app.bsky.feed.post/3",
+            ]
+        );
+
+        let rate_limits = user_storage::pds_rate_limit_list(&task.pool).await.unwrap();
+        assert_eq!(rate_limits.len(), 1);
+        assert_eq!(rate_limits[0].remaining, 100);
+    }
+
+    #[tokio::test]
+    async fn test_starter_pack_members_block_reposts() {
+        let starter_pack = "at://did:plc:curator/app.bsky.graph.starterpack/spam";
+        let pds = MockPds::start(
+            MOCK_DID,
+            vec![vec![
+                mock_pds::timeline_repost("did:plc:author", "1", "did:plc:member", "2025-10-17T02:00:00Z"),
+                mock_pds::timeline_repost("did:plc:author", "2", "did:plc:friend", "2025-10-17T01:00:00Z"),
+            ]],
+        )
+        .await;
+        pds.add_starter_pack(starter_pack, &["did:plc:member"]);
+        let (mut task, _) = mock_consumer(
+            &pds,
+            &format!(
+                r#"filters:
+              blocked_reposter_starter_packs: ["{}"]"#,
+                starter_pack
+            ),
+        )
+        .await;
+
+        task.resolve_handles().await;
+        let mut feed = task.config.timeline_feeds.timeline_feeds[0].clone();
+        assert!(feed.filters.is_reposter_blocked("did:plc:member"));
+
+        task.poll_timeline_mode(&mut feed, false).await.unwrap();
+        assert_eq!(
+            indexed_uris(&task).await,
+            vec!["at://did:plc:author/app.bsky.feed.post/2"]
+        );
     }
 }
@@ -1,20 +1,31 @@
 use std::collections::HashSet;
+use std::fmt;
 
 use anyhow::{Context, Result};
 use chrono::Duration;
 use serde::Deserialize;
 
+use crate::aturi::{AtUri, FEED_GENERATOR_COLLECTION, POST_COLLECTION, STARTER_PACK_COLLECTION};
+use crate::identity::is_handle;
+use crate::redact::REDACTED;
+use crate::server_config::read_secret_file;
+
 /// Root configuration structure for timeline feeds
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct TimelineFeeds {
     #[serde(default)]
     pub timeline_feeds: Vec<TimelineFeed>,
+
+    /// Feeds merging the primary feeds of several configured accounts
+    #[serde(default)]
+    pub aggregate_feeds: Vec<AggregateFeed>,
 }
 
 /// Configuration for a single user's timeline feed
 #[derive(Clone, Debug, Deserialize)]
 pub struct TimelineFeed {
     /// User's DID (Decentralized Identifier)
+    /// A handle is also accepted and resolved to a DID when the consumer starts
     pub did: String,
 
     /// Feed URI for this filtered timeline
@@ -48,9 +59,165 @@ pub struct TimelineFeed {
     /// - None: Continue backfill until cursor becomes undefined (can be thousands of posts!)
     #[serde(default = "default_backfill_limit")]
     pub backfill_limit: Option<u32>,
+
+    /// Additional feeds built from the same timeline poll, each with its own filters
+    #[serde(default)]
+    pub variants: Vec<TimelineFeedVariant>,
+
+    /// Limits on how much of a page one author can take
+    #[serde(default)]
+    pub diversity: AuthorDiversity,
+
+    /// URL that receives each newly indexed post of this feed, in batches
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+
+    /// Only serve this feed and its variants to the owner
+    /// getFeedSkeleton then requires a service JWT issued by `did`
+    #[serde(default)]
+    pub private: bool,
+}
+
+/// An extra feed URI served from a user's timeline with a different filter set
+/// Variants share the owner's OAuth session and poll; only filtering differs
+#[derive(Clone, Debug, Deserialize)]
+pub struct TimelineFeedVariant {
+    /// Feed URI for this variant
+    pub feed_uri: String,
+
+    /// Display name for the feed
+    pub name: String,
+
+    /// Description of the feed
+    pub description: String,
+
+    /// Filtering rules applied to this variant only
+    #[serde(default)]
+    pub filters: FilterConfig,
+
+    /// Limits on how much of a page one author can take
+    #[serde(default)]
+    pub diversity: AuthorDiversity,
+
+    /// URL that receives each newly indexed post of this feed, in batches
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+/// A feed combining the timelines of several accounts, e.g. a team timeline
+/// Each source contributes the posts of its primary feed, after its own filters;
+/// posts seen in more than one timeline appear once
+#[derive(Clone, Debug, Deserialize)]
+pub struct AggregateFeed {
+    /// Feed URI for the merged feed
+    pub feed_uri: String,
+
+    /// Display name for the feed
+    pub name: String,
+
+    /// Description of the feed
+    pub description: String,
+
+    /// DIDs of the accounts whose timelines are merged
+    pub sources: Vec<String>,
+
+    /// Limits on how much of a page one author can take
+    #[serde(default)]
+    pub diversity: AuthorDiversity,
+
+    /// URL that receives each newly indexed post of this feed, in batches
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+/// Author diversity limits applied when serving a feed page
+/// Posts over a limit are skipped so one prolific account can't dominate a page;
+/// unset limits don't apply
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+pub struct AuthorDiversity {
+    /// Maximum posts in a row by the same author
+    #[serde(default)]
+    pub max_consecutive_per_author: Option<u32>,
+
+    /// Maximum posts by one author on a single page
+    #[serde(default)]
+    pub max_per_author_per_page: Option<u32>,
+}
+
+impl AuthorDiversity {
+    /// Whether any limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.max_consecutive_per_author.is_some() || self.max_per_author_per_page.is_some()
+    }
+
+    /// Validate the limits
+    pub fn validate(&self) -> Result<()> {
+        if self.max_consecutive_per_author == Some(0) || self.max_per_author_per_page == Some(0) {
+            anyhow::bail!("Author diversity limits must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+impl AggregateFeed {
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<()> {
+        if !is_feed_uri(&self.feed_uri) {
+            anyhow::bail!("Invalid aggregate feed_uri format: {}", self.feed_uri);
+        }
+        if self.sources.is_empty() {
+            anyhow::bail!("Aggregate feed {} has no sources", self.feed_uri);
+        }
+        self.diversity.validate()?;
+        validate_webhook_url(self.webhook_url.as_deref())?;
+        for source in &self.sources {
+            if !source.starts_with("did:") {
+                anyhow::bail!("Aggregate feed sources must be DIDs: {}", source);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl TimelineFeed {
+    /// Every feed built from this timeline: the primary feed followed by its variants
+    pub fn feed_targets(&self) -> impl Iterator<Item = (&String, &FilterConfig)> {
+        std::iter::once((&self.feed_uri, &self.filters)).chain(
+            self.variants
+                .iter()
+                .map(|variant| (&variant.feed_uri, &variant.filters)),
+        )
+    }
+
+    /// Author diversity limits of every feed built from this timeline
+    pub fn feed_diversity(&self) -> impl Iterator<Item = (&String, &AuthorDiversity)> {
+        std::iter::once((&self.feed_uri, &self.diversity)).chain(
+            self.variants
+                .iter()
+                .map(|variant| (&variant.feed_uri, &variant.diversity)),
+        )
+    }
+
+    /// Webhook URL of one of the feeds built from this timeline
+    pub fn webhook_for(&self, feed_uri: &str) -> Option<&str> {
+        if feed_uri == self.feed_uri {
+            return self.webhook_url.as_deref();
+        }
+        self.variants
+            .iter()
+            .find(|variant| variant.feed_uri == feed_uri)
+            .and_then(|variant| variant.webhook_url.as_deref())
+    }
+
+    /// Like feed_targets, with mutable access to each feed's filters
+    pub fn feed_targets_mut(&mut self) -> impl Iterator<Item = (&String, &mut FilterConfig)> {
+        std::iter::once((&self.feed_uri, &mut self.filters)).chain(
+            self.variants
+                .iter_mut()
+                .map(|variant| (&variant.feed_uri, &mut variant.filters)),
+        )
+    }
+
     /// Parse poll_interval string into chrono::Duration
     pub fn poll_interval_duration(&self) -> Option<Duration> {
         self.poll_interval.as_ref().and_then(|s| {
@@ -74,13 +241,13 @@ impl TimelineFeed {
 
     /// Validate the configuration with cleanup max age for backfill limit checking
     pub fn validate_with_cleanup_age(&self, cleanup_max_age: Option<chrono::Duration>) -> Result<()> {
-        // Validate DID format
-        if !self.did.starts_with("did:") {
+        // Validate DID format (handles are resolved by the consumer on startup)
+        if !self.did.starts_with("did:") && !is_handle(&self.did) {
             anyhow::bail!("Invalid DID format: {}", self.did);
         }
 
         // Validate feed_uri format
-        if !self.feed_uri.starts_with("at://") {
+        if !is_feed_uri(&self.feed_uri) {
             anyhow::bail!("Invalid feed_uri format: {}", self.feed_uri);
         }
 
@@ -185,13 +352,39 @@ impl TimelineFeed {
 
         // Validate filters
         self.filters.validate()?;
+        for (_, diversity) in self.feed_diversity() {
+            diversity.validate()?;
+        }
+        validate_webhook_url(self.webhook_url.as_deref())?;
+        for variant in &self.variants {
+            validate_webhook_url(variant.webhook_url.as_deref())?;
+        }
+
+        // Validate variants
+        let mut feed_uris = HashSet::from([self.feed_uri.as_str()]);
+        for variant in &self.variants {
+            if !is_feed_uri(&variant.feed_uri) {
+                anyhow::bail!("Invalid variant feed_uri format: {}", variant.feed_uri);
+            }
+            if !feed_uris.insert(variant.feed_uri.as_str()) {
+                anyhow::bail!("Duplicate feed_uri in variants: {}", variant.feed_uri);
+            }
+            variant
+                .filters
+                .validate()
+                .with_context(|| format!("Invalid filters for variant {}", variant.feed_uri))?;
+        }
 
         Ok(())
     }
 }
 
 /// OAuth configuration for a user
-#[derive(Clone, Debug, Deserialize)]
+///
+/// Tokens can be given inline or as `access_token_file` / `refresh_token_file`,
+/// paths to mounted secret files that are read when the config is loaded.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "RawOAuthConfig")]
 pub struct OAuthConfig {
     /// Access token for AT Protocol API calls
     pub access_token: String,
@@ -209,6 +402,63 @@ pub struct OAuthConfig {
     pub pds_url: String,
 }
 
+impl fmt::Debug for OAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthConfig")
+            .field("access_token", &REDACTED)
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| REDACTED))
+            .field("expires_at", &self.expires_at)
+            .field("pds_url", &self.pds_url)
+            .finish()
+    }
+}
+
+/// OAuthConfig as written in the YAML file, before secret files are read
+#[derive(Deserialize)]
+struct RawOAuthConfig {
+    #[serde(default)]
+    access_token: Option<String>,
+    #[serde(default)]
+    access_token_file: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    refresh_token_file: Option<String>,
+    #[serde(default)]
+    expires_at: Option<String>,
+    pds_url: String,
+}
+
+impl TryFrom<RawOAuthConfig> for OAuthConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawOAuthConfig) -> Result<Self> {
+        let access_token = match (raw.access_token, raw.access_token_file) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("access_token and access_token_file cannot both be set")
+            }
+            (Some(access_token), None) => access_token,
+            (None, Some(path)) => read_secret_file(&path)?,
+            (None, None) => anyhow::bail!("access_token or access_token_file must be set"),
+        };
+        let refresh_token = match (raw.refresh_token, raw.refresh_token_file) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("refresh_token and refresh_token_file cannot both be set")
+            }
+            (Some(refresh_token), None) => Some(refresh_token),
+            (None, Some(path)) => Some(read_secret_file(&path)?),
+            (None, None) => None,
+        };
+
+        Ok(Self {
+            access_token,
+            refresh_token,
+            expires_at: raw.expires_at,
+            pds_url: raw.pds_url,
+        })
+    }
+}
+
 impl OAuthConfig {
     /// Validate the OAuth configuration
     pub fn validate(&self) -> Result<()> {
@@ -247,9 +497,35 @@ impl OAuthConfig {
 pub struct FilterConfig {
     /// List of DIDs whose reposts should be filtered out
     /// The original posts from these users will still appear
+    /// Handles (e.g. "@spam.bsky.social") are accepted and resolved periodically
     #[serde(default)]
     pub blocked_reposters: HashSet<String>,
 
+    /// DIDs resolved from the handles in blocked_reposters
+    #[serde(skip)]
+    pub resolved_reposters: HashSet<String>,
+
+    /// AT-URIs of starter packs whose members' reposts should be filtered out
+    /// Members are looked up on startup and every HANDLE_REFRESH_INTERVAL
+    #[serde(default)]
+    pub blocked_reposter_starter_packs: HashSet<String>,
+
+    /// DIDs of the members of blocked_reposter_starter_packs
+    #[serde(skip)]
+    pub starter_pack_members: HashSet<String>,
+
+    /// AT-URIs of thread root posts to hide, along with every reply in the thread
+    #[serde(default)]
+    pub muted_threads: HashSet<String>,
+
+    /// Filter out every repost, whoever made it
+    #[serde(default)]
+    pub hide_all_reposts: bool,
+
+    /// Most reposts by one account kept in any 24 hours; later ones are filtered out
+    #[serde(default, alias = "max_reposts_per_author_per_day")]
+    pub max_reposts_per_day: Option<u32>,
+
     // Future filter types can be added here:
     // pub blocked_authors: HashSet<String>,
     // pub blocked_keywords: Vec<String>,
@@ -257,17 +533,48 @@ pub struct FilterConfig {
 }
 
 impl FilterConfig {
-    /// Check if a DID is in the blocked reposters list
+    /// Check if reposts by a DID are filtered out
     pub fn is_reposter_blocked(&self, did: &str) -> bool {
-        self.blocked_reposters.contains(did)
+        self.hide_all_reposts
+            || self.blocked_reposters.contains(did)
+            || self.resolved_reposters.contains(did)
+            || self.starter_pack_members.contains(did)
+    }
+
+    /// Check if a thread, given by its root post URI, is muted
+    pub fn is_thread_muted(&self, root_uri: &str) -> bool {
+        self.muted_threads.contains(root_uri)
+    }
+
+    /// Handles listed in blocked_reposters that need resolving to DIDs
+    pub fn reposter_handles(&self) -> impl Iterator<Item = &String> {
+        self.blocked_reposters
+            .iter()
+            .filter(|entry| !entry.starts_with("did:"))
     }
 
     /// Validate the filter configuration
     pub fn validate(&self) -> Result<()> {
-        // Validate all blocked reposter DIDs
+        // Validate all blocked reposter DIDs and handles
         for did in &self.blocked_reposters {
-            if !did.starts_with("did:") {
-                anyhow::bail!("Invalid DID in blocked_reposters: {}", did);
+            if !did.starts_with("did:") && !is_handle(did) {
+                anyhow::bail!("Invalid DID or handle in blocked_reposters: {}", did);
+            }
+        }
+
+        for uri in &self.blocked_reposter_starter_packs {
+            if !AtUri::parse(uri).is_ok_and(|uri| uri.is_record_in(STARTER_PACK_COLLECTION)) {
+                anyhow::bail!("Invalid starter pack URI in blocked_reposter_starter_packs: {}", uri);
+            }
+        }
+
+        if self.max_reposts_per_day == Some(0) {
+            anyhow::bail!("max_reposts_per_day must be at least 1, use hide_all_reposts to hide every repost");
+        }
+
+        for uri in &self.muted_threads {
+            if !AtUri::parse(uri).is_ok_and(|uri| uri.is_record_in(POST_COLLECTION)) {
+                anyhow::bail!("Invalid post URI in muted_threads: {}", uri);
             }
         }
 
@@ -275,45 +582,169 @@ impl FilterConfig {
     }
 }
 
+/// Whether `uri` names an app.bsky.feed.generator record
+fn is_feed_uri(uri: &str) -> bool {
+    AtUri::parse(uri).is_ok_and(|uri| uri.is_record_in(FEED_GENERATOR_COLLECTION))
+}
+
+/// Webhooks must be plain HTTP(S) URLs
+fn validate_webhook_url(url: Option<&str>) -> Result<()> {
+    match url {
+        Some(url) if !url.starts_with("https://") && !url.starts_with("http://") => {
+            anyhow::bail!("webhook_url must be an http(s) URL: {}", url)
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Default value for max_posts_per_poll
 fn default_max_posts() -> u32 {
     50
 }
 
 /// Default value for backfill_limit
-fn default_backfill_limit() -> Option<u32> {
+pub(crate) fn default_backfill_limit() -> Option<u32> {
     Some(500)
 }
 
+/// The config file before each entry is parsed, so entries can fail on their own
+#[derive(Deserialize)]
+struct RawTimelineFeeds {
+    #[serde(default)]
+    timeline_feeds: Vec<serde_yaml::Value>,
+    #[serde(default)]
+    aggregate_feeds: Vec<serde_yaml::Value>,
+}
+
 impl TimelineFeeds {
     /// Load TimelineFeeds from a file path with optional cleanup_max_age for validation
     pub fn load_from_path(path: &str, cleanup_max_age: Option<Duration>) -> Result<Self> {
+        let (feeds, _) = Self::load(path, cleanup_max_age, false)?;
+        Ok(feeds)
+    }
+
+    /// Load TimelineFeeds, skipping entries that fail to parse or validate
+    ///
+    /// Returns the valid feeds and one error message per skipped entry. Errors
+    /// reading the file or parsing it as YAML still fail the whole load.
+    pub fn load_lenient(
+        path: &str,
+        cleanup_max_age: Option<Duration>,
+    ) -> Result<(Self, Vec<String>)> {
+        Self::load(path, cleanup_max_age, true)
+    }
+
+    fn load(
+        path: &str,
+        cleanup_max_age: Option<Duration>,
+        lenient: bool,
+    ) -> Result<(Self, Vec<String>)> {
+        let mut feeds = TimelineFeeds {
+            timeline_feeds: vec![],
+            aggregate_feeds: vec![],
+        };
         if path.is_empty() {
             // Return empty config if no path provided
-            return Ok(TimelineFeeds {
-                timeline_feeds: vec![],
-            });
+            return Ok((feeds, vec![]));
         }
 
         let content = std::fs::read(path)
             .with_context(|| format!("Failed to read timeline feeds config file: {}", path))?;
 
-        let feeds: TimelineFeeds = serde_yaml::from_slice(&content)
+        let raw: RawTimelineFeeds = serde_yaml::from_slice(&content)
             .with_context(|| format!("Failed to parse timeline feeds config: {}", path))?;
 
+        let mut errors = Vec::new();
+        let mut skip = |err: anyhow::Error| -> Result<()> {
+            if !lenient {
+                return Err(err);
+            }
+            tracing::error!(error = %format!("{:#}", err), "Skipping invalid timeline feed");
+            errors.push(format!("{:#}", err));
+            Ok(())
+        };
+
         // Validate all feeds with cleanup_max_age
-        for (idx, feed) in feeds.timeline_feeds.iter().enumerate() {
-            feed.validate_with_cleanup_age(cleanup_max_age)
-                .with_context(|| format!("Invalid configuration for feed #{} ({})", idx, feed.did))?;
+        let mut feed_uris = HashSet::new();
+        for (idx, value) in raw.timeline_feeds.into_iter().enumerate() {
+            let did = value
+                .get("did")
+                .and_then(|did| did.as_str())
+                .unwrap_or("no did")
+                .to_string();
+            let feed = serde_yaml::from_value::<TimelineFeed>(value)
+                .map_err(anyhow::Error::from)
+                .and_then(|feed| {
+                    feed.validate_with_cleanup_age(cleanup_max_age)?;
+                    for (feed_uri, _) in feed.feed_targets() {
+                        if feed_uris.contains(feed_uri) {
+                            anyhow::bail!("Feed URI configured more than once: {}", feed_uri);
+                        }
+                    }
+                    Ok(feed)
+                })
+                .with_context(|| format!("Invalid configuration for feed #{} ({})", idx, did));
+            match feed {
+                Ok(feed) => {
+                    feed_uris.extend(feed.feed_targets().map(|(feed_uri, _)| feed_uri.clone()));
+                    feeds.timeline_feeds.push(feed);
+                }
+                Err(err) => skip(err)?,
+            }
+        }
+
+        for value in raw.aggregate_feeds {
+            let aggregate = serde_yaml::from_value::<AggregateFeed>(value)
+                .context("Invalid configuration for aggregate feed")
+                .and_then(|aggregate| {
+                    aggregate.validate().with_context(|| {
+                        format!("Invalid configuration for aggregate feed {}", aggregate.feed_uri)
+                    })?;
+                    if feed_uris.contains(&aggregate.feed_uri) {
+                        anyhow::bail!("Feed URI configured more than once: {}", aggregate.feed_uri);
+                    }
+                    for source in &aggregate.sources {
+                        if feeds.get_by_did(source).is_some_and(|feed| feed.private) {
+                            anyhow::bail!(
+                                "Aggregate feed {} would expose the private timeline of {}",
+                                aggregate.feed_uri,
+                                source
+                            );
+                        }
+                    }
+                    Ok(aggregate)
+                });
+            let aggregate = match aggregate {
+                Ok(aggregate) => aggregate,
+                Err(err) => {
+                    skip(err)?;
+                    continue;
+                }
+            };
+
+            for source in &aggregate.sources {
+                if feeds.get_by_did(source).is_none() {
+                    // Self-service feeds are not in the YAML but can still be sources
+                    tracing::warn!(
+                        feed_uri = %aggregate.feed_uri,
+                        source = %source,
+                        "Aggregate feed source is not a configured timeline feed"
+                    );
+                }
+            }
+            feed_uris.insert(aggregate.feed_uri.clone());
+            feeds.aggregate_feeds.push(aggregate);
         }
 
         tracing::info!(
             count = feeds.timeline_feeds.len(),
+            aggregate_count = feeds.aggregate_feeds.len(),
+            skipped = errors.len(),
             cleanup_max_age = ?cleanup_max_age,
             "Loaded timeline feeds configuration"
         );
 
-        Ok(feeds)
+        Ok((feeds, errors))
     }
 }
 
@@ -332,9 +763,38 @@ impl TimelineFeeds {
         self.timeline_feeds.iter().find(|f| f.did == did)
     }
 
-    /// Get a feed by feed URI
+    /// Get a feed by feed URI, matching variants as well as primary feeds
     pub fn get_by_feed_uri(&self, feed_uri: &str) -> Option<&TimelineFeed> {
-        self.timeline_feeds.iter().find(|f| f.feed_uri == feed_uri)
+        self.timeline_feeds
+            .iter()
+            .find(|f| f.feed_targets().any(|(uri, _)| uri == feed_uri))
+    }
+
+    /// Aggregate feeds that merge the timeline of this DID
+    pub fn aggregates_for<'a>(&'a self, did: &'a str) -> impl Iterator<Item = &'a AggregateFeed> {
+        self.aggregate_feeds
+            .iter()
+            .filter(move |aggregate| aggregate.sources.iter().any(|source| source == did))
+    }
+
+    /// Every feed URI served from the config: primary feeds, variants and aggregates
+    pub fn feed_uris(&self) -> Vec<String> {
+        self.timeline_feeds
+            .iter()
+            .flat_map(|feed| feed.feed_targets().map(|(feed_uri, _)| feed_uri.clone()))
+            .chain(self.aggregate_feeds.iter().map(|aggregate| aggregate.feed_uri.clone()))
+            .collect()
+    }
+
+    /// Whether any configured feed delivers posts to a webhook
+    pub fn has_webhooks(&self) -> bool {
+        self.timeline_feeds.iter().any(|feed| {
+            feed.webhook_url.is_some()
+                || feed.variants.iter().any(|variant| variant.webhook_url.is_some())
+        }) || self
+            .aggregate_feeds
+            .iter()
+            .any(|aggregate| aggregate.webhook_url.is_some())
     }
 
     /// Check if configuration is empty
@@ -369,6 +829,10 @@ mod tests {
             poll_interval: Some("30s".to_string()),
             max_posts_per_poll: 50,
             backfill_limit: Some(500),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         assert!(feed.validate().is_ok());
@@ -391,6 +855,10 @@ mod tests {
             poll_interval: None,
             max_posts_per_poll: 50,
             backfill_limit: Some(500),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         assert!(feed.validate().is_err());
@@ -413,6 +881,10 @@ mod tests {
             poll_interval: Some("30s".to_string()),
             backfill_limit: Some(500),
             max_posts_per_poll: 50,
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         let duration = feed.poll_interval_duration();
@@ -428,6 +900,17 @@ mod tests {
 
         assert!(filters.is_reposter_blocked("did:plc:blocked1"));
         assert!(!filters.is_reposter_blocked("did:plc:notblocked"));
+
+        // Handles are accepted and only match once resolved
+        filters.blocked_reposters.insert("@spam.bsky.social".to_string());
+        assert!(filters.validate().is_ok());
+        assert_eq!(filters.reposter_handles().count(), 1);
+        assert!(!filters.is_reposter_blocked("did:plc:spam"));
+        filters.resolved_reposters.insert("did:plc:spam".to_string());
+        assert!(filters.is_reposter_blocked("did:plc:spam"));
+
+        filters.blocked_reposters.insert("not a handle".to_string());
+        assert!(filters.validate().is_err());
     }
 
     #[test]
@@ -469,6 +952,10 @@ mod tests {
             poll_interval: None,
             max_posts_per_poll: 50,
             backfill_limit: Some(1000),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         let cleanup_age_48h = Some(Duration::hours(48));
@@ -491,6 +978,10 @@ mod tests {
             poll_interval: None,
             max_posts_per_poll: 50,
             backfill_limit: Some(10000),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         // Should not error but will log warning (we can't test log output easily)
@@ -512,6 +1003,10 @@ mod tests {
             poll_interval: None,
             max_posts_per_poll: 50,
             backfill_limit: None,
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         // Should not error but will log warning
@@ -533,10 +1028,120 @@ mod tests {
             poll_interval: None,
             max_posts_per_poll: 50,
             backfill_limit: Some(3500),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         let cleanup_age_7d = Some(Duration::days(7));
         // Should be fine - 3500 is reasonable for 7 days
         assert!(feed_7d.validate_with_cleanup_age(cleanup_age_7d).is_ok());
     }
+
+    #[test]
+    fn test_feed_variants() {
+        let yaml = r#"
+timeline_feeds:
+  - did: "did:plc:test123"
+    feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/main"
+    name: "Main"
+    description: "Everything"
+    oauth:
+      access_token: "test_token"
+      pds_url: "https://bsky.social"
+    variants:
+      - feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/quiet"
+        name: "Quiet"
+        description: "No reposts from noisy accounts"
+        filters:
+          blocked_reposters:
+            - "did:plc:noisy"
+"#;
+        let feeds: TimelineFeeds = serde_yaml::from_str(yaml).unwrap();
+        let feed = &feeds.timeline_feeds[0];
+        assert!(feed.validate().is_ok());
+
+        let targets: Vec<_> = feed.feed_targets().collect();
+        assert_eq!(targets.len(), 2);
+        assert!(!targets[0].1.is_reposter_blocked("did:plc:noisy"));
+        assert!(targets[1].1.is_reposter_blocked("did:plc:noisy"));
+
+        let quiet = "at://did:plc:feedgen/app.bsky.feed.generator/quiet";
+        assert_eq!(feeds.get_by_feed_uri(quiet).map(|f| f.did.as_str()), Some("did:plc:test123"));
+
+        // A variant cannot reuse the primary feed URI
+        let mut duplicate = feed.clone();
+        duplicate.variants[0].feed_uri = duplicate.feed_uri.clone();
+        assert!(duplicate.validate().is_err());
+    }
+
+    #[test]
+    fn test_oauth_token_files() {
+        let path = std::env::temp_dir().join(format!(
+            "timeline-filter-token-test-{}",
+            std::process::id()
+        ));
+        std::fs::write(&path, "secret_token\n").unwrap();
+
+        let yaml = format!(
+            "access_token_file: \"{}\"\npds_url: \"https://bsky.social\"\n",
+            path.display()
+        );
+        let oauth: OAuthConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(oauth.access_token, "secret_token");
+        assert!(oauth.refresh_token.is_none());
+
+        let both = format!("access_token: \"inline\"\n{}", yaml);
+        assert!(serde_yaml::from_str::<OAuthConfig>(&both).is_err());
+        assert!(serde_yaml::from_str::<OAuthConfig>("pds_url: \"https://bsky.social\"\n").is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_lenient_skips_invalid_feeds() {
+        let yaml = r#"
+timeline_feeds:
+  - did: "did:plc:alice"
+    feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/alice"
+    name: "Alice"
+    description: "Timeline of alice"
+    oauth:
+      access_token: "alice_token"
+      pds_url: "https://bsky.social"
+  - did: "did:plc:bob"
+    feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/bob"
+    name: "Bob"
+    description: "Timeline of bob"
+    oauth:
+      access_token: "bob_token"
+      pds_url: "bsky.social"
+  - did: "did:plc:carol"
+    feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/carol"
+    name: "Carol"
+    description: "Timeline of carol"
+    max_posts_per_poll: "many"
+    oauth:
+      access_token: "carol_token"
+      pds_url: "https://bsky.social"
+"#;
+        let path = std::env::temp_dir().join(format!(
+            "timeline-filter-lenient-test-{}.yml",
+            std::process::id()
+        ));
+        std::fs::write(&path, yaml).unwrap();
+        let path = path.to_str().unwrap();
+
+        assert!(TimelineFeeds::load_from_path(path, None).is_err());
+
+        let (feeds, errors) = TimelineFeeds::load_lenient(path, None).unwrap();
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds.timeline_feeds[0].did, "did:plc:alice");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("did:plc:bob"));
+        assert!(errors[1].contains("did:plc:carol"));
+
+        std::fs::remove_file(path).unwrap();
+    }
 }
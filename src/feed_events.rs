@@ -0,0 +1,112 @@
+//! Live stream of newly indexed posts
+//!
+//! The timeline consumer publishes every post it inserts, and
+//! `/api/v1/feeds/:feed/stream` forwards the posts of one feed to its
+//! subscribers as server-sent events.
+
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+
+/// Posts buffered per subscriber; a subscriber further behind skips ahead
+const FEED_EVENTS_CAPACITY: usize = 1024;
+
+/// A post newly indexed into a feed
+#[derive(Clone, Debug, Serialize)]
+pub struct IndexedPost {
+    pub feed: String,
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repost_uri: Option<String>,
+    pub indexed_at: i64,
+}
+
+/// Broadcasts newly indexed posts to stream subscribers, cheap to clone
+///
+/// Publishing never waits; with no subscribers posts are discarded.
+#[derive(Clone)]
+pub struct FeedEvents {
+    sender: broadcast::Sender<IndexedPost>,
+    /// Ends every subscription, so open streams don't hold up shutdown
+    cancellation_token: CancellationToken,
+}
+
+impl FeedEvents {
+    pub fn new(cancellation_token: CancellationToken) -> Self {
+        let (sender, _) = broadcast::channel(FEED_EVENTS_CAPACITY);
+        Self {
+            sender,
+            cancellation_token,
+        }
+    }
+
+    pub fn publish(&self, post: IndexedPost) {
+        // Only fails when nobody is subscribed
+        let _ = self.sender.send(post);
+    }
+
+    pub fn subscribe(&self) -> FeedSubscription {
+        FeedSubscription {
+            receiver: self.sender.subscribe(),
+            cancellation_token: self.cancellation_token.clone(),
+        }
+    }
+}
+
+/// Posts published after [`FeedEvents::subscribe`]
+pub struct FeedSubscription {
+    receiver: broadcast::Receiver<IndexedPost>,
+    cancellation_token: CancellationToken,
+}
+
+impl FeedSubscription {
+    /// The next post, `Err` with the number of posts skipped when this
+    /// subscriber fell behind, or `None` once the server shuts down
+    pub async fn recv(&mut self) -> Option<Result<IndexedPost, u64>> {
+        tokio::select! {
+            // Shutdown wins over posts still buffered
+            biased;
+            () = self.cancellation_token.cancelled() => None,
+            received = self.receiver.recv() => match received {
+                Ok(post) => Some(Ok(post)),
+                Err(RecvError::Lagged(skipped)) => Some(Err(skipped)),
+                Err(RecvError::Closed) => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(feed: &str, uri: &str) -> IndexedPost {
+        IndexedPost {
+            feed: feed.to_string(),
+            uri: uri.to_string(),
+            repost_uri: None,
+            indexed_at: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feed_events() {
+        let token = CancellationToken::new();
+        let events = FeedEvents::new(token.clone());
+
+        // Nothing is kept for later subscribers
+        events.publish(post("feed-a", "before"));
+        let mut subscription = events.subscribe();
+        events.publish(post("feed-a", "after"));
+        assert_eq!(subscription.recv().await.unwrap().unwrap().uri, "after");
+
+        for i in 0..FEED_EVENTS_CAPACITY + 1 {
+            events.publish(post("feed-a", &i.to_string()));
+        }
+        assert_eq!(subscription.recv().await.unwrap().unwrap_err(), 1);
+        assert_eq!(subscription.recv().await.unwrap().unwrap().uri, "1");
+
+        token.cancel();
+        assert!(subscription.recv().await.is_none());
+    }
+}
@@ -0,0 +1,111 @@
+//! Startup check of the feed generator records behind configured feed URIs
+//!
+//! The AppView only calls getFeedSkeleton for a feed URI whose
+//! app.bsky.feed.generator record names this service's DID. A missing or
+//! mismatched record fails silently otherwise, so it is logged on startup.
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::aturi::{AtUri, FEED_GENERATOR_COLLECTION};
+use crate::feed_builder::extract_pds_endpoint;
+use crate::identity::resolve_did_document;
+use crate::redact::redact_tokens;
+
+#[derive(Deserialize)]
+struct GetRecordResponse {
+    value: serde_json::Value,
+}
+
+/// Log a warning for every feed URI whose generator record doesn't point at `service_did`
+pub async fn verify_feed_records(
+    http_client: &reqwest::Client,
+    plc_hostname: &str,
+    service_did: &str,
+    feed_uris: &[String],
+) {
+    for feed_uri in feed_uris {
+        match check_feed_record(http_client, plc_hostname, service_did, feed_uri).await {
+            Ok(()) => tracing::debug!(feed_uri = %feed_uri, "Feed generator record points here"),
+            Err(err) => tracing::warn!(
+                feed_uri = %feed_uri,
+                service_did = %service_did,
+                error = %format!("{:#}", err),
+                "Feed URI is misconfigured, Bluesky will not request it from this service"
+            ),
+        }
+    }
+}
+
+async fn check_feed_record(
+    http_client: &reqwest::Client,
+    plc_hostname: &str,
+    service_did: &str,
+    feed_uri: &str,
+) -> Result<()> {
+    let uri = parse_feed_uri(feed_uri)?;
+    let (repo, rkey) = (uri.authority(), uri.rkey().unwrap_or_default());
+
+    let document = resolve_did_document(http_client, plc_hostname, repo).await?;
+    let pds_url = extract_pds_endpoint(&document)
+        .ok_or_else(|| anyhow!("DID document of {} has no PDS endpoint", repo))?;
+
+    let response = http_client
+        .get(format!("{}/xrpc/com.atproto.repo.getRecord", pds_url))
+        .query(&[
+            ("repo", repo),
+            ("collection", FEED_GENERATOR_COLLECTION),
+            ("rkey", rkey),
+        ])
+        .send()
+        .await
+        .context("Failed to send getRecord request")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!(
+            "No feed generator record found on {}: {} - {}",
+            pds_url,
+            status,
+            redact_tokens(&body)
+        );
+    }
+
+    let record: GetRecordResponse = response
+        .json()
+        .await
+        .context("Failed to parse getRecord response")?;
+
+    match record.value.get("did").and_then(|did| did.as_str()) {
+        Some(did) if did == service_did => Ok(()),
+        Some(did) => anyhow::bail!("Feed generator record points at {} instead", did),
+        None => anyhow::bail!("Feed generator record has no did"),
+    }
+}
+
+/// An app.bsky.feed.generator URI in a repository named by DID
+fn parse_feed_uri(feed_uri: &str) -> Result<AtUri> {
+    let uri = AtUri::parse(feed_uri)?;
+    if !uri.authority().starts_with("did:") || !uri.is_record_in(FEED_GENERATOR_COLLECTION) {
+        anyhow::bail!(
+            "Feed URI must look like at://<did>/{}/<rkey>",
+            FEED_GENERATOR_COLLECTION
+        );
+    }
+    Ok(uri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feed_uri() {
+        let uri = parse_feed_uri("at://did:plc:feedgen/app.bsky.feed.generator/timeline").unwrap();
+        assert_eq!((uri.authority(), uri.rkey()), ("did:plc:feedgen", Some("timeline")));
+        assert!(parse_feed_uri("at://did:plc:feedgen/app.bsky.feed.post/timeline").is_err());
+        assert!(parse_feed_uri("at://alice.example.com/app.bsky.feed.generator/timeline").is_err());
+        assert!(parse_feed_uri("did:plc:feedgen/app.bsky.feed.generator/timeline").is_err());
+    }
+}
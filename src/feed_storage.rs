@@ -1,11 +1,116 @@
+use std::{collections::HashSet, str::FromStr};
+
 use anyhow::{Context, Result};
 use chrono::prelude::*;
-use sqlx::{Execute, Pool, QueryBuilder, Sqlite};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+    Execute, Pool, QueryBuilder, Sqlite,
+};
 
 use model::FeedContent;
 
 pub type StoragePool = Pool<Sqlite>;
 
+/// Open the database pool
+///
+/// WAL lets skeleton reads run while the consumer writes, and busy_timeout makes
+/// a writer wait for the lock instead of failing with "database is locked".
+pub async fn connect(
+    database_url: &str,
+    max_connections: u32,
+    busy_timeout: std::time::Duration,
+) -> Result<StoragePool> {
+    let options = SqliteConnectOptions::from_str(database_url)
+        .with_context(|| format!("invalid DATABASE_URL: {}", database_url))?
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(busy_timeout)
+        .foreign_keys(true);
+
+    SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .connect_with(options)
+        .await
+        .context("failed to open database")
+}
+
+/// Separate pools for writes and for the hot read paths
+///
+/// SQLite allows one writer at a time, so writers queue for the small write
+/// pool instead of the database lock. Skeleton requests and feed cache loads
+/// use read-only connections and never hold up a writer.
+#[derive(Clone)]
+pub struct DatabasePools {
+    pub write: StoragePool,
+    pub read: StoragePool,
+}
+
+/// Open the write pool, then a read-only pool on the same database
+pub async fn connect_pools(
+    database_url: &str,
+    write_connections: u32,
+    read_connections: u32,
+    busy_timeout: std::time::Duration,
+) -> Result<DatabasePools> {
+    let write = connect(database_url, write_connections, busy_timeout).await?;
+
+    // An in-memory database only exists within its pool, so reads share it
+    if database_url.contains(":memory:") || database_url.contains("mode=memory") {
+        return Ok(DatabasePools {
+            read: write.clone(),
+            write,
+        });
+    }
+
+    let read = connect_read_only(database_url, read_connections, busy_timeout).await?;
+
+    Ok(DatabasePools { write, read })
+}
+
+/// Open only a read-only pool, for a read replica of a database written elsewhere
+///
+/// Both pools are the same read-only pool, so any write fails.
+pub async fn connect_replica_pools(
+    database_url: &str,
+    read_connections: u32,
+    busy_timeout: std::time::Duration,
+) -> Result<DatabasePools> {
+    let read = connect_read_only(database_url, read_connections, busy_timeout).await?;
+
+    Ok(DatabasePools {
+        write: read.clone(),
+        read,
+    })
+}
+
+/// Apply pending migrations to the database behind the write pool
+pub async fn migrate(pools: &DatabasePools) -> Result<()> {
+    sqlx::migrate!()
+        .run(&pools.write)
+        .await
+        .context("failed to apply database migrations")
+}
+
+async fn connect_read_only(
+    database_url: &str,
+    read_connections: u32,
+    busy_timeout: std::time::Duration,
+) -> Result<StoragePool> {
+    let options = SqliteConnectOptions::from_str(database_url)
+        .with_context(|| format!("invalid DATABASE_URL: {}", database_url))?;
+
+    SqlitePoolOptions::new()
+        .max_connections(read_connections)
+        .connect_with(
+            options
+                .read_only(true)
+                .create_if_missing(false)
+                .busy_timeout(busy_timeout),
+        )
+        .await
+        .context("failed to open read-only database pool")
+}
+
 pub mod model {
     use chrono::{DateTime, Utc};
     use sqlx::prelude::*;
@@ -18,47 +123,110 @@ pub mod model {
         pub score: i32,
         pub is_repost: bool,
         pub repost_uri: Option<String>,
+        /// DID of the account whose timeline the post came from
+        pub source_did: Option<String>,
+        /// CID of the post record, when known
+        pub cid: Option<String>,
+    }
+
+    /// Text, author and embed of an indexed post
+    #[derive(Clone, FromRow)]
+    pub struct PostMetadata {
+        pub uri: String,
+        pub text: String,
+        pub author_did: Option<String>,
+        pub author_handle: Option<String>,
+        /// `$type` of the record's embed, e.g. "app.bsky.embed.images"
+        pub embed_type: Option<String>,
+    }
+
+    /// A post found by full-text search, with the time it was indexed into the feed
+    #[derive(Clone, FromRow, serde::Serialize)]
+    pub struct PostSearchResult {
+        pub uri: String,
+        pub text: String,
+        pub author_did: Option<String>,
+        pub author_handle: Option<String>,
+        pub embed_type: Option<String>,
+        pub indexed_at: i64,
     }
 
     #[derive(Clone, FromRow)]
     pub struct Denylist {
         pub subject: String,
+        /// None = applies to every feed
+        pub feed_id: Option<String>,
         pub reason: String,
-        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+        pub expires_at: Option<DateTime<Utc>>,
     }
 }
 
+/// What feed_content_upsert did with a post
+#[derive(Debug, PartialEq, Eq)]
+pub enum Upserted {
+    /// New post inserted
+    Inserted,
+    /// Post already in the feed, skipped
+    Duplicate,
+    /// Post already in the feed under another CID, i.e. edited; the CID was updated
+    Edited,
+}
+
 /// Insert or skip feed content
-/// Returns true if a new post was inserted, false if it was a duplicate (skipped)
-pub async fn feed_content_upsert(pool: &StoragePool, feed_content: &FeedContent) -> Result<bool> {
+pub async fn feed_content_upsert(pool: &StoragePool, feed_content: &FeedContent) -> Result<Upserted> {
     // Check if post already exists
-    let exists = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM feed_content WHERE feed_id = ? AND uri = ?"
+    let existing = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT cid FROM feed_content WHERE feed_id = ? AND uri = ?"
     )
     .bind(&feed_content.feed_id)
     .bind(&feed_content.uri)
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await
     .context("failed to check if post exists")?;
 
-    if exists > 0 {
-        // Post already exists - skip it (no UPDATE needed)
-        Ok(false) // Duplicate
-    } else {
-        // Insert new post
-        let now = Utc::now();
-        sqlx::query("INSERT INTO feed_content (feed_id, uri, indexed_at, updated_at, score, is_repost, repost_uri) VALUES (?, ?, ?, ?, ?, ?, ?)")
-            .bind(&feed_content.feed_id)
-            .bind(&feed_content.uri)
-            .bind(feed_content.indexed_at)
-            .bind(now)
-            .bind(feed_content.score)
-            .bind(feed_content.is_repost)
-            .bind(&feed_content.repost_uri)
-            .execute(pool)
-            .await
-            .context("failed to insert feed content record")?;
-        Ok(true) // New post
+    match existing {
+        Some(Some(cid)) if feed_content.cid.as_ref().is_some_and(|new_cid| *new_cid != cid) => {
+            sqlx::query("UPDATE feed_content SET cid = ?, updated_at = ? WHERE feed_id = ? AND uri = ?")
+                .bind(&feed_content.cid)
+                .bind(Utc::now())
+                .bind(&feed_content.feed_id)
+                .bind(&feed_content.uri)
+                .execute(pool)
+                .await
+                .context("failed to update feed content cid")?;
+            Ok(Upserted::Edited)
+        }
+        Some(None) if feed_content.cid.is_some() => {
+            // Rows indexed before CIDs were stored pick theirs up on the next poll
+            sqlx::query("UPDATE feed_content SET cid = ? WHERE feed_id = ? AND uri = ?")
+                .bind(&feed_content.cid)
+                .bind(&feed_content.feed_id)
+                .bind(&feed_content.uri)
+                .execute(pool)
+                .await
+                .context("failed to update feed content cid")?;
+            Ok(Upserted::Duplicate)
+        }
+        Some(_) => Ok(Upserted::Duplicate),
+        None => {
+            // Insert new post
+            let now = Utc::now();
+            sqlx::query("INSERT INTO feed_content (feed_id, uri, indexed_at, updated_at, score, is_repost, repost_uri, source_did, cid) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")
+                .bind(&feed_content.feed_id)
+                .bind(&feed_content.uri)
+                .bind(feed_content.indexed_at)
+                .bind(now)
+                .bind(feed_content.score)
+                .bind(feed_content.is_repost)
+                .bind(&feed_content.repost_uri)
+                .bind(&feed_content.source_did)
+                .bind(&feed_content.cid)
+                .execute(pool)
+                .await
+                .context("failed to insert feed content record")?;
+            Ok(Upserted::Inserted)
+        }
     }
 }
 
@@ -80,6 +248,157 @@ pub async fn feed_content_update(pool: &StoragePool, feed_content: &FeedContent)
     tx.commit().await.context("failed to commit transaction")
 }
 
+/// List every post of a feed, newest first
+pub async fn feed_content_list(pool: &StoragePool, feed_id: &str) -> Result<Vec<FeedContent>> {
+    sqlx::query_as::<_, FeedContent>(
+        "SELECT feed_id, uri, indexed_at, score, is_repost, repost_uri, source_did, cid FROM feed_content WHERE feed_id = ? ORDER BY indexed_at DESC, uri DESC",
+    )
+    .bind(feed_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to list feed content records")
+}
+
+/// A page of a feed's rows, oldest first, within `[since, until)` (microseconds)
+///
+/// `after` is the `(indexed_at, uri)` of the last row of the previous page.
+pub async fn feed_content_page(
+    pool: &StoragePool,
+    feed_id: &str,
+    since: Option<i64>,
+    until: Option<i64>,
+    after: Option<(i64, &str)>,
+    limit: u32,
+) -> Result<Vec<FeedContent>> {
+    let (after_indexed_at, after_uri) = after.unzip();
+    sqlx::query_as::<_, FeedContent>(
+        r#"
+        SELECT feed_id, uri, indexed_at, score, is_repost, repost_uri, source_did, cid
+        FROM feed_content
+        WHERE feed_id = ?
+          AND (? IS NULL OR indexed_at >= ?)
+          AND (? IS NULL OR indexed_at < ?)
+          AND (? IS NULL OR (indexed_at, uri) > (?, ?))
+        ORDER BY indexed_at ASC, uri ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(feed_id)
+    .bind(since)
+    .bind(since)
+    .bind(until)
+    .bind(until)
+    .bind(after_indexed_at)
+    .bind(after_indexed_at)
+    .bind(after_uri)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("failed to page feed content records")
+}
+
+/// Repost URIs of a feed with the time they were indexed, from `since` (microseconds) on
+pub async fn feed_content_reposts_since(
+    pool: &StoragePool,
+    feed_id: &str,
+    since: i64,
+) -> Result<Vec<(String, i64)>> {
+    sqlx::query_as::<_, (String, i64)>(
+        "SELECT repost_uri, indexed_at FROM feed_content WHERE feed_id = ? AND indexed_at >= ? AND repost_uri IS NOT NULL",
+    )
+    .bind(feed_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .context("failed to list feed reposts")
+}
+
+/// Insert feed content in one transaction, skipping posts already in their feed
+/// Returns the number of posts inserted
+pub async fn feed_content_import(pool: &StoragePool, entries: &[FeedContent]) -> Result<u64> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    let now = Utc::now();
+    let mut inserted = 0;
+    for entry in entries {
+        inserted += sqlx::query(
+            "INSERT OR IGNORE INTO feed_content (feed_id, uri, indexed_at, updated_at, score, is_repost, repost_uri, source_did, cid) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&entry.feed_id)
+        .bind(&entry.uri)
+        .bind(entry.indexed_at)
+        .bind(now)
+        .bind(entry.score)
+        .bind(entry.is_repost)
+        .bind(&entry.repost_uri)
+        .bind(&entry.source_did)
+        .bind(&entry.cid)
+        .execute(tx.as_mut())
+        .await
+        .context("failed to insert feed content record")?
+        .rows_affected();
+    }
+
+    tx.commit().await.context("failed to commit transaction")?;
+    Ok(inserted)
+}
+
+/// Store the metadata of a post; a post indexed into several feeds is stored once
+pub async fn post_metadata_upsert(pool: &StoragePool, metadata: &model::PostMetadata) -> Result<()> {
+    sqlx::query(
+        "INSERT OR IGNORE INTO post_metadata (uri, text, author_did, author_handle, embed_type, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&metadata.uri)
+    .bind(&metadata.text)
+    .bind(&metadata.author_did)
+    .bind(&metadata.author_handle)
+    .bind(&metadata.embed_type)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .context("failed to insert post metadata")?;
+    Ok(())
+}
+
+/// Search the text of a feed's posts, newest first
+///
+/// The query is matched as a phrase, so FTS5 operators in it are taken literally.
+pub async fn post_metadata_search(
+    pool: &StoragePool,
+    feed_id: &str,
+    query: &str,
+    limit: u32,
+) -> Result<Vec<model::PostSearchResult>> {
+    let phrase = format!("\"{}\"", query.replace('"', "\"\""));
+
+    sqlx::query_as::<_, model::PostSearchResult>(
+        r#"
+        SELECT m.uri, m.text, m.author_did, m.author_handle, m.embed_type, c.indexed_at
+        FROM post_metadata_fts f
+        JOIN post_metadata m ON m.rowid = f.rowid
+        JOIN feed_content c ON c.uri = m.uri AND c.feed_id = ?
+        WHERE post_metadata_fts MATCH ?
+        ORDER BY c.indexed_at DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(feed_id)
+    .bind(phrase)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await
+    .context("failed to search post metadata")
+}
+
+/// Delete stored post text no feed refers to anymore
+pub async fn post_metadata_remove_orphaned(pool: &StoragePool) -> Result<()> {
+    sqlx::query("DELETE FROM post_metadata WHERE uri NOT IN (SELECT uri FROM feed_content)")
+        .execute(pool)
+        .await
+        .context("failed to delete orphaned post metadata")?;
+    Ok(())
+}
+
 pub async fn feed_content_truncate_oldest(pool: &StoragePool, age: DateTime<Utc>) -> Result<()> {
     let mut tx = pool.begin().await.context("failed to begin transaction")?;
 
@@ -107,15 +426,107 @@ pub async fn denylist_insert(pool: &StoragePool, subject: &str, reason: &str) ->
     tx.commit().await.context("failed to commit transaction")
 }
 
-pub async fn denylist_upsert(pool: &StoragePool, subject: &str, reason: &str) -> Result<()> {
-    denylist_insert(pool, subject, reason).await
+/// Denylist a subject and purge content it already contributed
+///
+/// The entry is scoped to `feed` when set, otherwise it applies to every feed.
+/// When the subject is a DID, existing feed_content rows authored by it (uri) or
+/// reposted by it (repost_uri) are deleted from the same scope in the same
+/// transaction. Entries with an `expires_at` stop matching once it has passed.
+pub async fn denylist_upsert(
+    pool: &StoragePool,
+    subject: &str,
+    reason: &str,
+    expires_at: Option<DateTime<Utc>>,
+    feed: &Option<String>,
+) -> Result<()> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    denylist_upsert_tx(&mut tx, subject, reason, expires_at, feed).await?;
+
+    tx.commit().await.context("failed to commit transaction")
+}
+
+/// Denylist many subjects at once, all or nothing
+pub async fn denylist_import(pool: &StoragePool, entries: &[model::Denylist]) -> Result<()> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    for entry in entries {
+        denylist_upsert_tx(
+            &mut tx,
+            &entry.subject,
+            &entry.reason,
+            entry.expires_at,
+            &entry.feed_id,
+        )
+        .await?;
+    }
+
+    tx.commit().await.context("failed to commit transaction")
+}
+
+async fn denylist_upsert_tx(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    subject: &str,
+    reason: &str,
+    expires_at: Option<DateTime<Utc>>,
+    feed: &Option<String>,
+) -> Result<()> {
+    let now = Utc::now();
+    sqlx::query(
+        "INSERT OR REPLACE INTO denylist (subject, feed_id, reason, updated_at, expires_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(subject)
+    .bind(feed)
+    .bind(reason)
+    .bind(now)
+    .bind(expires_at)
+    .execute(tx.as_mut())
+    .await
+    .context("failed to upsert denylist record")?;
+
+    if subject.starts_with("did:") {
+        let prefix = format!("at://{}/", subject);
+        feed_content_purge_prefix(tx, &prefix, feed).await?;
+    }
+
+    Ok(())
+}
+
+/// List all active (non-expired) denylist entries, global and feed-scoped
+pub async fn denylist_list(pool: &StoragePool) -> Result<Vec<model::Denylist>> {
+    sqlx::query_as::<_, model::Denylist>(
+        "SELECT subject, feed_id, reason, updated_at, expires_at FROM denylist WHERE expires_at IS NULL OR expires_at > ? ORDER BY subject, feed_id",
+    )
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await
+    .context("failed to list denylist records")
 }
 
-pub async fn denylist_remove(pool: &StoragePool, subject: &str) -> Result<()> {
+/// Delete denylist entries whose expiry has passed
+pub async fn denylist_remove_expired(pool: &StoragePool, now: DateTime<Utc>) -> Result<()> {
     let mut tx = pool.begin().await.context("failed to begin transaction")?;
 
-    sqlx::query("DELETE FROM denylist WHERE subject = ?")
+    sqlx::query("DELETE FROM denylist WHERE expires_at IS NOT NULL AND expires_at <= ?")
+        .bind(now)
+        .execute(tx.as_mut())
+        .await
+        .context("failed to delete expired denylist records")?;
+
+    tx.commit().await.context("failed to commit transaction")
+}
+
+/// Remove a denylist entry from one scope (None = the global entry)
+pub async fn denylist_remove(
+    pool: &StoragePool,
+    subject: &str,
+    feed: &Option<String>,
+) -> Result<()> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    sqlx::query("DELETE FROM denylist WHERE subject = ? AND feed_id IS ?")
         .bind(subject)
+        .bind(feed)
         .execute(tx.as_mut())
         .await
         .context("failed to delete denylist record")?;
@@ -148,27 +559,436 @@ pub async fn feed_content_purge_aturi(
     tx.commit().await.context("failed to commit transaction")
 }
 
-pub async fn denylist_exists(pool: &StoragePool, subjects: &[&str]) -> Result<bool> {
+/// Delete feed content authored or reposted by a DID
+pub async fn feed_content_purge_did(
+    pool: &StoragePool,
+    did: &str,
+    feed: &Option<String>,
+) -> Result<()> {
     let mut tx = pool.begin().await.context("failed to begin transaction")?;
 
+    let prefix = format!("at://{}/", did);
+    feed_content_purge_prefix(&mut tx, &prefix, feed).await?;
+
+    tx.commit().await.context("failed to commit transaction")
+}
+
+async fn feed_content_purge_prefix(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    prefix: &str,
+    feed: &Option<String>,
+) -> Result<()> {
+    if let Some(feed) = feed {
+        sqlx::query(
+            "DELETE FROM feed_content WHERE feed_id = ? AND (instr(uri, ?) = 1 OR instr(repost_uri, ?) = 1)",
+        )
+        .bind(feed)
+        .bind(prefix)
+        .bind(prefix)
+        .execute(tx.as_mut())
+        .await
+        .context("failed to purge feed content by prefix")?;
+    } else {
+        sqlx::query("DELETE FROM feed_content WHERE instr(uri, ?) = 1 OR instr(repost_uri, ?) = 1")
+            .bind(prefix)
+            .bind(prefix)
+            .execute(tx.as_mut())
+            .await
+            .context("failed to purge feed content by prefix")?;
+    }
+
+    Ok(())
+}
+
+/// Check whether any subject is denylisted globally or for `feed`
+pub async fn denylist_exists(
+    pool: &StoragePool,
+    subjects: &[&str],
+    feed: &Option<String>,
+) -> Result<bool> {
+    Ok(!denylist_matching(pool, subjects, feed).await?.is_empty())
+}
+
+/// Return the subset of subjects that are denylisted globally or for `feed`
+pub async fn denylist_matching(
+    pool: &StoragePool,
+    subjects: &[&str],
+    feed: &Option<String>,
+) -> Result<HashSet<String>> {
+    if subjects.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let now = Utc::now();
     let mut query_builder: QueryBuilder<Sqlite> =
-        QueryBuilder::new("SELECT COUNT(*) FROM denylist WHERE subject IN (");
+        QueryBuilder::new("SELECT DISTINCT subject FROM denylist WHERE subject IN (");
     let mut separated = query_builder.separated(", ");
     for subject in subjects {
         separated.push_bind(subject);
     }
     separated.push_unseparated(") ");
+    query_builder.push("AND (feed_id IS NULL OR feed_id = ");
+    query_builder.push_bind(feed);
+    query_builder.push(") AND (expires_at IS NULL OR expires_at > ");
+    query_builder.push_bind(now);
+    query_builder.push(")");
 
-    let mut query = sqlx::query_scalar::<_, i64>(query_builder.build().sql());
+    let mut query = sqlx::query_scalar::<_, String>(query_builder.build().sql());
     for subject in subjects {
         query = query.bind(subject);
     }
-    let count = query
-        .fetch_one(tx.as_mut())
+    query = query.bind(feed).bind(now);
+    let matching = query
+        .fetch_all(pool)
         .await
-        .context("failed to delete denylist record")?;
+        .context("failed to query denylist records")?;
 
-    tx.commit().await.context("failed to commit transaction")?;
+    Ok(matching.into_iter().collect())
+}
+
+/// List the posts pinned to a feed, in display order
+pub async fn feed_pins_list(pool: &StoragePool, feed_id: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT uri FROM feed_pins WHERE feed_id = ? ORDER BY position",
+    )
+    .bind(feed_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to select feed pins")
+}
+
+/// Replace the posts pinned to a feed; an empty list removes all pins
+pub async fn feed_pins_replace(pool: &StoragePool, feed_id: &str, uris: &[String]) -> Result<()> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    sqlx::query("DELETE FROM feed_pins WHERE feed_id = ?")
+        .bind(feed_id)
+        .execute(tx.as_mut())
+        .await
+        .context("failed to delete feed pins")?;
+
+    let now = Utc::now();
+    for (position, uri) in uris.iter().enumerate() {
+        sqlx::query(
+            "INSERT OR IGNORE INTO feed_pins (feed_id, uri, position, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(feed_id)
+        .bind(uri)
+        .bind(position as i64)
+        .bind(now)
+        .execute(tx.as_mut())
+        .await
+        .context("failed to insert feed pin")?;
+    }
+
+    tx.commit().await.context("failed to commit transaction")
+}
+
+/// Get a cached verification method (multikey) for a DID, if fresher than `min_updated_at`
+pub async fn verification_method_get(
+    pool: &StoragePool,
+    did: &str,
+    min_updated_at: DateTime<Utc>,
+) -> Result<Option<(String, DateTime<Utc>)>> {
+    sqlx::query_as::<_, (String, DateTime<Utc>)>(
+        "SELECT multikey, updated_at FROM verification_method_cache WHERE did = ? AND updated_at >= ?",
+    )
+    .bind(did)
+    .bind(min_updated_at)
+    .fetch_optional(pool)
+    .await
+    .context("failed to select verification method record")
+}
 
-    Ok(count > 0)
+pub async fn verification_method_upsert(
+    pool: &StoragePool,
+    did: &str,
+    multikey: &str,
+) -> Result<()> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    let now = Utc::now();
+    sqlx::query(
+        "INSERT OR REPLACE INTO verification_method_cache (did, multikey, updated_at) VALUES (?, ?, ?)",
+    )
+    .bind(did)
+    .bind(multikey)
+    .bind(now)
+    .execute(tx.as_mut())
+    .await
+    .context("failed to upsert verification method record")?;
+
+    tx.commit().await.context("failed to commit transaction")
+}
+
+/// List cached DIDs last refreshed before `updated_before`
+pub async fn verification_method_stale(
+    pool: &StoragePool,
+    updated_before: DateTime<Utc>,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT did FROM verification_method_cache WHERE updated_at < ? ORDER BY updated_at",
+    )
+    .bind(updated_before)
+    .fetch_all(pool)
+    .await
+    .context("failed to select stale verification method records")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+
+    async fn setup_test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_connect_pools_read_only() {
+        let dir = std::env::temp_dir().join(format!(
+            "timeline-filter-pools-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let url = format!("sqlite://{}/pools.db?mode=rwc", dir.display());
+
+        let pools = connect_pools(&url, 1, 2, std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pools.write).await.unwrap();
+        sqlx::query("INSERT INTO denylist (subject, reason, updated_at) VALUES ('did:plc:spam', 'spam', '2025-01-01T00:00:00Z')")
+            .execute(&pools.write)
+            .await
+            .unwrap();
+
+        // Reads see committed writes, but cannot write themselves
+        assert_eq!(denylist_list(&pools.read).await.unwrap().len(), 1);
+        assert!(sqlx::query("DELETE FROM denylist").execute(&pools.read).await.is_err());
+
+        // A replica cannot write through either pool
+        let replica = connect_replica_pools(&url, 2, std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(denylist_list(&replica.read).await.unwrap().len(), 1);
+        assert!(sqlx::query("DELETE FROM denylist").execute(&replica.write).await.is_err());
+        replica.read.close().await;
+
+        pools.write.close().await;
+        pools.read.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    async fn insert(pool: &StoragePool, feed_id: &str, uri: &str, repost_uri: Option<&str>) {
+        feed_content_upsert(
+            pool,
+            &FeedContent {
+                feed_id: feed_id.to_string(),
+                uri: uri.to_string(),
+                indexed_at: 1,
+                score: 1,
+                is_repost: repost_uri.is_some(),
+                repost_uri: repost_uri.map(str::to_string),
+                source_did: None,
+                cid: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn count(pool: &StoragePool, feed_id: &str) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM feed_content WHERE feed_id = ?")
+            .bind(feed_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_feed_content_upsert_keeps_first_repost() {
+        let pool = setup_test_pool().await;
+        let post = "at://did:plc:author/app.bsky.feed.post/1";
+
+        // Several followed accounts repost the same post: one row, first reason kept
+        for reposter in ["first", "second", "third"] {
+            let repost_uri = format!("at://did:plc:{}/app.bsky.feed.repost/1", reposter);
+            insert(&pool, "feed-a", post, Some(&repost_uri)).await;
+        }
+
+        let content = feed_content_list(&pool, "feed-a").await.unwrap();
+        assert_eq!(content.len(), 1);
+        assert_eq!(
+            content[0].repost_uri.as_deref(),
+            Some("at://did:plc:first/app.bsky.feed.repost/1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_feed_content_upsert_detects_edits() {
+        let pool = setup_test_pool().await;
+        let content = |cid: Option<&str>| FeedContent {
+            feed_id: "feed-a".to_string(),
+            uri: "at://did:plc:author/app.bsky.feed.post/1".to_string(),
+            indexed_at: 1,
+            score: 1,
+            is_repost: false,
+            repost_uri: None,
+            source_did: None,
+            cid: cid.map(str::to_string),
+        };
+
+        let upsert = |cid| {
+            let pool = pool.clone();
+            async move { feed_content_upsert(&pool, &content(cid)).await.unwrap() }
+        };
+        assert_eq!(upsert(Some("cid-1")).await, Upserted::Inserted);
+        assert_eq!(upsert(Some("cid-1")).await, Upserted::Duplicate);
+        assert_eq!(upsert(None).await, Upserted::Duplicate);
+        assert_eq!(upsert(Some("cid-2")).await, Upserted::Edited);
+
+        let stored = feed_content_list(&pool, "feed-a").await.unwrap();
+        assert_eq!(stored[0].cid.as_deref(), Some("cid-2"));
+    }
+
+    #[tokio::test]
+    async fn test_feed_content_page() {
+        let pool = setup_test_pool().await;
+        for (indexed_at, post) in [(10, "a"), (20, "b"), (20, "c"), (30, "d")] {
+            feed_content_upsert(
+                &pool,
+                &FeedContent {
+                    feed_id: "feed-a".to_string(),
+                    uri: format!("at://did:plc:author/app.bsky.feed.post/{}", post),
+                    indexed_at,
+                    score: 1,
+                    is_repost: false,
+                    repost_uri: None,
+                    source_did: None,
+                    cid: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let first = feed_content_page(&pool, "feed-a", Some(20), None, None, 2).await.unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].uri, "at://did:plc:author/app.bsky.feed.post/b");
+
+        // Rows sharing indexed_at are split across pages by uri
+        let last = &first[1];
+        let after = Some((last.indexed_at, last.uri.as_str()));
+        let next = feed_content_page(&pool, "feed-a", Some(20), None, after, 2).await.unwrap();
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].uri, "at://did:plc:author/app.bsky.feed.post/d");
+
+        let until = feed_content_page(&pool, "feed-a", None, Some(20), None, 10).await.unwrap();
+        assert_eq!(until.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_denylist_upsert_purges_existing_content() {
+        let pool = setup_test_pool().await;
+
+        insert(&pool, "feed-a", "at://did:plc:spam/app.bsky.feed.post/1", None).await;
+        insert(
+            &pool,
+            "feed-a",
+            "at://did:plc:ok/app.bsky.feed.post/2",
+            Some("at://did:plc:spam/app.bsky.feed.repost/3"),
+        )
+        .await;
+        insert(&pool, "feed-a", "at://did:plc:spammer/app.bsky.feed.post/4", None).await;
+        insert(&pool, "feed-b", "at://did:plc:spam/app.bsky.feed.post/1", None).await;
+
+        denylist_upsert(&pool, "did:plc:spam", "spam", None, &Some("feed-a".to_string()))
+            .await
+            .unwrap();
+
+        // Only the prefix-exact DID is purged, and only in the scoped feed
+        assert_eq!(count(&pool, "feed-a").await, 1);
+        assert_eq!(count(&pool, "feed-b").await, 1);
+        assert!(denylist_exists(&pool, &["did:plc:spam"], &Some("feed-a".to_string()))
+            .await
+            .unwrap());
+        assert!(!denylist_exists(&pool, &["did:plc:spam"], &Some("feed-b".to_string()))
+            .await
+            .unwrap());
+
+        denylist_upsert(&pool, "did:plc:spam", "spam", None, &None)
+            .await
+            .unwrap();
+        assert_eq!(count(&pool, "feed-b").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_denylist_expiry() {
+        let pool = setup_test_pool().await;
+
+        let past = Utc::now() - chrono::Duration::hours(1);
+        let future = Utc::now() + chrono::Duration::hours(1);
+        denylist_upsert(&pool, "did:plc:expired", "timeout", Some(past), &None)
+            .await
+            .unwrap();
+        denylist_upsert(&pool, "did:plc:active", "timeout", Some(future), &None)
+            .await
+            .unwrap();
+
+        assert!(!denylist_exists(&pool, &["did:plc:expired"], &None).await.unwrap());
+        assert!(denylist_exists(&pool, &["did:plc:active"], &None).await.unwrap());
+
+        let listed = denylist_list(&pool).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].subject, "did:plc:active");
+
+        denylist_remove_expired(&pool, Utc::now()).await.unwrap();
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM denylist")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_metadata_search() {
+        let pool = setup_test_pool().await;
+
+        insert(&pool, "feed-a", "at://did:plc:a/app.bsky.feed.post/1", None).await;
+        insert(&pool, "feed-b", "at://did:plc:a/app.bsky.feed.post/2", None).await;
+        for (uri, text) in [
+            ("at://did:plc:a/app.bsky.feed.post/1", "Cats are \"great\""),
+            ("at://did:plc:a/app.bsky.feed.post/2", "cats again"),
+        ] {
+            let metadata = model::PostMetadata {
+                uri: uri.to_string(),
+                text: text.to_string(),
+                author_did: Some("did:plc:a".to_string()),
+                author_handle: None,
+                embed_type: None,
+            };
+            post_metadata_upsert(&pool, &metadata).await.unwrap();
+        }
+
+        // Only the feed's own posts match, and quotes in the query are literal
+        let found = post_metadata_search(&pool, "feed-a", "cats", 10).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].uri, "at://did:plc:a/app.bsky.feed.post/1");
+        assert_eq!(found[0].author_did.as_deref(), Some("did:plc:a"));
+        assert!(post_metadata_search(&pool, "feed-a", "\"great", 10).await.is_ok());
+        assert!(post_metadata_search(&pool, "feed-a", "dogs", 10).await.unwrap().is_empty());
+
+        sqlx::query("DELETE FROM feed_content WHERE feed_id = 'feed-b'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        post_metadata_remove_orphaned(&pool).await.unwrap();
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM post_metadata_fts WHERE post_metadata_fts MATCH 'cats'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
 }
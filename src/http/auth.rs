@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{
+        header::{AUTHORIZATION, COOKIE},
+        request::Parts,
+    },
+    response::Redirect,
+};
+use chrono::Utc;
+use subtle::ConstantTimeEq;
+
+use crate::errors::TimelineFilterError;
+use crate::user_storage::session_get_did;
+
+use super::context::WebContext;
+
+/// Name of the cookie carrying the self-service session id
+pub(crate) const SESSION_COOKIE: &str = "timeline_filter_session";
+
+/// Extractor guarding operator-only routes
+///
+/// Requires `Authorization: Bearer <ADMIN_TOKEN>`. When no admin token is
+/// configured every admin route responds 404 so the surface is not advertised.
+pub struct AdminAuth;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminAuth
+where
+    WebContext: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = TimelineFilterError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let web_context = WebContext::from_ref(state);
+
+        let Some(admin_token) = web_context.admin_token.as_ref() else {
+            return Err(TimelineFilterError::NotFound("not found".to_string()));
+        };
+
+        let provided = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match provided {
+            Some(token) if admin_token_matches(token, admin_token) => Ok(AdminAuth),
+            _ => Err(TimelineFilterError::AuthRequired(
+                "admin token required".to_string(),
+            )),
+        }
+    }
+}
+
+/// Extractor for a signed-in self-service user
+///
+/// Reads the session cookie set by the login flow. Missing, unknown or
+/// expired sessions are redirected to the login page.
+pub struct WebSession {
+    pub session_id: String,
+    pub did: String,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for WebSession
+where
+    WebContext: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Redirect;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let web_context = WebContext::from_ref(state);
+
+        let Some(session_id) = session_cookie(parts) else {
+            return Err(Redirect::to("/login"));
+        };
+
+        match session_get_did(&web_context.pool, &session_id, Utc::now()).await {
+            Ok(Some(did)) => Ok(WebSession { session_id, did }),
+            Ok(None) => Err(Redirect::to("/login")),
+            Err(err) => {
+                tracing::error!(error = ?err, "Failed to load web session");
+                Err(Redirect::to("/login"))
+            }
+        }
+    }
+}
+
+/// Extractor for the JSON API
+///
+/// Accepts either the operator's `Authorization: Bearer <ADMIN_TOKEN>` or a
+/// self-service session cookie. Session users may only act on their own DID.
+pub enum ApiAuth {
+    Admin,
+    User(String),
+}
+
+impl ApiAuth {
+    /// Whether the caller may read and change data belonging to `did`
+    pub fn can_access(&self, did: &str) -> bool {
+        match self {
+            ApiAuth::Admin => true,
+            ApiAuth::User(user_did) => user_did == did,
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ApiAuth
+where
+    WebContext: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = TimelineFilterError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let web_context = WebContext::from_ref(state);
+
+        let bearer = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if let (Some(token), Some(admin_token)) = (bearer, web_context.admin_token.as_ref()) {
+            if admin_token_matches(token, admin_token) {
+                return Ok(ApiAuth::Admin);
+            }
+        }
+
+        let auth_required =
+            || TimelineFilterError::AuthRequired("admin token or session required".to_string());
+
+        let Some(session_id) = session_cookie(parts) else {
+            return Err(auth_required());
+        };
+
+        match session_get_did(&web_context.pool, &session_id, Utc::now()).await? {
+            Some(did) => Ok(ApiAuth::User(did)),
+            None => Err(auth_required()),
+        }
+    }
+}
+
+/// Compare a bearer token with the admin token in constant time
+///
+/// Only the length can leak; the bytes are compared without short-circuiting.
+fn admin_token_matches(token: &str, admin_token: &str) -> bool {
+    token.len() == admin_token.len() && bool::from(token.as_bytes().ct_eq(admin_token.as_bytes()))
+}
+
+/// Find the session id in the request's Cookie headers
+fn session_cookie(parts: &Parts) -> Option<String> {
+    parts
+        .headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_token_matches() {
+        assert!(admin_token_matches("secret-token", "secret-token"));
+        assert!(!admin_token_matches("secret-tokem", "secret-token"));
+        assert!(!admin_token_matches("secret", "secret-token"));
+        assert!(!admin_token_matches("", "secret-token"));
+    }
+}
@@ -1,14 +1,45 @@
+use anyhow::Result;
 use axum::extract::FromRef;
+use chrono::{DateTime, Duration, Utc};
 use std::{
     ops::Deref,
     sync::Arc,
 };
+use tokio::sync::RwLock;
 
-use crate::feed_storage::StoragePool;
+use crate::cache::FeedCache;
+use crate::feed_events::FeedEvents;
+use crate::feed_storage::{DatabasePools, StoragePool};
+use crate::serve_log::ServeLog;
+use crate::server_config::ServiceIdentity;
+use crate::user_storage::{get_feed_descriptions, FeedDescription};
+use crate::vmc::VerificationMethodCache;
+
+use super::rate_limit::{LoginLimiter, RateLimiter};
+
+/// How long describeFeedGenerator serves the feed list before reloading it
+const FEED_DESCRIPTIONS_TTL: Duration = Duration::seconds(60);
+
+/// Feed list of describeFeedGenerator and when it was loaded
+type CachedFeedDescriptions = Option<(DateTime<Utc>, Arc<Vec<FeedDescription>>)>;
 
 pub struct InnerWebContext {
+    /// Write pool, also used for reads that must see the caller's own writes
     pub(crate) pool: StoragePool,
-    pub(crate) external_base: String,
+    pub(crate) read_pool: StoragePool,
+    pub(crate) http_client: reqwest::Client,
+    pub(crate) service: ServiceIdentity,
+    pub(crate) admin_token: Option<String>,
+    pub(crate) feed_cache: FeedCache,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    /// Always on, unlike the XRPC rate limit
+    pub(crate) login_limiter: LoginLimiter,
+    pub(crate) verification_method_cache: VerificationMethodCache,
+    /// Records which posts getFeedSkeleton served, when SERVE_LOG is set
+    pub(crate) serve_log: ServeLog,
+    /// Posts as the consumer indexes them, for feed streams
+    pub(crate) feed_events: FeedEvents,
+    feed_descriptions: RwLock<CachedFeedDescriptions>,
 }
 
 #[derive(Clone, FromRef)]
@@ -23,13 +54,50 @@ impl Deref for WebContext {
 }
 
 impl WebContext {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        pool: StoragePool,
-        external_base: &str,
+        pools: DatabasePools,
+        http_client: reqwest::Client,
+        service: ServiceIdentity,
+        admin_token: Option<String>,
+        feed_cache: FeedCache,
+        rate_limiter: Option<RateLimiter>,
+        login_limiter: LoginLimiter,
+        verification_method_cache: VerificationMethodCache,
+        serve_log: ServeLog,
+        feed_events: FeedEvents,
     ) -> Self {
         Self(Arc::new(InnerWebContext {
-            pool,
-            external_base: external_base.to_string(),
+            pool: pools.write,
+            read_pool: pools.read,
+            http_client,
+            service,
+            admin_token,
+            feed_cache,
+            rate_limiter,
+            login_limiter,
+            verification_method_cache,
+            serve_log,
+            feed_events,
+            feed_descriptions: RwLock::new(None),
         }))
     }
+
+    /// DID of this feed generator, from SERVICE_DID or EXTERNAL_BASE
+    pub fn service_did(&self) -> String {
+        self.service.service_did.clone()
+    }
+
+    /// Name and description of every served feed, reloaded at most once a minute
+    pub async fn feed_descriptions(&self) -> Result<Arc<Vec<FeedDescription>>> {
+        if let Some((loaded_at, feeds)) = self.feed_descriptions.read().await.as_ref() {
+            if Utc::now() - *loaded_at < FEED_DESCRIPTIONS_TTL {
+                return Ok(feeds.clone());
+            }
+        }
+
+        let feeds = Arc::new(get_feed_descriptions(&self.read_pool).await?);
+        *self.feed_descriptions.write().await = Some((Utc::now(), feeds.clone()));
+        Ok(feeds)
+    }
 }
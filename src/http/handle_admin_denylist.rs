@@ -0,0 +1,369 @@
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::State,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::Query;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::aturi::AtUri;
+use crate::errors::TimelineFilterError;
+use crate::feed_storage::{denylist_import, denylist_list, denylist_upsert, model::Denylist};
+
+use super::{auth::AdminAuth, context::WebContext};
+
+#[derive(Deserialize, Default)]
+pub struct DenylistImportParams {
+    /// Reason applied to rows that don't carry their own
+    pub reason: Option<String>,
+    /// Expiry applied to rows that don't carry their own, e.g. "24h" or "7d"
+    pub expires_in: Option<String>,
+    /// Feed URI applied to rows that don't carry their own; unset = global
+    pub feed: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DenylistAddBody {
+    /// DID or AT-URI to deny
+    pub subject: String,
+    pub reason: Option<String>,
+    /// How long the entry applies, e.g. "24h" or "7d"; unset = forever
+    pub expires_in: Option<String>,
+    /// Feed URI the entry is scoped to; unset = global
+    pub feed: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct DenylistExportParams {
+    /// "csv" (default) or "text" for newline-separated subjects
+    pub format: Option<String>,
+}
+
+/// Bulk import denylist entries
+///
+/// The body is either newline-separated subjects or CSV rows of
+/// `subject,reason,expires_at,feed_id` (all but subject are optional; an empty
+/// feed_id means the entry applies to every feed).
+/// Blank lines, `#` comments and a `subject,...` header as the first row are ignored.
+/// The import is applied in a single transaction.
+pub async fn handle_admin_denylist_import(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Query(params): Query<DenylistImportParams>,
+    body: String,
+) -> Result<Response, TimelineFilterError> {
+    let default_reason = params.reason.unwrap_or_else(|| "imported".to_string());
+    let default_expires_at = parse_expires_in(params.expires_in.as_deref())?;
+
+    let entries = match parse_denylist_import(
+        &body,
+        &default_reason,
+        default_expires_at,
+        params.feed.as_deref(),
+    ) {
+        Ok(entries) => entries,
+        Err(err) => return Err(TimelineFilterError::InvalidRequest(format!("{:#}", err))),
+    };
+
+    denylist_import(&web_context.pool, &entries).await?;
+    invalidate_denied_feeds(&web_context, &entries).await;
+
+    tracing::info!(count = entries.len(), "Imported denylist entries");
+
+    Ok(Json(json!({"imported": entries.len()})).into_response())
+}
+
+/// Handle POST /admin/denylist
+///
+/// Denies one subject and, when it is a DID, purges the posts it already
+/// authored or reposted from the entry's scope.
+pub async fn handle_admin_denylist_add(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Json(body): Json<DenylistAddBody>,
+) -> Result<Response, TimelineFilterError> {
+    let subject = body.subject.trim();
+    if !subject.starts_with("did:") && AtUri::parse(subject).is_err() {
+        return Err(TimelineFilterError::InvalidRequest(format!(
+            "subject must be a DID or AT-URI: {}",
+            subject
+        )));
+    }
+    if let Some(feed) = body.feed.as_deref().filter(|feed| AtUri::parse(feed).is_err()) {
+        return Err(TimelineFilterError::InvalidRequest(format!(
+            "feed must be an AT-URI: {}",
+            feed
+        )));
+    }
+
+    let entry = Denylist {
+        subject: subject.to_string(),
+        feed_id: body.feed,
+        reason: body.reason.unwrap_or_else(|| "denied".to_string()),
+        updated_at: Utc::now(),
+        expires_at: parse_expires_in(body.expires_in.as_deref())?,
+    };
+
+    denylist_upsert(
+        &web_context.pool,
+        &entry.subject,
+        &entry.reason,
+        entry.expires_at,
+        &entry.feed_id,
+    )
+    .await?;
+    invalidate_denied_feeds(&web_context, std::slice::from_ref(&entry)).await;
+
+    tracing::info!(subject = %entry.subject, feed = ?entry.feed_id, "Added denylist entry");
+
+    Ok(Json(json!({
+        "subject": entry.subject,
+        "feed_id": entry.feed_id,
+        "reason": entry.reason,
+        "expires_at": entry.expires_at.map(|expires_at| expires_at.to_rfc3339()),
+    }))
+    .into_response())
+}
+
+/// Denying purges matching posts; drop cached pages so they stop being served
+async fn invalidate_denied_feeds(web_context: &WebContext, entries: &[Denylist]) {
+    if entries.iter().any(|entry| entry.feed_id.is_none()) {
+        web_context.feed_cache.invalidate_all().await;
+    } else {
+        for feed_id in entries.iter().filter_map(|entry| entry.feed_id.as_deref()) {
+            web_context.feed_cache.invalidate(feed_id).await;
+        }
+    }
+}
+
+/// Expiry of an entry given as a duration from now, e.g. "7d"
+fn parse_expires_in(expires_in: Option<&str>) -> Result<Option<DateTime<Utc>>, TimelineFilterError> {
+    match expires_in {
+        Some(expires_in) => match duration_str::parse_chrono(expires_in) {
+            Ok(duration) => Ok(Some(Utc::now() + duration)),
+            Err(err) => Err(TimelineFilterError::InvalidRequest(format!(
+                "invalid expires_in: {}",
+                err
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Export active denylist entries as CSV or newline-separated subjects
+///
+/// The text format only carries subjects, so it lists global entries only.
+pub async fn handle_admin_denylist_export(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Query(params): Query<DenylistExportParams>,
+) -> Result<Response, TimelineFilterError> {
+    let entries = denylist_list(&web_context.pool).await?;
+
+    let body = match params.format.as_deref() {
+        Some("text") => entries
+            .iter()
+            .filter(|entry| entry.feed_id.is_none())
+            .map(|entry| format!("{}\n", entry.subject))
+            .collect::<String>(),
+        Some("csv") | None => denylist_csv(&entries),
+        Some(other) => {
+            return Err(TimelineFilterError::InvalidRequest(format!(
+                "unsupported format: {}",
+                other
+            )))
+        }
+    };
+
+    let content_type = if params.format.as_deref() == Some("text") {
+        "text/plain; charset=utf-8"
+    } else {
+        "text/csv; charset=utf-8"
+    };
+
+    Ok(([(CONTENT_TYPE, content_type)], body).into_response())
+}
+
+/// Denylist entries as CSV accepted by the import, with a header row
+pub fn denylist_csv(entries: &[Denylist]) -> String {
+    let mut body = String::from("subject,reason,expires_at,feed_id\n");
+    for entry in entries {
+        body.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&entry.subject),
+            csv_field(&entry.reason),
+            entry
+                .expires_at
+                .map(|expires_at| expires_at.to_rfc3339())
+                .unwrap_or_default(),
+            entry.feed_id.as_deref().map(csv_field).unwrap_or_default()
+        ));
+    }
+    body
+}
+
+/// Parse an import body into denylist entries
+fn parse_denylist_import(
+    body: &str,
+    default_reason: &str,
+    default_expires_at: Option<DateTime<Utc>>,
+    default_feed: Option<&str>,
+) -> Result<Vec<Denylist>> {
+    let now = Utc::now();
+    let mut entries = Vec::new();
+    let mut seen_first_row = false;
+
+    for (idx, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // The header may follow leading comments and blank lines
+        let fields = split_csv_line(line);
+        let subject = fields[0].trim();
+        let first_row = !seen_first_row;
+        seen_first_row = true;
+        if first_row && subject == "subject" {
+            continue;
+        }
+
+        if !subject.starts_with("did:") && AtUri::parse(subject).is_err() {
+            return Err(anyhow!(
+                "line {}: subject must be a DID or AT-URI: {}",
+                idx + 1,
+                subject
+            ));
+        }
+
+        let reason = fields
+            .get(1)
+            .map(|reason| reason.trim())
+            .filter(|reason| !reason.is_empty())
+            .unwrap_or(default_reason);
+
+        let expires_at = match fields.get(2).map(|value| value.trim()) {
+            Some(value) if !value.is_empty() => Some(
+                DateTime::parse_from_rfc3339(value)
+                    .with_context(|| format!("line {}: invalid expires_at: {}", idx + 1, value))?
+                    .with_timezone(&Utc),
+            ),
+            _ => default_expires_at,
+        };
+
+        let feed_id = match fields.get(3).map(|value| value.trim()) {
+            Some(value) if !value.is_empty() => {
+                if AtUri::parse(value).is_err() {
+                    return Err(anyhow!("line {}: feed_id must be an AT-URI: {}", idx + 1, value));
+                }
+                Some(value.to_string())
+            }
+            _ => default_feed.map(str::to_string),
+        };
+
+        entries.push(Denylist {
+            subject: subject.to_string(),
+            feed_id,
+            reason: reason.to_string(),
+            updated_at: now,
+            expires_at,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Split a CSV line, honoring double-quoted fields with `""` escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+
+    fields
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_newline_subjects() {
+        let body = "# spam wave\ndid:plc:one\n\ndid:plc:two\n";
+        let entries = parse_denylist_import(body, "imported", None, None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].subject, "did:plc:two");
+        assert_eq!(entries[1].reason, "imported");
+        assert!(entries[1].expires_at.is_none());
+    }
+
+    #[test]
+    fn test_parse_csv_rows() {
+        let body = "subject,reason,expires_at,feed_id\n\
+                    did:plc:one,\"spam, again\",2099-01-01T00:00:00Z\n\
+                    did:plc:two,,,at://did:plc:feedgen/app.bsky.feed.generator/curated\n";
+        let default_expiry = Some(Utc::now());
+        let entries = parse_denylist_import(body, "imported", default_expiry, None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].reason, "spam, again");
+        assert_eq!(
+            entries[0].expires_at.unwrap().to_rfc3339(),
+            "2099-01-01T00:00:00+00:00"
+        );
+        assert_eq!(entries[1].reason, "imported");
+        assert_eq!(entries[1].expires_at, default_expiry);
+        assert!(entries[0].feed_id.is_none());
+        assert_eq!(
+            entries[1].feed_id.as_deref(),
+            Some("at://did:plc:feedgen/app.bsky.feed.generator/curated")
+        );
+    }
+
+    #[test]
+    fn test_parse_header_after_comments() {
+        let body = "# exported from another instance\n\nsubject,reason\ndid:plc:one,spam\n";
+        let entries = parse_denylist_import(body, "imported", None, None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].subject, "did:plc:one");
+
+        // Only the first row can be a header
+        assert!(parse_denylist_import("did:plc:one\nsubject\n", "imported", None, None).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_subject() {
+        assert!(parse_denylist_import("spam.bsky.social\n", "imported", None, None).is_err());
+        assert!(parse_denylist_import("did:plc:one,x,tomorrow\n", "imported", None, None).is_err());
+    }
+
+    #[test]
+    fn test_csv_field_roundtrip() {
+        let line = format!("{},{}", csv_field("did:plc:one"), csv_field("a \"b\", c"));
+        assert_eq!(split_csv_line(&line), vec!["did:plc:one", "a \"b\", c"]);
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::State,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::aturi::AtUri;
+use crate::cid::validate_record_cid;
+use crate::errors::TimelineFilterError;
+use crate::feed_storage::{
+    denylist_matching, feed_content_import, feed_content_list, model::FeedContent, StoragePool,
+};
+use crate::user_storage;
+
+use super::{auth::AdminAuth, context::WebContext};
+
+#[derive(Deserialize, Default)]
+pub struct FeedContentParams {
+    pub feed: Option<String>,
+}
+
+/// One post per NDJSON line; the feed is given by the request, not the line
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct FeedContentLine {
+    uri: String,
+    indexed_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repost_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_did: Option<String>,
+    #[serde(default = "default_score")]
+    score: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cid: Option<String>,
+}
+
+fn default_score() -> i32 {
+    1
+}
+
+/// Handle GET /admin/feeds/export?feed=
+///
+/// Returns the feed's rows, newest first, as NDJSON. Other feeds and tokens
+/// are not included.
+pub async fn handle_admin_feed_export(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Query(params): Query<FeedContentParams>,
+) -> Result<Response, TimelineFilterError> {
+    let feed_uri = target_feed(&web_context, params).await?;
+    let body = feed_content_export(&web_context.pool, &feed_uri).await?;
+
+    Ok(([(CONTENT_TYPE, "application/x-ndjson")], body).into_response())
+}
+
+/// A feed's rows, newest first, as NDJSON accepted by the import
+pub async fn feed_content_export(pool: &StoragePool, feed_uri: &str) -> Result<String> {
+    let rows = feed_content_list(pool, feed_uri).await?;
+
+    let mut body = String::new();
+    for row in rows {
+        let line = FeedContentLine {
+            uri: row.uri,
+            indexed_at: row.indexed_at,
+            repost_uri: row.repost_uri,
+            source_did: row.source_did,
+            score: row.score,
+            cid: row.cid,
+        };
+        body.push_str(&serde_json::to_string(&line)?);
+        body.push('\n');
+    }
+    Ok(body)
+}
+
+/// Handle POST /admin/feeds/import?feed=
+///
+/// Imports NDJSON produced by the export into the given feed, keeping
+/// indexed_at and repost info. Posts already in the feed and denylisted
+/// subjects are skipped. The import is applied in a single transaction.
+pub async fn handle_admin_feed_import(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Query(params): Query<FeedContentParams>,
+    body: String,
+) -> Result<Response, TimelineFilterError> {
+    let feed_uri = target_feed(&web_context, params).await?;
+
+    let lines = match parse_feed_content(&body) {
+        Ok(lines) => lines,
+        Err(err) => return Err(TimelineFilterError::InvalidRequest(format!("{:#}", err))),
+    };
+
+    let mut subjects: Vec<String> = Vec::new();
+    for line in &lines {
+        subjects.push(line.uri.clone());
+        subjects.extend(author_did(&line.uri));
+        if let Some(repost_uri) = &line.repost_uri {
+            subjects.extend(author_did(repost_uri));
+        }
+    }
+    let subjects: Vec<&str> = subjects.iter().map(String::as_str).collect();
+    let denied =
+        denylist_matching(&web_context.pool, &subjects, &Some(feed_uri.clone())).await?;
+
+    let total = lines.len();
+    let entries: Vec<FeedContent> = lines
+        .into_iter()
+        .filter(|line| {
+            !denied.contains(&line.uri)
+                && !author_did(&line.uri).is_some_and(|did| denied.contains(&did))
+                && !line
+                    .repost_uri
+                    .as_deref()
+                    .and_then(author_did)
+                    .is_some_and(|did| denied.contains(&did))
+        })
+        .map(|line| FeedContent {
+            feed_id: feed_uri.clone(),
+            is_repost: line.repost_uri.is_some(),
+            uri: line.uri,
+            indexed_at: line.indexed_at,
+            score: line.score,
+            repost_uri: line.repost_uri,
+            source_did: line.source_did,
+            cid: line.cid,
+        })
+        .collect();
+
+    let imported = feed_content_import(&web_context.pool, &entries).await?;
+    web_context.feed_cache.invalidate(&feed_uri).await;
+
+    tracing::info!(feed_uri = %feed_uri, imported, total, "Imported feed content");
+
+    Ok(Json(json!({
+        "imported": imported,
+        "skipped": total as u64 - imported,
+    }))
+    .into_response())
+}
+
+async fn target_feed(
+    web_context: &WebContext,
+    params: FeedContentParams,
+) -> Result<String, TimelineFilterError> {
+    let Some(feed_uri) = params.feed else {
+        return Err(TimelineFilterError::InvalidRequest(
+            "feed parameter is required".to_string(),
+        ));
+    };
+    if !user_storage::feed_exists(&web_context.pool, &feed_uri).await? {
+        return Err(TimelineFilterError::UnknownFeed(
+            "feed is not served by this feed generator".to_string(),
+        ));
+    }
+    Ok(feed_uri)
+}
+
+/// Parse an NDJSON import body, ignoring blank lines
+fn parse_feed_content(body: &str) -> Result<Vec<FeedContentLine>> {
+    let mut lines = Vec::new();
+    for (idx, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let parsed: FeedContentLine = serde_json::from_str(line)
+            .with_context(|| format!("line {}: invalid JSON", idx + 1))?;
+        if let Err(err) = AtUri::parse(&parsed.uri) {
+            return Err(anyhow!("line {}: {}", idx + 1, err));
+        }
+        if let Some(Err(err)) = parsed.repost_uri.as_deref().map(AtUri::parse) {
+            return Err(anyhow!("line {}: {}", idx + 1, err));
+        }
+        if let Some(Err(err)) = parsed.cid.as_deref().map(validate_record_cid) {
+            return Err(anyhow!("line {}: {}", idx + 1, err));
+        }
+        lines.push(parsed);
+    }
+    Ok(lines)
+}
+
+/// The DID authority of an AT-URI
+fn author_did(uri: &str) -> Option<String> {
+    AtUri::parse(uri)
+        .ok()
+        .map(|uri| uri.authority().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feed_content() {
+        let body = r#"{"uri":"at://did:plc:a/app.bsky.feed.post/1","indexed_at":5}
+
+{"uri":"at://did:plc:b/app.bsky.feed.post/2","indexed_at":4,"repost_uri":"at://did:plc:c/app.bsky.feed.repost/3","score":2}
+"#;
+        let lines = parse_feed_content(body).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].score, 1);
+        assert_eq!(
+            lines[1].repost_uri.as_deref().and_then(author_did).as_deref(),
+            Some("did:plc:c")
+        );
+
+        // Export lines parse back unchanged
+        let exported = serde_json::to_string(&lines[1]).unwrap();
+        assert_eq!(parse_feed_content(&exported).unwrap()[0], lines[1]);
+
+        assert!(parse_feed_content("{\"uri\":\"https://x\",\"indexed_at\":1}").is_err());
+        assert!(parse_feed_content("{\"uri\":\"at://did:plc:a/app.bsky.feed.post/1 \",\"indexed_at\":1}").is_err());
+        assert!(parse_feed_content("not json").is_err());
+    }
+}
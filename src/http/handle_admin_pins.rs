@@ -0,0 +1,95 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::Query;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::aturi::{AtUri, POST_COLLECTION};
+use crate::errors::TimelineFilterError;
+use crate::feed_storage::{feed_pins_list, feed_pins_replace};
+use crate::user_storage;
+
+use super::{auth::AdminAuth, context::WebContext};
+
+/// Most posts that can be pinned to one feed
+const MAX_PINS: usize = 10;
+
+#[derive(Deserialize, Default)]
+pub struct PinsParams {
+    pub feed: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PinsBody {
+    #[serde(default)]
+    pub uris: Vec<String>,
+}
+
+/// Handle GET /admin/pins?feed=
+pub async fn handle_admin_pins_list(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Query(params): Query<PinsParams>,
+) -> Result<Response, TimelineFilterError> {
+    let feed_uri = pinned_feed(&web_context, params).await?;
+    let uris = feed_pins_list(&web_context.pool, &feed_uri).await?;
+
+    Ok(Json(json!({"feed": feed_uri, "uris": uris})).into_response())
+}
+
+/// Handle PUT /admin/pins?feed=
+///
+/// Replaces the feed's pins with the posts in `uris`, in order. Pinned posts
+/// lead the first getFeedSkeleton page and are left out of later pages.
+pub async fn handle_admin_pins_replace(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Query(params): Query<PinsParams>,
+    Json(body): Json<PinsBody>,
+) -> Result<Response, TimelineFilterError> {
+    let feed_uri = pinned_feed(&web_context, params).await?;
+
+    if body.uris.len() > MAX_PINS {
+        return Err(TimelineFilterError::InvalidRequest(format!(
+            "at most {} posts can be pinned",
+            MAX_PINS
+        )));
+    }
+    if let Some(uri) = body
+        .uris
+        .iter()
+        .find(|uri| !AtUri::parse(uri).is_ok_and(|uri| uri.is_record_in(POST_COLLECTION)))
+    {
+        return Err(TimelineFilterError::InvalidRequest(format!(
+            "not a post URI: {}",
+            uri
+        )));
+    }
+
+    feed_pins_replace(&web_context.pool, &feed_uri, &body.uris).await?;
+    web_context.feed_cache.invalidate(&feed_uri).await;
+
+    tracing::info!(feed_uri = %feed_uri, pins = body.uris.len(), "Updated feed pins");
+
+    Ok(Json(json!({"feed": feed_uri, "uris": body.uris})).into_response())
+}
+
+async fn pinned_feed(
+    web_context: &WebContext,
+    params: PinsParams,
+) -> Result<String, TimelineFilterError> {
+    let Some(feed_uri) = params.feed else {
+        return Err(TimelineFilterError::InvalidRequest(
+            "feed parameter is required".to_string(),
+        ));
+    };
+    if !user_storage::feed_exists(&web_context.pool, &feed_uri).await? {
+        return Err(TimelineFilterError::UnknownFeed(
+            "feed is not served by this feed generator".to_string(),
+        ));
+    }
+    Ok(feed_uri)
+}
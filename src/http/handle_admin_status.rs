@@ -0,0 +1,102 @@
+use axum::{extract::State, response::Html};
+use chrono::DateTime;
+
+use crate::errors::TimelineFilterError;
+use crate::user_storage::{self, FeedStatus, POLL_SUSPEND_AFTER};
+
+use super::{
+    auth::AdminAuth,
+    context::WebContext,
+    handle_self_service::{escape_html, page},
+};
+
+/// Handle GET /admin/status
+///
+/// Lists timeline feed config entries skipped on startup, then every served feed with its row count, indexed_at range, cached posts
+/// and, for timeline feeds, the poll, backfill and error state of the owner.
+pub async fn handle_admin_status(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+) -> Result<Html<String>, TimelineFilterError> {
+    let feeds = user_storage::get_feed_status(&web_context.read_pool).await?;
+    let config_errors = user_storage::get_config_errors(&web_context.read_pool).await?;
+
+    let mut rows = String::new();
+    for feed in &feeds {
+        let cached = web_context.feed_cache.cached_posts(&feed.feed_uri).await;
+        rows.push_str(&status_row(feed, cached));
+    }
+
+    let errors = if config_errors.is_empty() {
+        String::new()
+    } else {
+        let items: String = config_errors
+            .iter()
+            .map(|error| format!("<li>{}</li>\n", escape_html(error)))
+            .collect();
+        format!("<h2>Skipped config entries</h2>\n<ul>\n{}</ul>\n", items)
+    };
+
+    let body = format!(
+        r#"<h1>Feed status</h1>
+{errors}<p>{count} feeds, feed cache size {cache_size}</p>
+<table border="1" cellpadding="4">
+<tr><th>Feed</th><th>Kind</th><th>Owner</th><th>Posts</th><th>Newest</th><th>Oldest</th><th>Cached</th><th>Last indexed</th><th>Last poll</th><th>Last backfill</th><th>Backfill</th><th>Total indexed</th><th>Poll errors</th></tr>
+{rows}</table>"#,
+        errors = errors,
+        count = feeds.len(),
+        cache_size = web_context.feed_cache.size(),
+        rows = rows,
+    );
+
+    Ok(page("Feed status", &body))
+}
+
+fn status_row(feed: &FeedStatus, cached: usize) -> String {
+    let backfill = match feed.backfill_pending {
+        Some(true) => "running",
+        Some(false) => "done",
+        None => "-",
+    };
+
+    let errors = match (feed.consecutive_failures, &feed.last_error) {
+        (Some(failures), Some(last_error)) if feed.reauth_required == Some(true) => {
+            format!(
+                "{} (session revoked, log in again): {}",
+                failures,
+                escape_html(last_error)
+            )
+        }
+        (Some(failures), Some(last_error)) if failures >= POLL_SUSPEND_AFTER => {
+            format!("{} (suspended): {}", failures, escape_html(last_error))
+        }
+        (Some(failures), Some(last_error)) => format!("{}: {}", failures, escape_html(last_error)),
+        _ => "-".to_string(),
+    };
+
+    format!(
+        "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+        escape_html(&feed.feed_uri),
+        escape_html(&feed.kind),
+        escape_html(feed.owner_did.as_deref().unwrap_or("-")),
+        feed.post_count,
+        format_micros(feed.newest_indexed_at),
+        format_micros(feed.oldest_indexed_at),
+        cached,
+        escape_html(feed.last_indexed.as_deref().unwrap_or("-")),
+        escape_html(feed.last_poll_at.as_deref().unwrap_or("-")),
+        escape_html(feed.backfill_last_poll_at.as_deref().unwrap_or("-")),
+        backfill,
+        feed.total_posts_indexed
+            .map(|total| total.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        errors,
+    )
+}
+
+fn format_micros(micros: Option<i64>) -> String {
+    micros
+        .and_then(DateTime::from_timestamp_micros)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| "-".to_string())
+}
@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use crate::errors::TimelineFilterError;
+use crate::user_storage;
+
+use super::{auth::AdminAuth, context::WebContext};
+
+/// Handle DELETE /admin/users/:did
+///
+/// Removes everything stored for a timeline user, including their tokens and
+/// the content of their feeds, in one transaction. Users from TIMELINE_FEEDS
+/// are refused, since the consumer would keep polling them from the config.
+pub async fn handle_admin_user_delete(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Path(did): Path<String>,
+) -> Result<Response, TimelineFilterError> {
+    let config = user_storage::get_user_config(&web_context.pool, &did).await?;
+    if config.is_some_and(|config| config.managed_by != "web") {
+        return Err(TimelineFilterError::Conflict(format!(
+            "{} is configured in TIMELINE_FEEDS, remove them from TIMELINE_FEEDS first",
+            did
+        )));
+    }
+
+    let Some(deleted) = user_storage::delete_user_data(&web_context.pool, &did).await? else {
+        return Err(TimelineFilterError::NotFound(format!(
+            "no timeline user {}",
+            did
+        )));
+    };
+
+    for feed_uri in &deleted.feed_uris {
+        web_context.feed_cache.invalidate(feed_uri).await;
+    }
+
+    tracing::info!(
+        user_did = %did,
+        feeds = deleted.feed_uris.len(),
+        rows = deleted.rows_deleted,
+        "Deleted timeline user data"
+    );
+
+    Ok(Json(json!({
+        "did": did,
+        "feeds": deleted.feed_uris,
+        "deleted": deleted.rows_deleted,
+    }))
+    .into_response())
+}
@@ -3,44 +3,61 @@ use axum::{extract::State, response::IntoResponse, Json};
 use serde_json::json;
 
 use crate::errors::TimelineFilterError;
-use crate::user_storage;
 
 use super::context::WebContext;
 
 /// Handle describeFeedGenerator endpoint
 ///
-/// Returns service DID and list of Timeline feeds hosted by this generator.
+/// Returns service DID and list of Timeline feeds hosted by this generator,
+/// with their configured name and description. The list is cached for a
+/// minute, so new feeds can take that long to show up here.
 /// Required by AT Protocol for feed generator discovery.
 ///
 /// Response format:
 /// ```json
 /// {
-///   "did": "did:web:hostname",
-///   "feeds": [{"uri": "at://did/app.bsky.feed.generator/rkey"}]
+///   "did": "did:web:hostname",  // or SERVICE_DID
+///   "feeds": [{"uri": "at://did/app.bsky.feed.generator/rkey", "displayName": "...", "description": "..."}],
+///   "links": {"privacyPolicy": "...", "termsOfService": "..."}  // if configured
 /// }
 /// ```
 pub async fn handle_describe_feed_generator(
     State(web_context): State<WebContext>,
 ) -> Result<impl IntoResponse, TimelineFilterError> {
-    // Construct service DID from external_base
-    // Format: did:web:hostname (strip https:// and trailing slashes)
-    let hostname = web_context.external_base
-        .trim_start_matches("https://")
-        .trim_start_matches("http://")
-        .trim_end_matches('/');
+    let service_did = web_context.service_did();
 
-    let service_did = format!("did:web:{}", hostname);
+    let all_feeds: Vec<serde_json::Value> = match web_context.feed_descriptions().await {
+        Ok(feeds) => feeds
+            .iter()
+            .map(|feed| {
+                let mut entry = json!({"uri": feed.feed_uri, "displayName": feed.name});
+                if !feed.description.is_empty() {
+                    entry["description"] = json!(feed.description);
+                }
+                entry
+            })
+            .collect(),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to load feed descriptions");
+            Vec::new()
+        }
+    };
 
-    // Get Timeline feeds from database
-    let all_feeds: Vec<serde_json::Value> = user_storage::get_all_feed_uris(&web_context.pool)
-        .await
-        .unwrap_or_default()
-        .into_iter()
-        .map(|uri| json!({"uri": uri}))
-        .collect();
-
-    Ok(Json(json!({
+    let mut response = json!({
         "did": service_did,
         "feeds": all_feeds,
-    })))
+    });
+
+    let mut links = serde_json::Map::new();
+    if let Some(url) = &web_context.service.privacy_policy_url {
+        links.insert("privacyPolicy".to_string(), json!(url));
+    }
+    if let Some(url) = &web_context.service.terms_of_service_url {
+        links.insert("termsOfService".to_string(), json!(url));
+    }
+    if !links.is_empty() {
+        response["links"] = links.into();
+    }
+
+    Ok(Json(response))
 }
@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Path, State},
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::Query;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::errors::TimelineFilterError;
+use crate::feed_storage::feed_content_page;
+use crate::user_storage::{self, FeedCursor};
+
+use super::{auth::AdminAuth, context::WebContext};
+
+/// Response header carrying the next cursor of an NDJSON page
+const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+#[derive(Deserialize, Default)]
+pub struct FeedContentParams {
+    /// RFC 3339 timestamp, inclusive
+    pub since: Option<String>,
+    /// RFC 3339 timestamp, exclusive
+    pub until: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    /// "json" (default) or "ndjson"
+    pub format: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ContentRow {
+    uri: String,
+    indexed_at: i64,
+    score: i32,
+    is_repost: bool,
+}
+
+/// Handle GET /api/v1/feeds/:feed/content?since=&until=
+///
+/// Pages through the feed's indexed rows, oldest first, so analytics jobs can
+/// pull new rows incrementally. `:feed` is the URL-encoded feed URI. The next
+/// cursor is in the JSON body, or in the x-next-cursor header for NDJSON.
+pub async fn handle_feed_content(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Path(feed_uri): Path<String>,
+    Query(params): Query<FeedContentParams>,
+) -> Result<Response, TimelineFilterError> {
+    let since = parse_time("since", params.since.as_deref())?;
+    let until = parse_time("until", params.until.as_deref())?;
+    let limit = match params.limit {
+        Some(limit) if (1..=1000).contains(&limit) => limit,
+        Some(_) => {
+            return Err(TimelineFilterError::InvalidRequest(
+                "limit must be between 1 and 1000".to_string(),
+            ))
+        }
+        None => 100,
+    };
+    let cursor = match params.cursor.as_deref() {
+        Some(cursor) => Some(FeedCursor::parse(cursor).ok_or_else(|| {
+            TimelineFilterError::InvalidRequest("invalid cursor".to_string())
+        })?),
+        None => None,
+    };
+    let ndjson = match params.format.as_deref() {
+        Some("json") | None => false,
+        Some("ndjson") => true,
+        Some(other) => {
+            return Err(TimelineFilterError::InvalidRequest(format!(
+                "unsupported format: {}",
+                other
+            )))
+        }
+    };
+
+    if !user_storage::feed_exists(&web_context.read_pool, &feed_uri).await? {
+        return Err(TimelineFilterError::UnknownFeed(
+            "feed is not served by this feed generator".to_string(),
+        ));
+    }
+
+    let after = cursor
+        .as_ref()
+        .map(|cursor| (cursor.indexed_at, cursor.uri.as_str()));
+    let rows =
+        feed_content_page(&web_context.read_pool, &feed_uri, since, until, after, limit).await?;
+
+    // A short page is the last one
+    let next_cursor = match rows.last() {
+        Some(last) if rows.len() == limit as usize => {
+            Some(format!("{}::{}", last.indexed_at, last.uri))
+        }
+        _ => None,
+    };
+    let rows: Vec<ContentRow> = rows
+        .into_iter()
+        .map(|row| ContentRow {
+            uri: row.uri,
+            indexed_at: row.indexed_at,
+            score: row.score,
+            is_repost: row.is_repost,
+        })
+        .collect();
+
+    if !ndjson {
+        return Ok(Json(json!({"feed": feed_uri, "rows": rows, "cursor": next_cursor})).into_response());
+    }
+
+    let mut body = String::new();
+    for row in &rows {
+        body.push_str(&serde_json::to_string(row).map_err(anyhow::Error::from)?);
+        body.push('\n');
+    }
+    let mut response = ([(CONTENT_TYPE, "application/x-ndjson")], body).into_response();
+    if let Some(next_cursor) = next_cursor.and_then(|cursor| cursor.parse().ok()) {
+        response.headers_mut().insert(NEXT_CURSOR_HEADER, next_cursor);
+    }
+    Ok(response)
+}
+
+/// An RFC 3339 query parameter as microseconds, the unit of indexed_at
+fn parse_time(name: &str, value: Option<&str>) -> Result<Option<i64>, TimelineFilterError> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|time| time.timestamp_micros())
+                .map_err(|_| {
+                    TimelineFilterError::InvalidRequest(format!(
+                        "{} must be an RFC 3339 timestamp",
+                        name
+                    ))
+                })
+        })
+        .transpose()
+}
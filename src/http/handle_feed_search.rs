@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::Query;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::errors::TimelineFilterError;
+use crate::feed_storage::post_metadata_search;
+use crate::user_storage;
+
+use super::{auth::AdminAuth, context::WebContext};
+
+#[derive(Deserialize, Default)]
+pub struct FeedSearchParams {
+    pub q: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// Handle GET /api/v1/feeds/:feed/search?q=
+///
+/// Finds the feed's posts whose text contains the query as a phrase, newest
+/// first. `:feed` is the URL-encoded feed URI. Post text is only stored while
+/// POST_METADATA_ENABLE is set.
+pub async fn handle_feed_search(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Path(feed_uri): Path<String>,
+    Query(params): Query<FeedSearchParams>,
+) -> Result<Response, TimelineFilterError> {
+    let query = match params.q.as_deref().map(str::trim) {
+        Some(query) if !query.is_empty() => query,
+        _ => {
+            return Err(TimelineFilterError::InvalidRequest(
+                "q parameter is required".to_string(),
+            ))
+        }
+    };
+    let limit = match params.limit {
+        Some(limit) if (1..=100).contains(&limit) => limit,
+        Some(_) => {
+            return Err(TimelineFilterError::InvalidRequest(
+                "limit must be between 1 and 100".to_string(),
+            ))
+        }
+        None => 25,
+    };
+
+    if !user_storage::feed_exists(&web_context.read_pool, &feed_uri).await? {
+        return Err(TimelineFilterError::UnknownFeed(
+            "feed is not served by this feed generator".to_string(),
+        ));
+    }
+
+    let posts = post_metadata_search(&web_context.read_pool, &feed_uri, query, limit).await?;
+
+    Ok(Json(json!({"feed": feed_uri, "posts": posts})).into_response())
+}
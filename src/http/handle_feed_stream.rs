@@ -0,0 +1,55 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Path, State},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures_util::stream;
+
+use crate::errors::TimelineFilterError;
+use crate::user_storage;
+
+use super::{auth::AdminAuth, context::WebContext};
+
+/// Handle GET /api/v1/feeds/:feed/stream
+///
+/// Streams posts as they are indexed into the feed, as server-sent `post`
+/// events holding `{"feed", "uri", "repost_uri", "indexed_at"}`. A client
+/// too slow to keep up gets a `lagged` event with the number of posts it
+/// missed. `:feed` is the URL-encoded feed URI.
+pub async fn handle_feed_stream(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+    Path(feed_uri): Path<String>,
+) -> Result<Response, TimelineFilterError> {
+    if !user_storage::feed_exists(&web_context.read_pool, &feed_uri).await? {
+        return Err(TimelineFilterError::UnknownFeed(
+            "feed is not served by this feed generator".to_string(),
+        ));
+    }
+
+    let subscription = web_context.feed_events.subscribe();
+    let events = stream::unfold(subscription, move |mut subscription| {
+        let feed_uri = feed_uri.clone();
+        async move {
+            loop {
+                let event = match subscription.recv().await? {
+                    Ok(post) if post.feed == feed_uri => Event::default()
+                        .event("post")
+                        .json_data(&post)
+                        .unwrap_or_else(|_| Event::default().event("error")),
+                    Ok(_) => continue,
+                    Err(skipped) => Event::default().event("lagged").data(skipped.to_string()),
+                };
+                return Some((Ok::<_, Infallible>(event), subscription));
+            }
+        }
+    });
+
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
@@ -1,20 +1,46 @@
 use anyhow::anyhow;
-use axum::{extract::State, response::IntoResponse, Json};
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
 use axum_extra::extract::Query;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+use crate::aturi::{AtUri, FEED_GENERATOR_COLLECTION};
 use crate::errors::TimelineFilterError;
-use crate::user_storage;
+use crate::feed_config::AuthorDiversity;
+use crate::serve_log::ServedPage;
+use crate::service_auth::{claimed_issuer, verify_service_jwt, WrongIssuer};
+use crate::user_storage::{self, FeedCursor, FeedPost};
 
 use super::context::WebContext;
 
+/// Posts fetched per page slot when author diversity limits may skip some
+const DIVERSITY_OVERFETCH: u32 = 3;
+
+/// Lexicon method service JWTs for this endpoint are bound to
+const GET_FEED_SKELETON: &str = "app.bsky.feed.getFeedSkeleton";
+
 #[derive(Deserialize, Default)]
 pub struct FeedParams {
     pub feed: Option<String>,
-    pub limit: Option<u16>,
+    /// Kept as a string so malformed values get an XRPC error instead of a rejection
+    pub limit: Option<String>,
     pub cursor: Option<String>,
 }
 
+/// Validated getFeedSkeleton parameters
+#[derive(Debug, PartialEq)]
+struct SkeletonRequest {
+    feed_uri: String,
+    limit: u32,
+    cursor: Option<FeedCursor>,
+}
+
 #[derive(Serialize)]
 pub struct FeedItemView {
     pub post: String,
@@ -38,51 +64,359 @@ pub struct FeedItemsView {
 
 pub async fn handle_get_feed_skeleton(
     State(web_context): State<WebContext>,
+    headers: HeaderMap,
     Query(feed_params): Query<FeedParams>,
-) -> Result<impl IntoResponse, TimelineFilterError> {
-    if feed_params.feed.is_none() {
-        return Err(anyhow!("feed parameter is required").into());
+) -> Result<Response, TimelineFilterError> {
+    let SkeletonRequest {
+        feed_uri,
+        limit,
+        cursor,
+    } = validate_params(feed_params).map_err(TimelineFilterError::InvalidRequest)?;
+
+    // Existence, privacy, diversity and pins come from the cache like the posts
+    let Some(metadata) = web_context.feed_cache.metadata(&feed_uri).await? else {
+        return Err(TimelineFilterError::UnknownFeed(
+            "feed is not served by this feed generator".to_string(),
+        ));
+    };
+
+    // Private timelines are only served to their owner, checked before the posts
+    let mut viewer_did = None;
+    if let Some(owner) = metadata.private_owner {
+        verify_owner(&web_context, &headers, &owner).await?;
+        viewer_did = Some(owner);
     }
-    let feed_uri = feed_params.feed.unwrap();
 
-    // Get timeline feed posts from database
-    let limit = feed_params.limit.unwrap_or(50).min(100) as u32;
-    let posts = user_storage::get_feed_posts(
-        &web_context.pool,
-        &feed_uri,
-        limit,
-        feed_params.cursor.clone(),
+    // Fetch extra posts to fill the page when diversity limits skip some
+    let diversity = metadata.diversity;
+    let fetch_limit = if diversity.is_enabled() {
+        limit * DIVERSITY_OVERFETCH
+    } else {
+        limit
+    };
+
+    // Serve from the feed cache, loading it when the first page misses
+    let cache = &web_context.feed_cache;
+    let mut posts = cache.page(&feed_uri, cursor.as_ref(), fetch_limit as usize).await;
+    if posts.is_none() && cursor.is_none() && fetch_limit as usize <= cache.size() {
+        if let Err(e) = cache.refresh(&feed_uri).await {
+            tracing::warn!(error = ?e, feed_uri = %feed_uri, "Failed to refresh feed cache");
+        }
+        posts = cache.page(&feed_uri, None, fetch_limit as usize).await;
+    }
+
+    // Fall back to the database
+    let posts = match posts {
+        Some(posts) => posts,
+        None => user_storage::get_feed_posts(
+            &web_context.read_pool,
+            &feed_uri,
+            fetch_limit,
+            cursor.as_ref(),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "Failed to get timeline feed posts");
+            anyhow!("Failed to get feed posts")
+        })?,
+    };
+
+    let posts = diversify(posts, &diversity, limit as usize);
+
+    let (feed_item_views, next_cursor) = splice_pins(&metadata.pins, posts, cursor.is_none(), limit as usize);
+
+    if web_context.serve_log.is_enabled() {
+        // Only private feeds verify the JWT; for the others the viewer is claimed
+        let viewer_did_claimed = bearer_token(&headers)
+            .and_then(|token| claimed_issuer(token, &web_context.service_did(), GET_FEED_SKELETON));
+        web_context.serve_log.push(ServedPage {
+            feed_uri,
+            viewer_did,
+            viewer_did_claimed,
+            cursor: cursor
+                .as_ref()
+                .map(|cursor| format!("{}::{}", cursor.indexed_at, cursor.uri)),
+            uris: feed_item_views.iter().map(|item| item.post.clone()).collect(),
+            served_at: Utc::now(),
+        });
+    }
+
+    Ok(Json(FeedItemsView {
+        cursor: next_cursor,
+        feed: feed_item_views,
+    })
+    .into_response())
+}
+
+/// Check that the service JWT the AppView sends was issued by the feed's owner
+async fn verify_owner(
+    web_context: &WebContext,
+    headers: &HeaderMap,
+    owner: &str,
+) -> Result<(), TimelineFilterError> {
+    let token = bearer_token(headers)
+        .ok_or_else(|| TimelineFilterError::AuthRequired("service JWT required".to_string()))?;
+
+    verify_service_jwt(
+        token,
+        owner,
+        &web_context.service_did(),
+        GET_FEED_SKELETON,
+        &web_context.verification_method_cache,
     )
     .await
     .map_err(|e| {
-        tracing::error!(error = ?e, "Failed to get timeline feed posts");
-        anyhow!("Failed to get feed posts")
-    })?;
+        tracing::debug!(error = ?e, "Rejected service JWT");
+        if e.downcast_ref::<WrongIssuer>().is_some() {
+            TimelineFilterError::Forbidden("this feed is private".to_string())
+        } else {
+            TimelineFilterError::AuthRequired("invalid service JWT".to_string())
+        }
+    })
+}
 
-    let offset = feed_params.cursor
-        .and_then(|c| c.parse::<u32>().ok())
-        .unwrap_or(0);
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
 
-    let next_cursor = if posts.is_empty() {
-        None
-    } else {
-        Some((offset + posts.len() as u32).to_string())
-    };
+/// Put pinned posts at the top of the first page and drop them from every page's
+/// regular posts, returning the items and the next cursor
+///
+/// The cursor names the last regular post served, so posts indexed while a
+/// client pages through the feed neither shift nor repeat later pages.
+fn splice_pins(
+    pins: &[String],
+    posts: Vec<FeedPost>,
+    first_page: bool,
+    limit: usize,
+) -> (Vec<FeedItemView>, Option<String>) {
+    let mut items: Vec<FeedItemView> = Vec::with_capacity(limit);
+    if first_page {
+        items.extend(pins.iter().take(limit).map(|uri| FeedItemView {
+            post: uri.clone(),
+            reason: None,
+        }));
+    }
 
-    let feed_item_views = posts
-        .iter()
-        .map(|feed_post| FeedItemView {
+    let mut next_cursor = None;
+    let has_posts = !posts.is_empty();
+    for feed_post in posts {
+        if items.len() >= limit {
+            break;
+        }
+        next_cursor = Some(feed_post.cursor());
+        if pins.contains(&feed_post.uri) {
+            continue;
+        }
+        items.push(FeedItemView {
             post: feed_post.uri.clone(),
-            reason: feed_post.repost_uri.as_ref().map(|repost_uri| SkeletonReasonRepost {
+            reason: feed_post.repost_uri.map(|repost_uri| SkeletonReasonRepost {
                 reason_type: "app.bsky.feed.defs#skeletonReasonRepost".to_string(),
-                repost: repost_uri.clone(),
+                repost: repost_uri,
             }),
-        })
-        .collect::<Vec<_>>();
+        });
+    }
 
-    Ok(Json(FeedItemsView {
-        cursor: next_cursor,
-        feed: feed_item_views,
+    // Pins filled the whole first page; the next page starts at the newest post
+    if next_cursor.is_none() && has_posts {
+        next_cursor = Some(format!("{}::at://", i64::MAX));
+    }
+
+    (items, next_cursor)
+}
+
+/// Keep at most `limit` posts, skipping those that break the author diversity limits
+///
+/// Posts skipped before the last kept one fall behind the next page's cursor
+/// and are not served later; the limits thin the feed rather than reorder it.
+fn diversify(posts: Vec<FeedPost>, diversity: &AuthorDiversity, limit: usize) -> Vec<FeedPost> {
+    if !diversity.is_enabled() {
+        return posts.into_iter().take(limit).collect();
+    }
+
+    let mut per_author: HashMap<String, u32> = HashMap::new();
+    let mut last_author: Option<String> = None;
+    let mut run = 0;
+    let mut kept = Vec::with_capacity(limit);
+
+    for post in posts {
+        if kept.len() >= limit {
+            break;
+        }
+        let author = post_author(&post.uri);
+
+        let consecutive = if last_author.as_deref() == Some(author.as_str()) {
+            run + 1
+        } else {
+            1
+        };
+        let count = per_author.get(&author).copied().unwrap_or(0) + 1;
+        if diversity.max_consecutive_per_author.is_some_and(|max| consecutive > max)
+            || diversity.max_per_author_per_page.is_some_and(|max| count > max)
+        {
+            continue;
+        }
+
+        run = consecutive;
+        per_author.insert(author.clone(), count);
+        last_author = Some(author);
+        kept.push(post);
+    }
+
+    kept
+}
+
+/// Author DID of a post, taken from its at:// URI
+fn post_author(uri: &str) -> String {
+    AtUri::parse(uri)
+        .map(|uri| uri.authority().to_string())
+        .unwrap_or_else(|_| uri.to_string())
+}
+
+/// Check getFeedSkeleton parameters against the lexicon
+fn validate_params(params: FeedParams) -> Result<SkeletonRequest, String> {
+    let Some(feed_uri) = params.feed else {
+        return Err("feed parameter is required".to_string());
+    };
+    if !AtUri::parse(&feed_uri).is_ok_and(|uri| uri.is_record_in(FEED_GENERATOR_COLLECTION)) {
+        return Err("feed must be an app.bsky.feed.generator AT-URI".to_string());
+    }
+
+    let limit = match params.limit {
+        Some(limit) => match limit.parse::<u32>() {
+            Ok(limit) if (1..=100).contains(&limit) => limit,
+            _ => return Err("limit must be an integer between 1 and 100".to_string()),
+        },
+        None => 50,
+    };
+
+    let cursor = match params.cursor {
+        Some(cursor) => match FeedCursor::parse(&cursor) {
+            Some(cursor) => Some(cursor),
+            None => return Err("malformed cursor".to_string()),
+        },
+        None => None,
+    };
+
+    Ok(SkeletonRequest {
+        feed_uri,
+        limit,
+        cursor,
     })
-    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = "at://did:plc:feedgen/app.bsky.feed.generator/test";
+
+    fn params(limit: Option<&str>, cursor: Option<&str>) -> FeedParams {
+        FeedParams {
+            feed: Some(FEED.to_string()),
+            limit: limit.map(str::to_string),
+            cursor: cursor.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let request = validate_params(params(None, None)).unwrap();
+        assert_eq!(request.limit, 50);
+        assert_eq!(request.cursor, None);
+
+        assert_eq!(validate_params(params(Some("1"), None)).unwrap().limit, 1);
+        assert_eq!(validate_params(params(Some("100"), None)).unwrap().limit, 100);
+        assert!(validate_params(params(Some("0"), None)).is_err());
+        assert!(validate_params(params(Some("101"), None)).is_err());
+        assert!(validate_params(params(Some("-1"), None)).is_err());
+        assert!(validate_params(params(Some("ten"), None)).is_err());
+
+        let cursor = "1700000000::at://did:plc:author/app.bsky.feed.post/1";
+        let request = validate_params(params(None, Some(cursor))).unwrap();
+        assert_eq!(request.cursor.unwrap().indexed_at, 1700000000);
+        assert!(validate_params(params(None, Some("50"))).is_err());
+
+        assert!(validate_params(FeedParams::default()).is_err());
+        assert!(validate_params(FeedParams {
+            feed: Some("https://example.com".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_diversify() {
+        let post = |author: &str, n: i64| FeedPost {
+            uri: format!("at://did:plc:{}/app.bsky.feed.post/{}", author, n),
+            repost_uri: None,
+            indexed_at: n,
+        };
+        let posts = || {
+            vec![
+                post("busy", 9),
+                post("busy", 8),
+                post("busy", 7),
+                post("quiet", 6),
+                post("busy", 5),
+                post("busy", 4),
+            ]
+        };
+        let authors = |posts: Vec<FeedPost>| -> Vec<String> {
+            posts
+                .iter()
+                .map(|post| post_author(&post.uri))
+                .collect()
+        };
+
+        assert_eq!(diversify(posts(), &AuthorDiversity::default(), 4).len(), 4);
+
+        let consecutive = AuthorDiversity {
+            max_consecutive_per_author: Some(2),
+            max_per_author_per_page: None,
+        };
+        assert_eq!(
+            authors(diversify(posts(), &consecutive, 10)),
+            vec!["did:plc:busy", "did:plc:busy", "did:plc:quiet", "did:plc:busy", "did:plc:busy"]
+        );
+
+        let per_page = AuthorDiversity {
+            max_consecutive_per_author: None,
+            max_per_author_per_page: Some(1),
+        };
+        assert_eq!(
+            authors(diversify(posts(), &per_page, 10)),
+            vec!["did:plc:busy", "did:plc:quiet"]
+        );
+    }
+
+    #[test]
+    fn test_splice_pins() {
+        let post = |n: i64| FeedPost {
+            uri: format!("at://did:plc:author/app.bsky.feed.post/{}", n),
+            repost_uri: None,
+            indexed_at: n,
+        };
+        let pins = vec![post(9).uri];
+
+        // Pins lead the first page and are not repeated as regular posts
+        let (items, cursor) = splice_pins(&pins, vec![post(9), post(8), post(7)], true, 3);
+        let uris: Vec<&str> = items.iter().map(|item| item.post.as_str()).collect();
+        assert_eq!(uris, vec![pins[0].as_str(), &post(8).uri, &post(7).uri]);
+        assert_eq!(cursor, Some(post(7).cursor()));
+
+        // Later pages only drop them
+        let (items, cursor) = splice_pins(&pins, vec![post(9), post(8)], false, 2);
+        assert_eq!(items.len(), 1);
+        assert_eq!(cursor, Some(post(8).cursor()));
+
+        // A page filled by pins continues from the newest post
+        let (items, cursor) = splice_pins(&pins, vec![post(8)], true, 1);
+        assert_eq!(items.len(), 1);
+        let cursor = FeedCursor::parse(&cursor.unwrap()).unwrap();
+        assert!(post(8).is_after(&cursor));
+    }
 }
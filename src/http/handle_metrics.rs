@@ -0,0 +1,88 @@
+use std::fmt::Write;
+
+use axum::{
+    extract::State,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+};
+
+use crate::errors::TimelineFilterError;
+use crate::user_storage::{self, PdsRateLimit};
+
+use super::{auth::AdminAuth, context::WebContext};
+
+/// Handle GET /metrics
+///
+/// Prometheus text format gauges of the rate limit budget each PDS last
+/// reported to the timeline consumer.
+pub async fn handle_metrics(
+    State(web_context): State<WebContext>,
+    _admin: AdminAuth,
+) -> Result<Response, TimelineFilterError> {
+    let rate_limits = user_storage::pds_rate_limit_list(&web_context.read_pool).await?;
+
+    Ok((
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render_rate_limits(&rate_limits),
+    )
+        .into_response())
+}
+
+fn render_rate_limits(rate_limits: &[PdsRateLimit]) -> String {
+    let mut body = String::new();
+    write_gauge(
+        &mut body,
+        "timeline_filter_pds_ratelimit_limit",
+        "Requests allowed per rate limit window by the PDS",
+        rate_limits.iter().map(|r| (r, Some(r.rate_limit))),
+    );
+    write_gauge(
+        &mut body,
+        "timeline_filter_pds_ratelimit_remaining",
+        "Requests left in the current rate limit window of the PDS",
+        rate_limits.iter().map(|r| (r, Some(r.remaining))),
+    );
+    write_gauge(
+        &mut body,
+        "timeline_filter_pds_ratelimit_reset_timestamp_seconds",
+        "Unix time when the rate limit window of the PDS resets",
+        rate_limits.iter().map(|r| (r, r.reset_at)),
+    );
+    body
+}
+
+/// One gauge with a sample per PDS that has a value
+fn write_gauge<'a>(
+    body: &mut String,
+    name: &str,
+    help: &str,
+    samples: impl Iterator<Item = (&'a PdsRateLimit, Option<i64>)>,
+) {
+    let _ = writeln!(body, "# HELP {} {}", name, help);
+    let _ = writeln!(body, "# TYPE {} gauge", name);
+    for (rate_limit, value) in samples {
+        if let Some(value) = value {
+            // Hostnames never need escaping, but keep the label well-formed anyway
+            let pds = rate_limit.pds_host.replace(['\\', '"', '\n'], "");
+            let _ = writeln!(body, "{}{{pds=\"{}\"}} {}", name, pds, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_rate_limits() {
+        let body = render_rate_limits(&[PdsRateLimit {
+            pds_host: "bsky.social".to_string(),
+            rate_limit: 3000,
+            remaining: 12,
+            reset_at: None,
+        }]);
+        assert!(body.contains("# TYPE timeline_filter_pds_ratelimit_remaining gauge\n"));
+        assert!(body.contains("timeline_filter_pds_ratelimit_remaining{pds=\"bsky.social\"} 12\n"));
+        assert!(!body.contains("timeline_filter_pds_ratelimit_reset_timestamp_seconds{"));
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::{header::SET_COOKIE, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::aturi::{AtUri, FEED_GENERATOR_COLLECTION};
+use crate::errors::TimelineFilterError;
+use crate::feed_builder::extract_pds_endpoint;
+use crate::feed_config::FilterConfig;
+use crate::identity::{is_handle, normalize_handle, resolve_handle};
+use crate::redact::redact_tokens;
+use crate::user_storage::{self, UserConfig};
+
+use super::{
+    auth::{WebSession, SESSION_COOKIE},
+    context::WebContext,
+};
+
+/// Service that resolves the handles users sign in with
+const HANDLE_RESOLVER_URL: &str = "https://bsky.social";
+
+/// Record key of the feed generator record published into the user's repo
+const FEED_RKEY: &str = "timeline";
+
+const FEED_NAME: &str = "Filtered Timeline";
+const FEED_DESCRIPTION: &str = "My Following timeline without unwanted reposts";
+
+/// How long a browser session stays signed in
+const SESSION_LIFETIME_DAYS: i64 = 7;
+
+#[derive(Deserialize)]
+pub struct LoginForm {
+    identifier: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+pub struct SettingsForm {
+    #[serde(default)]
+    blocked_reposters: String,
+    #[serde(default)]
+    muted_threads: String,
+    /// Checkbox, only sent when checked
+    #[serde(default)]
+    hide_all_reposts: Option<String>,
+    /// Empty for no limit
+    #[serde(default)]
+    max_reposts_per_day: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateSessionResponse {
+    did: String,
+    access_jwt: String,
+    refresh_jwt: String,
+}
+
+/// Handle GET /login
+pub async fn handle_login_form() -> Html<String> {
+    login_page(None)
+}
+
+/// Handle POST /login
+///
+/// Signs in with an app password, creates the user's feed on first login and
+/// starts a browser session. The consumer picks the feed up on its next cycle.
+pub async fn handle_login(
+    State(web_context): State<WebContext>,
+    Form(form): Form<LoginForm>,
+) -> Result<Response, TimelineFilterError> {
+    // The PDS comes from the account's own DID document, never from the user
+    // or the PDS, so a server can only sign in accounts it actually hosts
+    let identifier = normalize_handle(form.identifier.trim());
+    let did = if identifier.starts_with("did:") {
+        identifier
+    } else if is_handle(&identifier) {
+        match resolve_handle(&web_context.http_client, HANDLE_RESOLVER_URL, &identifier).await {
+            Ok(did) => did,
+            Err(err) => {
+                tracing::info!(handle = %identifier, error = ?err, "Self-service login handle not resolved");
+                return Ok(login_failed("Sign in failed. Check your handle and app password."));
+            }
+        }
+    } else {
+        return Ok(login_failed("Sign in failed. Check your handle and app password."));
+    };
+
+    // The client IP is limited by the route; this holds across IPs too
+    if !web_context.login_limiter.check_account(&did, Instant::now()) {
+        tracing::info!(user_did = %did, "Self-service login attempts rate limited");
+        return Ok((
+            StatusCode::TOO_MANY_REQUESTS,
+            login_page(Some("Too many sign-in attempts for this account. Try again later.")),
+        )
+            .into_response());
+    }
+
+    let pds_url = match web_context.verification_method_cache.resolve_document(&did).await {
+        Ok(document) => extract_pds_endpoint(&document),
+        Err(err) => {
+            tracing::info!(user_did = %did, error = ?err, "Self-service login DID not resolved");
+            None
+        }
+    };
+    let Some(pds_url) = pds_url.map(|pds_url| pds_url.trim_end_matches('/').to_string()) else {
+        return Ok(login_failed("Sign in failed. The account's PDS could not be found."));
+    };
+
+    let response = web_context
+        .http_client
+        .post(format!("{}/xrpc/com.atproto.server.createSession", pds_url))
+        .json(&json!({
+            "identifier": did,
+            "password": form.password,
+        }))
+        .send()
+        .await
+        .context("Failed to send createSession request")?;
+
+    if !response.status().is_success() {
+        tracing::info!(status = %response.status(), "Self-service login rejected by PDS");
+        return Ok(login_failed("Sign in failed. Check your handle and app password."));
+    }
+
+    let session: CreateSessionResponse = response
+        .json()
+        .await
+        .context("Failed to parse createSession response")?;
+    if session.did != did {
+        tracing::warn!(user_did = %did, session_did = %session.did, "PDS signed in a different account");
+        return Ok(login_failed("Sign in failed. Check your handle and app password."));
+    }
+
+    let existing = user_storage::get_user_config(&web_context.pool, &session.did).await?;
+    if existing.as_ref().is_some_and(|config| config.managed_by != "web") {
+        return Ok((
+            StatusCode::CONFLICT,
+            login_page(Some(
+                "This account's feed is managed by the operator's configuration file.",
+            )),
+        )
+            .into_response());
+    }
+
+    let feed_uri = match existing {
+        Some(config) => config.feed_uri,
+        None => {
+            publish_feed_record(&web_context, &pds_url, &session).await?;
+            AtUri::record(&session.did, FEED_GENERATOR_COLLECTION, FEED_RKEY)?.to_string()
+        }
+    };
+
+    user_storage::upsert_web_feed(
+        &web_context.pool,
+        &UserConfig {
+            did: session.did.clone(),
+            feed_uri,
+            name: FEED_NAME.to_string(),
+            description: FEED_DESCRIPTION.to_string(),
+            access_token: session.access_jwt,
+            refresh_token: Some(session.refresh_jwt),
+            // Same lifetime the consumer assumes after a token refresh
+            token_expires_at: Some((Utc::now() + Duration::hours(2)).to_rfc3339()),
+            pds_url,
+            poll_interval_seconds: 30,
+            max_posts_per_poll: 50,
+            managed_by: "web".to_string(),
+        },
+    )
+    .await?;
+
+    let mut session_bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut session_bytes);
+    let session_id = URL_SAFE_NO_PAD.encode(session_bytes);
+
+    let lifetime = Duration::days(SESSION_LIFETIME_DAYS);
+    user_storage::session_create(
+        &web_context.pool,
+        &session_id,
+        &session.did,
+        Utc::now() + lifetime,
+    )
+    .await?;
+
+    tracing::info!(user_did = %session.did, "Self-service user signed in");
+
+    let cookie = session_cookie(&web_context, &session_id, lifetime.num_seconds());
+    Ok(([(SET_COOKIE, cookie)], Redirect::to("/settings")).into_response())
+}
+
+/// Handle GET /settings
+pub async fn handle_settings(
+    State(web_context): State<WebContext>,
+    session: WebSession,
+) -> Result<Response, TimelineFilterError> {
+    settings_response(&web_context, &session.did, StatusCode::OK, None).await
+}
+
+/// Handle POST /settings
+///
+/// Replaces the feed's blocked reposters. Handles are resolved to DIDs here so
+/// the consumer only ever sees DIDs for self-service feeds.
+pub async fn handle_settings_save(
+    State(web_context): State<WebContext>,
+    session: WebSession,
+    Form(form): Form<SettingsForm>,
+) -> Result<Response, TimelineFilterError> {
+    let Some(config) = user_storage::get_user_config(&web_context.pool, &session.did).await?
+    else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let mut blocked_reposters = HashSet::new();
+    for entry in form
+        .blocked_reposters
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        if entry.starts_with("did:") {
+            blocked_reposters.insert(entry.to_string());
+            continue;
+        }
+
+        if !is_handle(entry) {
+            let message = format!("Not a DID or handle: {}", entry);
+            return settings_response(&web_context, &session.did, StatusCode::BAD_REQUEST, Some(&message))
+                .await;
+        }
+
+        match resolve_handle(&web_context.http_client, &config.pds_url, entry).await {
+            Ok(did) => {
+                blocked_reposters.insert(did);
+            }
+            Err(err) => {
+                tracing::debug!(handle = %entry, error = ?err, "Failed to resolve handle");
+                let message = format!("Could not resolve handle: {}", entry);
+                return settings_response(&web_context, &session.did, StatusCode::BAD_REQUEST, Some(&message))
+                    .await;
+            }
+        }
+    }
+
+    let max_reposts_per_day = match form.max_reposts_per_day.trim() {
+        "" => None,
+        value => match value.parse::<u32>() {
+            Ok(max) => Some(max),
+            Err(_) => {
+                let message = format!("Not a number: {}", value);
+                return settings_response(&web_context, &session.did, StatusCode::BAD_REQUEST, Some(&message))
+                    .await;
+            }
+        },
+    };
+
+    let filters = FilterConfig {
+        blocked_reposters,
+        muted_threads: form
+            .muted_threads
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+        hide_all_reposts: form.hide_all_reposts.is_some(),
+        max_reposts_per_day,
+        ..Default::default()
+    };
+    if let Err(err) = filters.validate() {
+        let message = format!("{:#}", err);
+        return settings_response(&web_context, &session.did, StatusCode::BAD_REQUEST, Some(&message))
+            .await;
+    }
+    user_storage::sync_feed_filters(&web_context.pool, &session.did, &config.feed_uri, &filters)
+        .await?;
+
+    settings_response(&web_context, &session.did, StatusCode::OK, Some("Saved.")).await
+}
+
+/// Handle POST /logout
+pub async fn handle_logout(
+    State(web_context): State<WebContext>,
+    session: WebSession,
+) -> Result<Response, TimelineFilterError> {
+    user_storage::session_delete(&web_context.pool, &session.session_id).await?;
+
+    let cookie = session_cookie(&web_context, "", 0);
+    Ok(([(SET_COOKIE, cookie)], Redirect::to("/login")).into_response())
+}
+
+/// Publish the app.bsky.feed.generator record pointing at this service
+async fn publish_feed_record(
+    web_context: &WebContext,
+    pds_url: &str,
+    session: &CreateSessionResponse,
+) -> Result<()> {
+    let response = web_context
+        .http_client
+        .post(format!("{}/xrpc/com.atproto.repo.putRecord", pds_url))
+        .bearer_auth(&session.access_jwt)
+        .json(&json!({
+            "repo": session.did,
+            "collection": FEED_GENERATOR_COLLECTION,
+            "rkey": FEED_RKEY,
+            "record": {
+                "$type": FEED_GENERATOR_COLLECTION,
+                "did": web_context.service_did(),
+                "displayName": FEED_NAME,
+                "description": FEED_DESCRIPTION,
+                "createdAt": Utc::now().to_rfc3339(),
+            },
+        }))
+        .send()
+        .await
+        .context("Failed to send putRecord request")?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("Failed to publish feed record: {} - {}", status, redact_tokens(&body));
+    }
+
+    Ok(())
+}
+
+async fn settings_response(
+    web_context: &WebContext,
+    did: &str,
+    status: StatusCode,
+    message: Option<&str>,
+) -> Result<Response, TimelineFilterError> {
+    let Some(config) = user_storage::get_user_config(&web_context.pool, did).await? else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+    let filters = user_storage::get_feed_filters(&web_context.pool, &config.feed_uri).await?;
+
+    let mut blocked_reposters = filters.blocked_reposters;
+    blocked_reposters.sort();
+    let mut muted_threads = filters.muted_threads;
+    muted_threads.sort();
+
+    let body = format!(
+        r#"<h1>{name}</h1>
+<p>Feed: <code>{feed_uri}</code></p>
+{message}
+<form method="post" action="/settings">
+  <input id="hide_all_reposts" name="hide_all_reposts" type="checkbox"{hide_all_reposts}>
+  <label for="hide_all_reposts">Hide all reposts</label><br>
+  <label for="max_reposts_per_day">Most reposts per account in 24 hours (empty for no limit)</label><br>
+  <input id="max_reposts_per_day" name="max_reposts_per_day" type="number" min="1" value="{max_reposts}"><br>
+  <label for="blocked_reposters">Hide reposts from (one DID or handle per line)</label><br>
+  <textarea id="blocked_reposters" name="blocked_reposters" rows="10" cols="60">{blocked}</textarea><br>
+  <label for="muted_threads">Hide threads (one at:// URI of the thread's first post per line)</label><br>
+  <textarea id="muted_threads" name="muted_threads" rows="5" cols="60">{muted}</textarea><br>
+  <button type="submit">Save</button>
+</form>
+<form method="post" action="/logout"><button type="submit">Sign out</button></form>"#,
+        name = escape_html(&config.name),
+        feed_uri = escape_html(&config.feed_uri),
+        message = message_html(message),
+        blocked = escape_html(&blocked_reposters.join("\n")),
+        muted = escape_html(&muted_threads.join("\n")),
+        hide_all_reposts = if filters.hide_all_reposts { " checked" } else { "" },
+        max_reposts = filters.max_reposts_per_day.map(|max| max.to_string()).unwrap_or_default(),
+    );
+
+    Ok((status, page("Feed settings", &body)).into_response())
+}
+
+fn login_page(message: Option<&str>) -> Html<String> {
+    let body = format!(
+        r#"<h1>Create your filtered timeline</h1>
+<p>Sign in with an app password. Your password is only used to start a session and is not stored.</p>
+{message}
+<form method="post" action="/login">
+  <label for="identifier">Handle or DID</label><br>
+  <input id="identifier" name="identifier" required><br>
+  <label for="password">App password</label><br>
+  <input id="password" name="password" type="password" required><br>
+  <button type="submit">Sign in</button>
+</form>"#,
+        message = message_html(message),
+    );
+
+    page("Sign in", &body)
+}
+
+fn login_failed(message: &str) -> Response {
+    (StatusCode::UNAUTHORIZED, login_page(Some(message))).into_response()
+}
+
+pub(crate) fn page(title: &str, body: &str) -> Html<String> {
+    Html(format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{}</title></head>\n<body>\n{}\n</body>\n</html>\n",
+        escape_html(title),
+        body
+    ))
+}
+
+fn message_html(message: Option<&str>) -> String {
+    message
+        .map(|message| format!("<p><strong>{}</strong></p>", escape_html(message)))
+        .unwrap_or_default()
+}
+
+fn session_cookie(web_context: &WebContext, value: &str, max_age: i64) -> String {
+    let secure = if web_context.service.external_base.starts_with("http://") {
+        ""
+    } else {
+        "; Secure"
+    };
+    format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}{}",
+        SESSION_COOKIE, value, max_age, secure
+    )
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::Query;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::errors::TimelineFilterError;
+use crate::feed_config::FilterConfig;
+use crate::user_storage::{self, UserConfig};
+
+use super::{auth::ApiAuth, context::WebContext};
+
+#[derive(Deserialize, Default)]
+pub struct UserFiltersParams {
+    /// Feed URI of a variant; unset = the user's primary feed
+    pub feed: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UserFiltersBody {
+    #[serde(default)]
+    pub blocked_reposters: Vec<String>,
+    #[serde(default)]
+    pub muted_threads: Vec<String>,
+    #[serde(default)]
+    pub hide_all_reposts: bool,
+    #[serde(default, alias = "max_reposts_per_author_per_day")]
+    pub max_reposts_per_day: Option<u32>,
+}
+
+/// Handle GET /api/v1/users/:did/filters
+///
+/// Returns the filters stored for the user's primary feed, or for the variant
+/// given by `?feed=`.
+pub async fn handle_get_user_filters(
+    State(web_context): State<WebContext>,
+    auth: ApiAuth,
+    Path(did): Path<String>,
+    Query(params): Query<UserFiltersParams>,
+) -> Result<Response, TimelineFilterError> {
+    let (_, feed_uri) = target_feed_uri(&web_context, &auth, &did, params.feed).await?;
+
+    let filters = user_storage::get_feed_filters(&web_context.pool, &feed_uri).await?;
+    let mut blocked_reposters = filters.blocked_reposters;
+    blocked_reposters.sort();
+    let mut muted_threads = filters.muted_threads;
+    muted_threads.sort();
+
+    Ok(Json(json!({
+        "did": did,
+        "feed_uri": feed_uri,
+        "blocked_reposters": blocked_reposters,
+        "muted_threads": muted_threads,
+        "hide_all_reposts": filters.hide_all_reposts,
+        "max_reposts_per_day": filters.max_reposts_per_day,
+    }))
+    .into_response())
+}
+
+/// Handle PUT /api/v1/users/:did/filters
+///
+/// Replaces the feed's filters. The timeline consumer applies them on its next
+/// poll cycle. Filters of feeds from TIMELINE_FEEDS come from the file and are
+/// refused with 409, since the next startup would overwrite them.
+pub async fn handle_put_user_filters(
+    State(web_context): State<WebContext>,
+    auth: ApiAuth,
+    Path(did): Path<String>,
+    Query(params): Query<UserFiltersParams>,
+    Json(body): Json<UserFiltersBody>,
+) -> Result<Response, TimelineFilterError> {
+    let (config, feed_uri) = target_feed_uri(&web_context, &auth, &did, params.feed).await?;
+    if config.managed_by != "web" {
+        return Err(TimelineFilterError::Conflict(
+            "filters of this feed are set in TIMELINE_FEEDS, edit the config file instead".to_string(),
+        ));
+    }
+
+    let filters = FilterConfig {
+        blocked_reposters: body.blocked_reposters.into_iter().collect(),
+        muted_threads: body.muted_threads.into_iter().collect(),
+        hide_all_reposts: body.hide_all_reposts,
+        max_reposts_per_day: body.max_reposts_per_day,
+        ..Default::default()
+    };
+    if let Err(err) = filters.validate() {
+        return Err(TimelineFilterError::InvalidRequest(format!("{:#}", err)));
+    }
+
+    user_storage::sync_feed_filters(&web_context.pool, &did, &feed_uri, &filters).await?;
+
+    tracing::info!(
+        user_did = %did,
+        feed_uri = %feed_uri,
+        blocked_reposters = filters.blocked_reposters.len(),
+        muted_threads = filters.muted_threads.len(),
+        hide_all_reposts = filters.hide_all_reposts,
+        "Updated user filters"
+    );
+
+    let mut blocked_reposters: Vec<String> = filters.blocked_reposters.into_iter().collect();
+    blocked_reposters.sort();
+    let mut muted_threads: Vec<String> = filters.muted_threads.into_iter().collect();
+    muted_threads.sort();
+
+    Ok(Json(json!({
+        "did": did,
+        "feed_uri": feed_uri,
+        "blocked_reposters": blocked_reposters,
+        "muted_threads": muted_threads,
+        "hide_all_reposts": filters.hide_all_reposts,
+        "max_reposts_per_day": filters.max_reposts_per_day,
+    }))
+    .into_response())
+}
+
+/// Check access and work out which of the user's feeds a request targets
+async fn target_feed_uri(
+    web_context: &WebContext,
+    auth: &ApiAuth,
+    did: &str,
+    feed: Option<String>,
+) -> Result<(UserConfig, String), TimelineFilterError> {
+    if !auth.can_access(did) {
+        return Err(TimelineFilterError::Forbidden(
+            "cannot access another user's filters".to_string(),
+        ));
+    }
+
+    let Some(config) = user_storage::get_user_config(&web_context.pool, did).await? else {
+        return Err(TimelineFilterError::NotFound("unknown user".to_string()));
+    };
+
+    let Some(feed_uri) = feed else {
+        let feed_uri = config.feed_uri.clone();
+        return Ok((config, feed_uri));
+    };
+
+    match user_storage::get_feed_owner(&web_context.pool, &feed_uri).await? {
+        Some(owner) if owner == did => Ok((config, feed_uri)),
+        _ => Err(TimelineFilterError::UnknownFeed(
+            "feed does not belong to this user".to_string(),
+        )),
+    }
+}
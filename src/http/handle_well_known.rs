@@ -6,31 +6,43 @@ use crate::errors::TimelineFilterError;
 
 use super::context::WebContext;
 
+/// Handle GET /.well-known/did.json
+///
+/// Publishes the did:web document: the feed generator service, any extra
+/// services from DID_SERVICES and the DID_ALSO_KNOWN_AS entries. A did:plc
+/// service identity is resolved through PLC instead, so there is none here.
 pub async fn handle_well_known(
     State(web_context): State<WebContext>,
 ) -> Result<impl IntoResponse, TimelineFilterError> {
-    // Strip protocol from external_base for DID (did:web doesn't include protocol)
-    let hostname = web_context.external_base
-        .trim_start_matches("https://")
-        .trim_start_matches("http://");
+    let service = &web_context.service;
+    if !service.service_did.starts_with("did:web:") {
+        return Err(TimelineFilterError::NotFound(
+            "service DID is not a did:web".to_string(),
+        ));
+    }
 
-    // Ensure serviceEndpoint has https:// protocol
-    let service_endpoint = if web_context.external_base.starts_with("http://") || web_context.external_base.starts_with("https://") {
-        web_context.external_base.clone()
-    } else {
-        format!("https://{}", web_context.external_base)
-    };
+    let services: Vec<serde_json::Value> = std::iter::once(json!({
+        "id": service.feed_service_id,
+        "type": "BskyFeedGenerator",
+        "serviceEndpoint": service.feed_service_endpoint,
+    }))
+    .chain(service.services.iter().map(|extra| {
+        json!({
+            "id": extra.id,
+            "type": extra.service_type,
+            "serviceEndpoint": extra.endpoint,
+        })
+    }))
+    .collect();
 
-    Ok(Json(json!({
+    let mut document = json!({
          "@context": ["https://www.w3.org/ns/did/v1"],
-         "id": format!("did:web:{}", hostname),
-         "service": [
-            {
-                "id": "#bsky_fg",
-                "type": "BskyFeedGenerator",
-                "serviceEndpoint": service_endpoint,
-            }
-         ]
-    }))
-    .into_response())
+         "id": web_context.service_did(),
+         "service": services,
+    });
+    if !service.also_known_as.is_empty() {
+        document["alsoKnownAs"] = json!(service.also_known_as);
+    }
+
+    Ok(Json(document).into_response())
 }
@@ -0,0 +1,239 @@
+use std::{
+    net::SocketAddr,
+    os::{fd::FromRawFd, fd::IntoRawFd, unix::fs::PermissionsExt},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context, Result};
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto::Builder,
+    service::TowerToHyperService,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, UnixListener},
+};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use crate::server_config::Config;
+
+use super::tls::serve_tls;
+
+/// First file descriptor passed by systemd socket activation
+const SD_LISTEN_FDS_START: i32 = 3;
+
+/// Where the HTTP server accepts connections
+pub enum HttpListener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl HttpListener {
+    /// Take the socket passed by systemd, else bind `HTTP_SOCKET`, else the TCP port
+    pub async fn bind(config: &Config) -> Result<Self> {
+        if let Some(listener) = Self::from_systemd()? {
+            return Ok(listener);
+        }
+
+        if let Some(socket_path) = &config.http_socket {
+            // A socket file left over from an earlier run would make bind fail
+            match std::fs::remove_file(socket_path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(anyhow::Error::new(err)
+                        .context(format!("failed to remove stale socket: {}", socket_path)))
+                }
+            }
+
+            let listener = UnixListener::bind(socket_path)
+                .with_context(|| format!("failed to bind socket: {}", socket_path))?;
+            // Let a reverse proxy in the same group connect
+            std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o660))?;
+
+            tracing::info!(socket = %socket_path, "listening on unix socket");
+            return Ok(Self::Unix(listener));
+        }
+
+        let addr = SocketAddr::new(*config.http_bind_addr.as_ref(), *config.http_port.as_ref());
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("failed to bind {}", addr))?;
+
+        tracing::info!(addr = %addr, "listening on TCP");
+        Ok(Self::Tcp(listener))
+    }
+
+    /// The first socket from `LISTEN_FDS`, if systemd started us for this process
+    fn from_systemd() -> Result<Option<Self>> {
+        let listen_pid = std::env::var("LISTEN_PID").ok();
+        let listen_fds = std::env::var("LISTEN_FDS").ok();
+        let (Some(listen_pid), Some(listen_fds)) = (listen_pid, listen_fds) else {
+            return Ok(None);
+        };
+        if listen_pid.parse::<u32>().ok() != Some(std::process::id()) {
+            return Ok(None);
+        }
+        let listen_fds: u32 = listen_fds
+            .parse()
+            .map_err(|_| anyhow!("LISTEN_FDS is not a number: {}", listen_fds))?;
+        if listen_fds == 0 {
+            return Ok(None);
+        }
+        if listen_fds > 1 {
+            tracing::warn!(listen_fds, "only the first activated socket is used");
+        }
+
+        // SAFETY: systemd hands over ownership of the descriptors starting at
+        // SD_LISTEN_FDS_START, and nothing else in the process uses them.
+        let tcp = unsafe { std::net::TcpListener::from_raw_fd(SD_LISTEN_FDS_START) };
+        if tcp.local_addr().is_ok() {
+            tcp.set_nonblocking(true)?;
+            tracing::info!("listening on systemd TCP socket");
+            return Ok(Some(Self::Tcp(TcpListener::from_std(tcp)?)));
+        }
+
+        // Not an inet socket, so it has to be a unix socket
+        // SAFETY: the descriptor was just released from the TCP listener above.
+        let unix =
+            unsafe { std::os::unix::net::UnixListener::from_raw_fd(tcp.into_raw_fd()) };
+        unix.set_nonblocking(true)?;
+        tracing::info!("listening on systemd unix socket");
+        Ok(Some(Self::Unix(UnixListener::from_std(unix)?)))
+    }
+}
+
+/// Serve the router on the listener until the token is cancelled
+pub async fn serve(
+    listener: HttpListener,
+    app: Router,
+    tls_config: Option<Arc<ServerConfig>>,
+    shutdown_token: CancellationToken,
+) -> Result<()> {
+    match (listener, tls_config) {
+        (HttpListener::Tcp(listener), Some(tls_config)) => {
+            serve_tls(listener, app, tls_config, shutdown_token).await
+        }
+        (HttpListener::Tcp(listener), None) => {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(async move {
+                tokio::select! {
+                    () = shutdown_token.cancelled() => { }
+                }
+                tracing::info!("axum graceful shutdown complete");
+            })
+            .await?;
+            Ok(())
+        }
+        (HttpListener::Unix(_), Some(_)) => Err(anyhow!(
+            "TLS is not supported on unix sockets, terminate it in the reverse proxy"
+        )),
+        (HttpListener::Unix(listener), None) => serve_unix(listener, app, shutdown_token).await,
+    }
+}
+
+/// Serve the router on a unix socket
+///
+/// Connections carry no `ConnectInfo`; set `RATE_LIMIT_FORWARDED_HEADER` so the
+/// rate limiter sees the client IP from the reverse proxy. On shutdown, open
+/// connections finish their in-flight requests before this returns.
+async fn serve_unix(
+    listener: UnixListener,
+    app: Router,
+    shutdown_token: CancellationToken,
+) -> Result<()> {
+    let connections = TaskTracker::new();
+
+    loop {
+        let stream = tokio::select! {
+            () = shutdown_token.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "failed to accept connection");
+                    continue;
+                }
+            },
+        };
+
+        connections.spawn(serve_connection(stream, app.clone(), shutdown_token.clone()));
+    }
+
+    connections.close();
+    connections.wait().await;
+    tracing::info!("unix socket listener shut down");
+    Ok(())
+}
+
+/// Serve HTTP/1.1 or HTTP/2 on one accepted connection
+///
+/// When the token is cancelled the connection stops taking new requests and
+/// closes once the in-flight ones are answered.
+pub(crate) async fn serve_connection<I>(io: I, app: Router, shutdown_token: CancellationToken)
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let builder = Builder::new(TokioExecutor::new());
+    let connection =
+        builder.serve_connection_with_upgrades(TokioIo::new(io), TowerToHyperService::new(app));
+    tokio::pin!(connection);
+
+    let result = tokio::select! {
+        result = connection.as_mut() => result,
+        () = shutdown_token.cancelled() => {
+            connection.as_mut().graceful_shutdown();
+            connection.await
+        }
+    };
+    if let Err(err) = result {
+        tracing::debug!(error = ?err, "connection error");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_serve_unix_drains_requests_on_shutdown() {
+        let path = std::env::temp_dir().join(format!("listener-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let app = Router::new().route(
+            "/",
+            get(|| async {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                "done"
+            }),
+        );
+        let token = CancellationToken::new();
+        let server = tokio::spawn(serve_unix(listener, app, token.clone()));
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // The request in flight is still answered after the token is cancelled
+        token.cancel();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!server.is_finished());
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("done"));
+        server.await.unwrap().unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
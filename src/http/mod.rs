@@ -1,6 +1,21 @@
+pub mod auth;
 pub mod context;
+pub mod handle_admin_denylist;
+pub mod handle_admin_feed_content;
+pub mod handle_admin_pins;
+pub mod handle_admin_status;
+pub mod handle_admin_users;
 pub mod handle_describe_feed_generator;
+pub mod handle_feed_content;
+pub mod handle_feed_search;
+pub mod handle_feed_stream;
 pub mod handle_get_feed_skeleton;
 pub mod handle_index;
+pub mod handle_metrics;
+pub mod handle_self_service;
+pub mod handle_user_filters;
 pub mod handle_well_known;
+pub mod listener;
+pub mod rate_limit;
 pub mod server;
+pub mod tls;
@@ -0,0 +1,242 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::errors::TimelineFilterError;
+
+use super::context::WebContext;
+
+/// Most clients tracked; the least recently seen half is dropped beyond this
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Sign-in attempts a client IP may burst, refilled over a minute
+const LOGIN_ATTEMPTS_PER_IP: u32 = 10;
+
+/// Sign-in attempts for one account, from any IP, refilled over an hour
+const LOGIN_ATTEMPTS_PER_ACCOUNT: u32 = 10;
+
+/// Per-client token bucket limiter, keyed by IP for the public XRPC routes
+///
+/// Each client may burst up to `per_minute` requests and then continues at
+/// `per_minute` requests per minute.
+#[derive(Clone)]
+pub struct RateLimiter<K = IpAddr>(Arc<InnerRateLimiter<K>>);
+
+struct InnerRateLimiter<K> {
+    capacity: f64,
+    refill_per_second: f64,
+    /// Header carrying the client IP when running behind a reverse proxy
+    forwarded_header: Option<String>,
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32, forwarded_header: Option<String>) -> Self {
+        Self::with_period(per_minute, Duration::from_secs(60), forwarded_header)
+    }
+
+    /// Client IP of a request: the forwarded header when configured, else the peer address
+    ///
+    /// Clients can send their own X-Forwarded-For, so only the last entry, the
+    /// one appended by the reverse proxy, is trusted.
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        if let Some(header) = &self.0.forwarded_header {
+            let forwarded = request
+                .headers()
+                .get(header.as_str())
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .and_then(|value| value.trim().parse::<IpAddr>().ok());
+            if forwarded.is_some() {
+                return forwarded;
+            }
+        }
+
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+    }
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    /// A limiter allowing bursts of `capacity` requests, refilled over `period`
+    pub fn with_period(capacity: u32, period: Duration, forwarded_header: Option<String>) -> Self {
+        Self(Arc::new(InnerRateLimiter {
+            capacity: capacity as f64,
+            refill_per_second: capacity as f64 / period.as_secs_f64(),
+            forwarded_header,
+            buckets: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Take one request from the client's bucket, returning false when it is empty
+    pub fn check(&self, key: K, now: Instant) -> bool {
+        let mut buckets = self.0.buckets.lock().unwrap();
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(&key) {
+            // Evicting half at once pays for the scan only every
+            // MAX_TRACKED_CLIENTS / 2 new clients, however they spend their tokens
+            let mut seen: Vec<Instant> = buckets.values().map(|bucket| bucket.updated_at).collect();
+            let middle = seen.len() / 2;
+            let (_, cutoff, _) = seen.select_nth_unstable(middle);
+            let cutoff = *cutoff;
+            buckets.retain(|_, bucket| bucket.updated_at > cutoff);
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.0.capacity,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.0.refill_per_second).min(self.0.capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Throttles POST /login, which tries app passwords against the account's PDS
+///
+/// The PDS only sees this server's address, so its own limits can't tell
+/// clients apart. Attempts are limited per client IP and per account; the
+/// account limit also holds for attempts spread over many IPs.
+#[derive(Clone)]
+pub struct LoginLimiter {
+    by_ip: RateLimiter,
+    by_account: RateLimiter<String>,
+}
+
+impl LoginLimiter {
+    pub fn new(forwarded_header: Option<String>) -> Self {
+        Self {
+            by_ip: RateLimiter::with_period(LOGIN_ATTEMPTS_PER_IP, Duration::from_secs(60), forwarded_header),
+            by_account: RateLimiter::with_period(LOGIN_ATTEMPTS_PER_ACCOUNT, Duration::from_secs(3600), None),
+        }
+    }
+
+    /// Take one attempt for the account, returning false when it has none left
+    pub fn check_account(&self, did: &str, now: Instant) -> bool {
+        self.by_account.check(did.to_string(), now)
+    }
+}
+
+/// Middleware rejecting clients that exceed the rate limit with 429
+pub async fn rate_limit(
+    State(web_context): State<WebContext>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(limiter) = &web_context.rate_limiter {
+        if let Some(ip) = limiter.client_ip(&request) {
+            if !limiter.check(ip, Instant::now()) {
+                tracing::debug!(client_ip = %ip, path = %request.uri().path(), "Rate limited");
+                return TimelineFilterError::RateLimitExceeded(
+                    "too many requests, slow down".to_string(),
+                )
+                .into_response();
+            }
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Middleware limiting sign-in attempts per client IP, whether or not
+/// RATE_LIMIT_ENABLE is set
+pub async fn login_rate_limit(
+    State(web_context): State<WebContext>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limiter = &web_context.login_limiter.by_ip;
+    if let Some(ip) = limiter.client_ip(&request) {
+        if !limiter.check(ip, Instant::now()) {
+            tracing::info!(client_ip = %ip, "Sign-in attempts rate limited");
+            return TimelineFilterError::RateLimitExceeded(
+                "too many sign-in attempts, try again later".to_string(),
+            )
+            .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(2, None);
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        let other: IpAddr = "192.0.2.2".parse().unwrap();
+        let now = Instant::now();
+
+        assert!(limiter.check(ip, now));
+        assert!(limiter.check(ip, now));
+        assert!(!limiter.check(ip, now));
+
+        // Other clients have their own bucket
+        assert!(limiter.check(other, now));
+
+        // Two per minute refills one request every 30 seconds
+        assert!(limiter.check(ip, now + Duration::from_secs(30)));
+        assert!(!limiter.check(ip, now + Duration::from_secs(30)));
+
+        // Clients that each spend a token can't grow the map past the cap
+        for i in 0..MAX_TRACKED_CLIENTS as u32 * 2 {
+            let spoofed = IpAddr::from(std::net::Ipv4Addr::from(u32::MAX - i));
+            limiter.check(spoofed, now + Duration::from_millis(i as u64));
+        }
+        assert!(limiter.0.buckets.lock().unwrap().len() <= MAX_TRACKED_CLIENTS);
+    }
+
+    #[test]
+    fn test_login_limiter() {
+        let limiter = LoginLimiter::new(None);
+        let now = Instant::now();
+
+        for _ in 0..LOGIN_ATTEMPTS_PER_ACCOUNT {
+            assert!(limiter.check_account("did:plc:target", now));
+        }
+        assert!(!limiter.check_account("did:plc:target", now));
+        assert!(limiter.check_account("did:plc:other", now));
+
+        // One attempt comes back every six minutes
+        assert!(!limiter.check_account("did:plc:target", now + Duration::from_secs(300)));
+        assert!(limiter.check_account("did:plc:target", now + Duration::from_secs(360)));
+    }
+
+    #[test]
+    fn test_client_ip_uses_last_forwarded_entry() {
+        let limiter = RateLimiter::new(2, Some("x-forwarded-for".to_string()));
+        let request = Request::builder()
+            .header("x-forwarded-for", "198.51.100.1, 203.0.113.7")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(limiter.client_ip(&request), Some("203.0.113.7".parse().unwrap()));
+    }
+}
@@ -1,12 +1,31 @@
 use super::{
     context::WebContext,
+    handle_admin_denylist::{
+        handle_admin_denylist_add, handle_admin_denylist_export, handle_admin_denylist_import,
+    },
+    handle_admin_feed_content::{handle_admin_feed_export, handle_admin_feed_import},
+    handle_admin_pins::{handle_admin_pins_list, handle_admin_pins_replace},
+    handle_admin_status::handle_admin_status,
+    handle_admin_users::handle_admin_user_delete,
     handle_describe_feed_generator::handle_describe_feed_generator,
+    handle_feed_content::handle_feed_content,
+    handle_feed_search::handle_feed_search,
+    handle_feed_stream::handle_feed_stream,
     handle_get_feed_skeleton::handle_get_feed_skeleton, handle_index::handle_index,
+    handle_metrics::handle_metrics,
+    handle_self_service::{
+        handle_login, handle_login_form, handle_logout, handle_settings, handle_settings_save,
+    },
+    handle_user_filters::{handle_get_user_filters, handle_put_user_filters},
     handle_well_known::handle_well_known,
+    rate_limit::{login_rate_limit, rate_limit},
 };
 use axum::{
-    http::HeaderValue,
-    routing::get,
+    body::Body,
+    extract::DefaultBodyLimit,
+    http::{HeaderValue, Request, Response},
+    middleware,
+    routing::{delete, get, post},
     Router,
 };
 use http::{
@@ -17,11 +36,70 @@ use std::time::Duration;
 use tower_http::cors::CorsLayer;
 use tower_http::timeout::TimeoutLayer;
 use tower_http::trace::TraceLayer;
+use tracing::Span;
+
+/// Largest feed content import accepted, well above axum's 2 MB default
+const IMPORT_BODY_LIMIT: usize = 64 * 1024 * 1024;
 
 pub fn build_router(web_context: WebContext) -> Router {
-    Router::new()
+    let router = Router::new()
+        .route("/", get(handle_index))
+        .route("/.well-known/did.json", get(handle_well_known))
+        .merge(xrpc_routes(&web_context))
+        .route(
+            "/login",
+            get(handle_login_form).merge(post(handle_login).route_layer(
+                middleware::from_fn_with_state(web_context.clone(), login_rate_limit),
+            )),
+        )
+        .route("/settings", get(handle_settings).post(handle_settings_save))
+        .route("/logout", post(handle_logout))
+        .route(
+            "/api/v1/users/:did/filters",
+            get(handle_get_user_filters).put(handle_put_user_filters),
+        )
+        .route("/api/v1/feeds/:feed/search", get(handle_feed_search))
+        .route("/api/v1/feeds/:feed/content", get(handle_feed_content))
+        .route("/api/v1/feeds/:feed/stream", get(handle_feed_stream))
+        .route("/admin/status", get(handle_admin_status))
+        .route("/admin/users/:did", delete(handle_admin_user_delete))
+        .route("/metrics", get(handle_metrics))
+        .route("/admin/feeds/export", get(handle_admin_feed_export))
+        .route(
+            "/admin/feeds/import",
+            post(handle_admin_feed_import).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+        )
+        .route(
+            "/admin/pins",
+            get(handle_admin_pins_list).put(handle_admin_pins_replace),
+        )
+        .route("/admin/denylist", post(handle_admin_denylist_add))
+        .route(
+            "/admin/denylist/import",
+            post(handle_admin_denylist_import),
+        )
+        .route(
+            "/admin/denylist/export",
+            get(handle_admin_denylist_export),
+        );
+
+    with_layers(router, web_context)
+}
+
+/// Routes of a read replica: the feed endpoints and the service identity, none
+/// of which write to the database
+pub fn build_replica_router(web_context: WebContext) -> Router {
+    let router = Router::new()
         .route("/", get(handle_index))
         .route("/.well-known/did.json", get(handle_well_known))
+        .merge(xrpc_routes(&web_context));
+
+    with_layers(router, web_context)
+}
+
+/// Public XRPC routes, rate limited per client IP
+fn xrpc_routes(web_context: &WebContext) -> Router<WebContext> {
+    Router::new()
         .route(
             "/xrpc/app.bsky.feed.getFeedSkeleton",
             get(handle_get_feed_skeleton),
@@ -30,15 +108,51 @@ pub fn build_router(web_context: WebContext) -> Router {
             "/xrpc/app.bsky.feed.describeFeedGenerator",
             get(handle_describe_feed_generator),
         )
+        .route_layer(middleware::from_fn_with_state(
+            web_context.clone(),
+            rate_limit,
+        ))
+}
+
+/// Access log, timeout and CORS shared by every route
+fn with_layers(router: Router<WebContext>, web_context: WebContext) -> Router {
+    router
         .layer((
-            TraceLayer::new_for_http(),
+            TraceLayer::new_for_http()
+                .make_span_with(access_log_span)
+                .on_response(access_log_response),
             TimeoutLayer::new(Duration::from_secs(10)),
         ))
         .layer(
             CorsLayer::new()
-                .allow_origin(web_context.external_base.parse::<HeaderValue>().unwrap())
+                .allow_origin(web_context.service.external_base.parse::<HeaderValue>().unwrap())
                 .allow_methods([Method::GET])
                 .allow_headers([ACCEPT_LANGUAGE, ACCEPT]),
         )
-        .with_state(web_context.clone())
+        .with_state(web_context)
+}
+
+/// Access log span: method, path and requested feed
+fn access_log_span(request: &Request<Body>) -> Span {
+    let feed = request
+        .uri()
+        .query()
+        .and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("feed=")))
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        feed = %feed,
+    )
+}
+
+/// Access log line emitted with status and latency once a response is ready
+fn access_log_response(response: &Response<Body>, latency: Duration, _span: &Span) {
+    tracing::info!(
+        status = response.status().as_u16(),
+        latency_ms = latency.as_millis() as u64,
+        "request completed"
+    );
 }
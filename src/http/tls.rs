@@ -0,0 +1,92 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use axum::{extract::ConnectInfo, Extension, Router};
+use tokio::net::TcpListener;
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+        ServerConfig,
+    },
+    TlsAcceptor,
+};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use super::listener::serve_connection;
+
+/// Time a client gets to complete the TLS handshake before it is dropped
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Load a PEM certificate chain and private key into a rustls server config
+pub fn load_tls_config(cert_path: &str, key_path: &str) -> Result<Arc<ServerConfig>> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .with_context(|| format!("failed to read TLS certificate: {}", cert_path))?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("failed to parse TLS certificate: {}", cert_path))?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .with_context(|| format!("failed to read TLS private key: {}", key_path))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .context("failed to select TLS protocol versions")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("invalid TLS certificate or key")?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(config))
+}
+
+/// Serve the router over TLS until the token is cancelled
+///
+/// Each connection gets the peer address as `ConnectInfo`, like
+/// `into_make_service_with_connect_info` does for plain HTTP. Clients that don't
+/// finish the handshake within `TLS_HANDSHAKE_TIMEOUT` are dropped. On shutdown,
+/// open connections finish their in-flight requests before this returns.
+pub async fn serve_tls(
+    listener: TcpListener,
+    app: Router,
+    tls_config: Arc<ServerConfig>,
+    shutdown_token: CancellationToken,
+) -> Result<()> {
+    let acceptor = TlsAcceptor::from(tls_config);
+    let connections = TaskTracker::new();
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            () = shutdown_token.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "failed to accept connection");
+                    continue;
+                }
+            },
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone().layer(Extension(ConnectInfo::<SocketAddr>(peer)));
+        let shutdown_token = shutdown_token.clone();
+        connections.spawn(async move {
+            let stream = match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(err)) => {
+                    tracing::debug!(error = ?err, peer = %peer, "TLS handshake failed");
+                    return;
+                }
+                Err(_) => {
+                    tracing::debug!(peer = %peer, "TLS handshake timed out");
+                    return;
+                }
+            };
+
+            serve_connection(stream, app, shutdown_token).await;
+        });
+    }
+
+    connections.close();
+    connections.wait().await;
+    tracing::info!("TLS listener shut down");
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! Identity helpers
+//!
+//! Operators often paste handles (`@spam.bsky.social`) where a DID is expected.
+//! These helpers recognise handles and resolve them to DIDs through
+//! `com.atproto.identity.resolveHandle` on a PDS or AppView.
+//!
+//! DID documents are resolved from a PLC directory for `did:plc` and from
+//! `/.well-known/did.json` for `did:web` (hostname form only, as in atproto).
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Returns true if the value looks like a handle rather than a DID
+///
+/// Accepts an optional leading `@`. Handles are domain names: at least two
+/// dot-separated labels of ASCII letters, digits, and hyphens.
+pub fn is_handle(value: &str) -> bool {
+    let value = value.strip_prefix('@').unwrap_or(value);
+    if value.starts_with("did:") || value.len() > 253 {
+        return false;
+    }
+
+    let labels: Vec<&str> = value.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Strip the optional leading `@` and lowercase the handle
+pub fn normalize_handle(value: &str) -> String {
+    value.strip_prefix('@').unwrap_or(value).to_ascii_lowercase()
+}
+
+/// Resolve a handle to a DID via `com.atproto.identity.resolveHandle`
+pub async fn resolve_handle(
+    http_client: &reqwest::Client,
+    service_url: &str,
+    handle: &str,
+) -> Result<String> {
+    #[derive(Deserialize)]
+    struct ResolveHandleResponse {
+        did: String,
+    }
+
+    let handle = normalize_handle(handle);
+    let url = format!(
+        "{}/xrpc/com.atproto.identity.resolveHandle",
+        service_url.trim_end_matches('/')
+    );
+
+    let response = http_client
+        .get(&url)
+        .query(&[("handle", handle.as_str())])
+        .send()
+        .await
+        .with_context(|| format!("Failed to send resolveHandle request for {}", handle))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "(failed to read body)".to_string());
+        anyhow::bail!("resolveHandle failed for {}: {} - {}", handle, status, body);
+    }
+
+    let resolved: ResolveHandleResponse = response
+        .json()
+        .await
+        .context("Failed to parse resolveHandle response")?;
+
+    if !resolved.did.starts_with("did:") {
+        anyhow::bail!("resolveHandle returned invalid DID for {}: {}", handle, resolved.did);
+    }
+
+    Ok(resolved.did)
+}
+
+/// Resolve a DID document for `did:plc` or `did:web`
+pub async fn resolve_did_document(
+    http_client: &reqwest::Client,
+    plc_hostname: &str,
+    did: &str,
+) -> Result<serde_json::Value> {
+    let url = if did.starts_with("did:plc:") {
+        format!("https://{}/{}", plc_hostname, did)
+    } else if let Some(hostname) = did.strip_prefix("did:web:") {
+        if hostname.contains(':') {
+            anyhow::bail!("did:web with path components is not supported: {}", did);
+        }
+        format!("https://{}/.well-known/did.json", hostname)
+    } else {
+        anyhow::bail!("Unsupported DID method: {}", did);
+    };
+
+    let response = http_client
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to send DID document request for {}", did))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("DID document request failed for {}: {}", did, status);
+    }
+
+    let document: serde_json::Value = response
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse DID document for {}", did))?;
+
+    if document.get("id").and_then(|id| id.as_str()) != Some(did) {
+        anyhow::bail!("DID document id does not match {}", did);
+    }
+
+    Ok(document)
+}
+
+/// Extract the atproto signing key (multibase multikey) from a DID document
+pub fn did_document_signing_key(document: &serde_json::Value) -> Option<String> {
+    let methods = document.get("verificationMethod")?.as_array()?;
+
+    methods.iter().find_map(|method| {
+        let id = method.get("id")?.as_str()?;
+        if !id.ends_with("#atproto") {
+            return None;
+        }
+        method
+            .get("publicKeyMultibase")?
+            .as_str()
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_handle() {
+        assert!(is_handle("spam.bsky.social"));
+        assert!(is_handle("@Spam.bsky.social"));
+        assert!(is_handle("example.com"));
+        assert!(!is_handle("did:plc:abc123"));
+        assert!(!is_handle("localhost"));
+        assert!(!is_handle("bad_name.bsky.social"));
+        assert!(!is_handle("-bad.bsky.social"));
+        assert!(!is_handle("spam..social"));
+    }
+
+    #[test]
+    fn test_normalize_handle() {
+        assert_eq!(normalize_handle("@Spam.Bsky.Social"), "spam.bsky.social");
+        assert_eq!(normalize_handle("alice.example.com"), "alice.example.com");
+    }
+
+    #[test]
+    fn test_did_document_signing_key() {
+        let document = serde_json::json!({
+            "id": "did:plc:abc123",
+            "verificationMethod": [
+                {
+                    "id": "did:plc:abc123#other",
+                    "type": "Multikey",
+                    "publicKeyMultibase": "zOther"
+                },
+                {
+                    "id": "did:plc:abc123#atproto",
+                    "type": "Multikey",
+                    "controller": "did:plc:abc123",
+                    "publicKeyMultibase": "zQ3shXjHeiBuRCKmM36cuYnm7YEMzhGnCmCyW92sRJ9pribSF"
+                }
+            ]
+        });
+
+        assert_eq!(
+            did_document_signing_key(&document).as_deref(),
+            Some("zQ3shXjHeiBuRCKmM36cuYnm7YEMzhGnCmCyW92sRJ9pribSF")
+        );
+        assert!(did_document_signing_key(&serde_json::json!({"id": "did:plc:abc123"})).is_none());
+    }
+}
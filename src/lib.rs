@@ -1,8 +1,26 @@
+pub mod aturi;
+pub mod backup;
+pub mod cache;
+pub mod cid;
 pub mod cleanup;
 pub mod errors;
 pub mod feed_builder;
 pub mod feed_config;
+pub mod feed_events;
+pub mod feed_records;
 pub mod feed_storage;
 pub mod http;
+pub mod identity;
+#[cfg(test)]
+mod mock_pds;
+pub mod redact;
+pub mod serve_log;
+pub mod server;
 pub mod server_config;
+pub mod service_auth;
+pub mod starter_pack;
 pub mod user_storage;
+pub mod vmc;
+pub mod webhook;
+
+pub use server::{Server, ServerBuilder};
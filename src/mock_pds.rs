@@ -0,0 +1,263 @@
+//! Fake PDS for timeline consumer tests
+//!
+//! Serves getTimeline from a fixed list of pages and rotates tokens on
+//! refreshSession, so polling can be tested end to end against a local server.
+//! Page N is requested with cursor "N"; the last page has no cursor. Starter
+//! packs are served in a single getList page.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Default)]
+struct MockPdsState {
+    did: String,
+    pages: Vec<Vec<serde_json::Value>>,
+    access_token: String,
+    refresh_token: String,
+    /// `(limit, remaining)` sent as ratelimit headers with every timeline page
+    rate_limit: Option<(u32, u32)>,
+    /// Cursor of every getTimeline request, in order
+    timeline_requests: Vec<Option<String>>,
+    refreshes: u32,
+    /// Member DIDs by starter pack URI
+    starter_packs: HashMap<String, Vec<String>>,
+}
+
+/// A fake PDS listening on a random local port until dropped
+pub(crate) struct MockPds {
+    pub url: String,
+    state: Arc<Mutex<MockPdsState>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl MockPds {
+    /// Serve `pages` of timeline items for `did`, accepting access token "access-0"
+    pub async fn start(did: &str, pages: Vec<Vec<serde_json::Value>>) -> Self {
+        let state = Arc::new(Mutex::new(MockPdsState {
+            did: did.to_string(),
+            pages,
+            access_token: "access-0".to_string(),
+            refresh_token: "refresh-0".to_string(),
+            ..Default::default()
+        }));
+
+        let app = Router::new()
+            .route("/xrpc/app.bsky.feed.getTimeline", get(handle_get_timeline))
+            .route("/xrpc/com.atproto.server.refreshSession", post(handle_refresh_session))
+            .route("/xrpc/app.bsky.graph.getStarterPack", get(handle_get_starter_pack))
+            .route("/xrpc/app.bsky.graph.getList", get(handle_get_list))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Self { url, state, server }
+    }
+
+    /// Send ratelimit headers with every timeline page
+    pub fn set_rate_limit(&self, limit: u32, remaining: u32) {
+        self.state.lock().unwrap().rate_limit = Some((limit, remaining));
+    }
+
+    /// Serve a starter pack with these members
+    pub fn add_starter_pack(&self, uri: &str, members: &[&str]) {
+        let members = members.iter().map(|did| did.to_string()).collect();
+        self.state
+            .lock()
+            .unwrap()
+            .starter_packs
+            .insert(uri.to_string(), members);
+    }
+
+    /// The access token currently accepted by getTimeline
+    pub fn access_token(&self) -> String {
+        self.state.lock().unwrap().access_token.clone()
+    }
+
+    /// Number of successful refreshSession calls
+    pub fn refreshes(&self) -> u32 {
+        self.state.lock().unwrap().refreshes
+    }
+
+    /// Cursor of every getTimeline request so far
+    pub fn timeline_requests(&self) -> Vec<Option<String>> {
+        self.state.lock().unwrap().timeline_requests.clone()
+    }
+}
+
+impl Drop for MockPds {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// A timeline item for a post by `author`
+pub(crate) fn timeline_post(author: &str, rkey: &str, indexed_at: &str) -> serde_json::Value {
+    json!({
+        "post": {
+            "uri": format!("at://{}/app.bsky.feed.post/{}", author, rkey),
+            "cid": format!("cid-{}", rkey),
+            "author": {"did": author, "handle": "author.test"},
+            "record": {"text": format!("Post {}", rkey)},
+            "indexedAt": indexed_at,
+        }
+    })
+}
+
+/// A timeline item for a post by `author` reposted by `reposter`
+pub(crate) fn timeline_repost(author: &str, rkey: &str, reposter: &str, indexed_at: &str) -> serde_json::Value {
+    let mut item = timeline_post(author, rkey, indexed_at);
+    item["reason"] = json!({
+        "$type": "app.bsky.feed.defs#reasonRepost",
+        "by": {"did": reposter, "handle": "reposter.test"},
+        "uri": format!("at://{}/app.bsky.feed.repost/{}", reposter, rkey),
+        "indexedAt": indexed_at,
+    });
+    item
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+fn expired_token() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({"error": "ExpiredToken", "message": "Token has expired"})),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct TimelineParams {
+    cursor: Option<String>,
+}
+
+async fn handle_get_timeline(
+    State(state): State<Arc<Mutex<MockPdsState>>>,
+    headers: HeaderMap,
+    Query(params): Query<TimelineParams>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    if bearer(&headers) != Some(state.access_token.as_str()) {
+        return expired_token();
+    }
+    state.timeline_requests.push(params.cursor.clone());
+
+    let page = params
+        .cursor
+        .as_deref()
+        .and_then(|cursor| cursor.parse::<usize>().ok())
+        .unwrap_or(0);
+    let feed = state.pages.get(page).cloned().unwrap_or_default();
+    let cursor = (page + 1 < state.pages.len()).then(|| (page + 1).to_string());
+
+    let mut response_headers = HeaderMap::new();
+    if let Some((limit, remaining)) = state.rate_limit {
+        response_headers.insert("ratelimit-limit", limit.into());
+        response_headers.insert("ratelimit-remaining", remaining.into());
+    }
+
+    (response_headers, Json(json!({"feed": feed, "cursor": cursor}))).into_response()
+}
+
+async fn handle_refresh_session(
+    State(state): State<Arc<Mutex<MockPdsState>>>,
+    headers: HeaderMap,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    if bearer(&headers) != Some(state.refresh_token.as_str()) {
+        return expired_token();
+    }
+
+    state.refreshes += 1;
+    state.access_token = format!("access-{}", state.refreshes);
+    state.refresh_token = format!("refresh-{}", state.refreshes);
+
+    Json(json!({
+        "accessJwt": state.access_token,
+        "refreshJwt": state.refresh_token,
+        "did": state.did,
+        "handle": "owner.test",
+    }))
+    .into_response()
+}
+
+/// URI of the list behind a starter pack
+fn starter_pack_list(starter_pack: &str) -> String {
+    starter_pack.replace("app.bsky.graph.starterpack", "app.bsky.graph.list")
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StarterPackParams {
+    starter_pack: String,
+}
+
+async fn handle_get_starter_pack(
+    State(state): State<Arc<Mutex<MockPdsState>>>,
+    headers: HeaderMap,
+    Query(params): Query<StarterPackParams>,
+) -> Response {
+    let state = state.lock().unwrap();
+    if bearer(&headers) != Some(state.access_token.as_str()) {
+        return expired_token();
+    }
+    if !state.starter_packs.contains_key(&params.starter_pack) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "NotFound"}))).into_response();
+    }
+
+    Json(json!({
+        "starterPack": {
+            "uri": params.starter_pack,
+            "list": {"uri": starter_pack_list(&params.starter_pack)},
+        }
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    list: String,
+}
+
+async fn handle_get_list(
+    State(state): State<Arc<Mutex<MockPdsState>>>,
+    headers: HeaderMap,
+    Query(params): Query<ListParams>,
+) -> Response {
+    let state = state.lock().unwrap();
+    if bearer(&headers) != Some(state.access_token.as_str()) {
+        return expired_token();
+    }
+    let Some(members) = state
+        .starter_packs
+        .iter()
+        .find(|(starter_pack, _)| starter_pack_list(starter_pack) == params.list)
+        .map(|(_, members)| members)
+    else {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "NotFound"}))).into_response();
+    };
+
+    let items: Vec<serde_json::Value> = members
+        .iter()
+        .map(|did| json!({"uri": format!("{}/item", params.list), "subject": {"did": did}}))
+        .collect();
+    Json(json!({"list": {"uri": params.list}, "items": items})).into_response()
+}
@@ -0,0 +1,219 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::feed_storage::StoragePool;
+
+/// Served pages waiting to be written; new pages are dropped beyond this
+const SERVE_LOG_QUEUE_SIZE: usize = 1000;
+
+/// Where served pages are written
+pub enum ServeLogSink {
+    /// The feed_serve_log table
+    Database(StoragePool),
+    /// One NDJSON line per served post, appended to a file
+    File(PathBuf),
+}
+
+/// One getFeedSkeleton page as it was served
+#[derive(Clone, Debug)]
+pub struct ServedPage {
+    pub feed_uri: String,
+    /// Owner of a private feed, from its verified service JWT; unknown for public feeds
+    pub viewer_did: Option<String>,
+    /// Issuer of the service JWT, for every feed, with the signature unchecked
+    pub viewer_did_claimed: Option<String>,
+    /// Cursor the page was requested with
+    pub cursor: Option<String>,
+    /// Post URIs in the order they were served
+    pub uris: Vec<String>,
+    pub served_at: DateTime<Utc>,
+}
+
+/// Handle for logging served pages, cheap to clone
+///
+/// Logging never waits: when the writer falls behind, pages are dropped rather
+/// than slowing down getFeedSkeleton.
+#[derive(Clone)]
+pub struct ServeLog(Option<mpsc::Sender<ServedPage>>);
+
+impl ServeLog {
+    /// A log that discards everything
+    pub fn disabled() -> Self {
+        Self(None)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Queue a served page for writing
+    pub fn push(&self, page: ServedPage) {
+        let Some(sender) = &self.0 else {
+            return;
+        };
+        if let Err(err) = sender.try_send(page) {
+            tracing::warn!(error = %err, "Serve log queue full, dropping page");
+        }
+    }
+}
+
+/// Writes served pages to the configured sink
+pub struct ServeLogTask {
+    sink: ServeLogSink,
+    receiver: mpsc::Receiver<ServedPage>,
+    cancellation_token: CancellationToken,
+}
+
+impl ServeLogTask {
+    pub fn new(sink: ServeLogSink, cancellation_token: CancellationToken) -> (Self, ServeLog) {
+        let (sender, receiver) = mpsc::channel(SERVE_LOG_QUEUE_SIZE);
+        let task = Self {
+            sink,
+            receiver,
+            cancellation_token,
+        };
+        (task, ServeLog(Some(sender)))
+    }
+
+    pub async fn run_background(mut self) -> Result<()> {
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            received = self.receiver.recv() => {
+                let Some(page) = received else {
+                    break;
+                };
+                if let Err(err) = write_page(&self.sink, &page).await {
+                    tracing::warn!(feed_uri = %page.feed_uri, error = ?err, "Failed to write serve log");
+                }
+            }
+            }
+        }
+
+        // Write what is left before shutting down
+        self.receiver.close();
+        while let Some(page) = self.receiver.recv().await {
+            if let Err(err) = write_page(&self.sink, &page).await {
+                tracing::warn!(feed_uri = %page.feed_uri, error = ?err, "Failed to write serve log");
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn write_page(sink: &ServeLogSink, page: &ServedPage) -> Result<()> {
+    match sink {
+        ServeLogSink::Database(pool) => serve_log_insert(pool, page).await,
+        ServeLogSink::File(path) => {
+            let mut lines = String::new();
+            for (position, uri) in page.uris.iter().enumerate() {
+                let line = json!({
+                    "feed": page.feed_uri,
+                    "viewer": page.viewer_did,
+                    "viewer_claimed": page.viewer_did_claimed,
+                    "cursor": page.cursor,
+                    "position": position,
+                    "uri": uri,
+                    "served_at": page.served_at.to_rfc3339(),
+                });
+                lines.push_str(&line.to_string());
+                lines.push('\n');
+            }
+
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(lines.as_bytes()))
+                .with_context(|| format!("failed to append to {}", path.display()))
+        }
+    }
+}
+
+/// Store a served page, one row per post
+async fn serve_log_insert(pool: &StoragePool, page: &ServedPage) -> Result<()> {
+    let mut tx = pool.begin().await.context("failed to begin transaction")?;
+
+    for (position, uri) in page.uris.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO feed_serve_log (feed_uri, viewer_did, viewer_did_claimed, cursor, position, uri, served_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&page.feed_uri)
+        .bind(&page.viewer_did)
+        .bind(&page.viewer_did_claimed)
+        .bind(&page.cursor)
+        .bind(position as i64)
+        .bind(uri)
+        .bind(page.served_at.to_rfc3339())
+        .execute(tx.as_mut())
+        .await
+        .context("failed to insert serve log row")?;
+    }
+
+    tx.commit().await.context("failed to commit transaction")
+}
+
+/// Delete serve log rows older than `before`
+pub async fn serve_log_remove_before(pool: &StoragePool, before: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM feed_serve_log WHERE served_at < ?")
+        .bind(before.to_rfc3339())
+        .execute(pool)
+        .await
+        .context("failed to remove old serve log rows")?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+
+    #[tokio::test]
+    async fn test_write_page() {
+        let page = ServedPage {
+            feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/test".to_string(),
+            viewer_did: Some("did:plc:viewer".to_string()),
+            viewer_did_claimed: Some("did:plc:viewer".to_string()),
+            cursor: None,
+            uris: vec![
+                "at://did:plc:author/app.bsky.feed.post/2".to_string(),
+                "at://did:plc:author/app.bsky.feed.post/1".to_string(),
+            ],
+            served_at: Utc::now(),
+        };
+
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        write_page(&ServeLogSink::Database(pool.clone()), &page).await.unwrap();
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as("SELECT position, uri FROM feed_serve_log ORDER BY position")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], (1, page.uris[1].clone()));
+        assert_eq!(serve_log_remove_before(&pool, Utc::now()).await.unwrap(), 2);
+
+        let path = std::env::temp_dir().join(format!("serve-log-{}.ndjson", std::process::id()));
+        let sink = ServeLogSink::File(path.clone());
+        write_page(&sink, &page).await.unwrap();
+        write_page(&sink, &page).await.unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<serde_json::Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["viewer"], "did:plc:viewer");
+        assert_eq!(lines[1]["position"], 1);
+    }
+}
@@ -0,0 +1,449 @@
+//! Running the feed generator inside another service
+//!
+//! [`Server::builder`] composes the storage, timeline consumer and router
+//! from values instead of the environment, so a larger Rust service can embed
+//! the feed generator and mount its router next to its own routes.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use timeline_filter::feed_storage::{connect_pools, migrate};
+//! use timeline_filter::server_config::ServiceIdentity;
+//! use timeline_filter::Server;
+//! use tokio_util::{sync::CancellationToken, task::TaskTracker};
+//!
+//! let pools = connect_pools("sqlite://feeds.db", 1, 8, std::time::Duration::from_secs(5)).await?;
+//! migrate(&pools).await?;
+//!
+//! let token = CancellationToken::new();
+//! let tracker = TaskTracker::new();
+//! let server = Server::builder()
+//!     .with_database(pools)
+//!     .with_service(ServiceIdentity::new("feeds.example.com"))
+//!     .build(token.clone())?;
+//! let router = server.start(&tracker);
+//!
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:4050").await?;
+//! axum::serve(listener, router).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use anyhow::{anyhow, Result};
+use axum::Router;
+use chrono::Duration;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use crate::cache::FeedCache;
+use crate::feed_builder::{TimelineConsumerConfig, TimelineConsumerTask};
+use crate::feed_config::{AggregateFeed, TimelineFeed, TimelineFeeds};
+use crate::feed_events::FeedEvents;
+use crate::feed_storage::DatabasePools;
+use crate::http::context::WebContext;
+use crate::http::rate_limit::{LoginLimiter, RateLimiter};
+use crate::http::server::{build_replica_router, build_router};
+use crate::serve_log::{ServeLog, ServeLogSink, ServeLogTask};
+use crate::server_config::ServiceIdentity;
+use crate::vmc::VerificationMethodCache;
+use crate::webhook::{WebhookQueue, WebhookTask, WEBHOOK_FLUSH_INTERVAL};
+
+/// Settings for [`Server`], with the same defaults as the environment
+pub struct ServerBuilder {
+    pools: Option<DatabasePools>,
+    service: Option<ServiceIdentity>,
+    http_client: Option<reqwest::Client>,
+    consumer_client_builder: Option<reqwest::ClientBuilder>,
+    timeline_feeds: TimelineFeeds,
+    consumer_enable: bool,
+    poll_interval: Duration,
+    handle_refresh_interval: Duration,
+    store_post_metadata: bool,
+    validate_cids: bool,
+    admin_token: Option<String>,
+    feed_cache_size: usize,
+    feed_cache_ttl: Duration,
+    rate_limiter: Option<RateLimiter>,
+    login_limiter: Option<LoginLimiter>,
+    plc_hostname: String,
+    vmc_cache_max_age: Duration,
+    serve_log: Option<ServeLogSink>,
+    read_replica: bool,
+}
+
+impl Default for ServerBuilder {
+    fn default() -> Self {
+        Self {
+            pools: None,
+            service: None,
+            http_client: None,
+            consumer_client_builder: None,
+            timeline_feeds: TimelineFeeds::default(),
+            consumer_enable: true,
+            poll_interval: Duration::seconds(30),
+            handle_refresh_interval: Duration::hours(6),
+            store_post_metadata: false,
+            validate_cids: false,
+            admin_token: None,
+            feed_cache_size: 500,
+            feed_cache_ttl: Duration::minutes(5),
+            rate_limiter: None,
+            login_limiter: None,
+            plc_hostname: "plc.directory".to_string(),
+            vmc_cache_max_age: Duration::hours(24),
+            serve_log: None,
+            read_replica: false,
+        }
+    }
+}
+
+impl ServerBuilder {
+    /// Database to serve from, with migrations already applied
+    pub fn with_database(mut self, pools: DatabasePools) -> Self {
+        self.pools = Some(pools);
+        self
+    }
+
+    /// How the service presents itself in did.json and describeFeedGenerator
+    pub fn with_service(mut self, service: ServiceIdentity) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Client for DID resolution and webhooks
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Client settings for polling timelines; the consumer adds its own timeout
+    pub fn with_consumer_client_builder(mut self, client_builder: reqwest::ClientBuilder) -> Self {
+        self.consumer_client_builder = Some(client_builder);
+        self
+    }
+
+    /// Serve and poll a user's timeline feed
+    pub fn with_feed(mut self, feed: TimelineFeed) -> Self {
+        self.timeline_feeds.timeline_feeds.push(feed);
+        self
+    }
+
+    /// Serve a feed merging the timelines of several feeds
+    pub fn with_aggregate_feed(mut self, aggregate: AggregateFeed) -> Self {
+        self.timeline_feeds.aggregate_feeds.push(aggregate);
+        self
+    }
+
+    /// Add every feed of a loaded config file
+    pub fn with_feeds(mut self, feeds: TimelineFeeds) -> Self {
+        self.timeline_feeds.timeline_feeds.extend(feeds.timeline_feeds);
+        self.timeline_feeds.aggregate_feeds.extend(feeds.aggregate_feeds);
+        self
+    }
+
+    /// Whether to poll timelines, on by default
+    pub fn with_consumer(mut self, enable: bool) -> Self {
+        self.consumer_enable = enable;
+        self
+    }
+
+    /// Poll interval of feeds that don't set their own
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// How often handles in feed filters are re-resolved to DIDs
+    pub fn with_handle_refresh_interval(mut self, interval: Duration) -> Self {
+        self.handle_refresh_interval = interval;
+        self
+    }
+
+    /// Store the text, author and embed type of indexed posts
+    pub fn with_post_metadata(mut self, enable: bool) -> Self {
+        self.store_post_metadata = enable;
+        self
+    }
+
+    /// Skip posts whose CID is not a valid record CID
+    pub fn with_cid_validation(mut self, enable: bool) -> Self {
+        self.validate_cids = enable;
+        self
+    }
+
+    /// Token accepted by the admin and feed content endpoints
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.admin_token = Some(admin_token.into());
+        self
+    }
+
+    /// Feeds kept in memory and how long until they are reloaded
+    pub fn with_feed_cache(mut self, size: usize, ttl: Duration) -> Self {
+        self.feed_cache_size = size;
+        self.feed_cache_ttl = ttl;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Sign-in throttling for /login; by default client IPs are the peer addresses
+    pub fn with_login_limiter(mut self, login_limiter: LoginLimiter) -> Self {
+        self.login_limiter = Some(login_limiter);
+        self
+    }
+
+    /// PLC directory used to resolve did:plc signing keys
+    pub fn with_plc_hostname(mut self, plc_hostname: impl Into<String>) -> Self {
+        self.plc_hostname = plc_hostname.into();
+        self
+    }
+
+    /// How long resolved signing keys are trusted
+    pub fn with_vmc_cache_max_age(mut self, max_age: Duration) -> Self {
+        self.vmc_cache_max_age = max_age;
+        self
+    }
+
+    /// Record every served getFeedSkeleton page
+    pub fn with_serve_log(mut self, sink: ServeLogSink) -> Self {
+        self.serve_log = Some(sink);
+        self
+    }
+
+    /// Serve feeds from a database written by another instance
+    ///
+    /// Only feed routes are served and the timeline consumer does not run.
+    pub fn with_read_replica(mut self, read_replica: bool) -> Self {
+        self.read_replica = read_replica;
+        self
+    }
+
+    /// Check the settings and set up the server
+    ///
+    /// Cancelling `cancellation_token` stops the background tasks and ends
+    /// open feed streams.
+    pub fn build(self, cancellation_token: CancellationToken) -> Result<Server> {
+        let pools = self
+            .pools
+            .ok_or_else(|| anyhow!("a database is required, see with_database"))?;
+        let service = self
+            .service
+            .ok_or_else(|| anyhow!("a service identity is required, see with_service"))?;
+        if self.read_replica && matches!(self.serve_log, Some(ServeLogSink::Database(_))) {
+            anyhow::bail!("a read replica cannot write the serve log to the database");
+        }
+        for feed in &self.timeline_feeds.timeline_feeds {
+            feed.validate()?;
+        }
+        for aggregate in &self.timeline_feeds.aggregate_feeds {
+            aggregate.validate()?;
+        }
+
+        let http_client = self.http_client.unwrap_or_default();
+        let feed_cache = FeedCache::new(pools.read.clone(), self.feed_cache_size, self.feed_cache_ttl);
+
+        let mut verification_method_cache = VerificationMethodCache::new(
+            pools.write.clone(),
+            http_client.clone(),
+            &self.plc_hostname,
+            self.vmc_cache_max_age,
+        );
+        if self.read_replica {
+            verification_method_cache = verification_method_cache.read_only();
+        }
+
+        let (serve_log_task, serve_log) = match self.serve_log {
+            Some(sink) => {
+                let (task, serve_log) = ServeLogTask::new(sink, cancellation_token.clone());
+                (Some(task), serve_log)
+            }
+            None => (None, ServeLog::disabled()),
+        };
+
+        // Posts indexed by the consumer, streamed to /api/v1/feeds/:feed/stream
+        let feed_events = FeedEvents::new(cancellation_token.clone());
+
+        let mut webhook_task = None;
+        // Runs without configured feeds too, polling the self-service feeds
+        let consumer = if !self.consumer_enable || self.read_replica {
+            None
+        } else {
+            // Deliver newly indexed posts to feeds that configure a webhook
+            let webhooks = if self.timeline_feeds.has_webhooks() {
+                let (task, webhooks) = WebhookTask::new(
+                    pools.write.clone(),
+                    http_client.clone(),
+                    cancellation_token.clone(),
+                );
+                webhook_task = Some(task);
+                webhooks
+            } else {
+                WebhookQueue::disabled()
+            };
+
+            let consumer_config = TimelineConsumerConfig {
+                timeline_feeds: self.timeline_feeds,
+                default_poll_interval: self.poll_interval,
+                handle_refresh_interval: self.handle_refresh_interval,
+                store_post_metadata: self.store_post_metadata,
+                validate_cids: self.validate_cids,
+            };
+            Some(TimelineConsumerTask::new(
+                pools.write.clone(),
+                self.consumer_client_builder.unwrap_or_default(),
+                consumer_config,
+                feed_cache.clone(),
+                webhooks,
+                feed_events.clone(),
+                cancellation_token.clone(),
+            )?)
+        };
+
+        let web_context = WebContext::new(
+            pools,
+            http_client,
+            service,
+            self.admin_token,
+            feed_cache,
+            self.rate_limiter,
+            self.login_limiter.unwrap_or_else(|| LoginLimiter::new(None)),
+            verification_method_cache.clone(),
+            serve_log,
+            feed_events,
+        );
+
+        Ok(Server {
+            web_context,
+            verification_method_cache,
+            read_replica: self.read_replica,
+            consumer,
+            webhook_task,
+            serve_log_task,
+            cancellation_token,
+        })
+    }
+}
+
+/// A configured feed generator, ready to start
+pub struct Server {
+    web_context: WebContext,
+    verification_method_cache: VerificationMethodCache,
+    read_replica: bool,
+    consumer: Option<TimelineConsumerTask>,
+    webhook_task: Option<WebhookTask>,
+    serve_log_task: Option<ServeLogTask>,
+    cancellation_token: CancellationToken,
+}
+
+impl Server {
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+
+    pub fn web_context(&self) -> &WebContext {
+        &self.web_context
+    }
+
+    /// Signing key cache shared with the router, e.g. for a refresh task
+    pub fn verification_method_cache(&self) -> &VerificationMethodCache {
+        &self.verification_method_cache
+    }
+
+    /// Whether the timeline consumer runs once started
+    pub fn has_consumer(&self) -> bool {
+        self.consumer.is_some()
+    }
+
+    /// Spawn the background tasks on `tracker` and return the router to serve
+    ///
+    /// A task that stops, for any reason, cancels the server's token so the
+    /// whole service shuts down rather than running half-working.
+    pub fn start(self, tracker: &TaskTracker) -> Router {
+        let token = self.cancellation_token;
+
+        if let Some(task) = self.serve_log_task {
+            spawn_task(tracker, &token, "serve log", task.run_background());
+        }
+        if let Some(task) = self.webhook_task {
+            spawn_task(tracker, &token, "webhook", task.run_background(WEBHOOK_FLUSH_INTERVAL));
+        }
+        if let Some(task) = self.consumer {
+            spawn_task(tracker, &token, "timeline consumer", task.run_background());
+        }
+
+        if self.read_replica {
+            build_replica_router(self.web_context)
+        } else {
+            build_router(self.web_context)
+        }
+    }
+}
+
+fn spawn_task(
+    tracker: &TaskTracker,
+    token: &CancellationToken,
+    name: &'static str,
+    task: impl Future<Output = Result<()>> + Send + 'static,
+) {
+    let inner_token = token.clone();
+    tracker.spawn(async move {
+        if let Err(err) = task.await {
+            tracing::warn!(error = ?err, "{} task error", name);
+        }
+        inner_token.cancel();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed_storage::{connect_pools, migrate};
+
+    #[tokio::test]
+    async fn test_server_builder() {
+        let pools = connect_pools("sqlite::memory:", 1, 1, std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+        migrate(&pools).await.unwrap();
+        let service = ServiceIdentity::new("feeds.example.com");
+        assert_eq!(service.service_did, "did:web:feeds.example.com");
+        assert_eq!(service.feed_service_endpoint, "https://feeds.example.com");
+
+        let token = CancellationToken::new();
+        assert!(Server::builder().with_service(service.clone()).build(token.clone()).is_err());
+        assert!(Server::builder()
+            .with_database(pools.clone())
+            .with_service(service.clone())
+            .with_read_replica(true)
+            .with_serve_log(ServeLogSink::Database(pools.write.clone()))
+            .build(token.clone())
+            .is_err());
+
+        assert!(!Server::builder()
+            .with_database(pools.clone())
+            .with_service(service.clone())
+            .with_consumer(false)
+            .build(token.clone())
+            .unwrap()
+            .has_consumer());
+
+        // Without configured feeds the consumer still polls self-service feeds
+        let server = Server::builder()
+            .with_database(pools)
+            .with_service(service)
+            .with_admin_token("secret")
+            .build(token.clone())
+            .unwrap();
+        assert!(server.has_consumer());
+        assert_eq!(server.web_context().service_did(), "did:web:feeds.example.com");
+
+        let tracker = TaskTracker::new();
+        let _router = server.start(&tracker);
+        tracker.close();
+        token.cancel();
+        tracker.wait().await;
+    }
+}
@@ -1,5 +1,8 @@
 
-use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use anyhow::{anyhow, Context, Result};
 use chrono::Duration;
 
 use crate::feed_config::TimelineFeeds;
@@ -7,96 +10,490 @@ use crate::feed_config::TimelineFeeds;
 #[derive(Clone)]
 pub struct HttpPort(u16);
 
+#[derive(Clone)]
+pub struct BindAddr(IpAddr);
+
 #[derive(Clone)]
 pub struct CertificateBundles(Vec<String>);
 
+/// Outbound proxy per destination host, from HTTP_PROXIES
+///
+/// Rules are `host=proxy` pairs separated by `;`, checked in order. A host
+/// matches itself and its subdomains, `*` matches every host, and the proxy
+/// `direct` connects without one. Unmatched hosts connect directly.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProxyRules(Vec<(String, Option<reqwest::Url>)>);
+
+/// IP version outbound connections are limited to, from HTTP_IP_FAMILY
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IpFamily {
+    Any,
+    V4,
+    V6,
+}
+
+/// Where getFeedSkeleton responses are logged, from SERVE_LOG
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServeLogTarget {
+    Disabled,
+    /// The feed_serve_log table
+    Database,
+    /// An NDJSON file, appended to
+    File(String),
+}
+
+/// Static `host=ip` DNS overrides for outbound requests, from HTTP_HOST_OVERRIDES
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HostOverrides(Vec<(String, IpAddr)>);
+
 #[derive(Clone)]
 pub struct TaskEnable(bool);
 
+#[derive(Clone)]
+pub struct PoolSize(u32);
+
+#[derive(Clone)]
+pub struct CacheSize(usize);
+
+#[derive(Clone)]
+pub struct RequestsPerMinute(u32);
+
+#[derive(Clone)]
+pub struct RetainCount(usize);
+
 #[derive(Clone)]
 pub struct TaskInterval(Duration);
 
+/// A service entry published in the did:web document
+#[derive(Clone, Debug, PartialEq)]
+pub struct DidService {
+    /// Fragment id, e.g. "#atproto_labeler"
+    pub id: String,
+    pub service_type: String,
+    pub endpoint: String,
+}
+
+/// How this service presents itself: its public URL and did:web document
+#[derive(Clone)]
+pub struct ServiceIdentity {
+    pub external_base: String,
+    /// DID of this feed generator: SERVICE_DID, else did:web of EXTERNAL_BASE
+    pub service_did: String,
+    /// Id of the BskyFeedGenerator service entry
+    pub feed_service_id: String,
+    /// Endpoint of the BskyFeedGenerator service entry
+    pub feed_service_endpoint: String,
+    /// Further services on the same host, e.g. a labeler
+    pub services: Vec<DidService>,
+    pub also_known_as: Vec<String>,
+    /// Links returned by describeFeedGenerator
+    pub privacy_policy_url: Option<String>,
+    pub terms_of_service_url: Option<String>,
+}
+
+impl ServiceIdentity {
+    /// The did:web identity of a feed generator served at `external_base`
+    pub fn new(external_base: &str) -> Self {
+        Self {
+            external_base: external_base.to_string(),
+            service_did: did_web(external_base),
+            feed_service_id: "#bsky_fg".to_string(),
+            feed_service_endpoint: default_feed_service_endpoint(external_base),
+            services: vec![],
+            also_known_as: vec![],
+            privacy_policy_url: None,
+            terms_of_service_url: None,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Config {
     pub version: String,
+    pub http_bind_addr: BindAddr,
     pub http_port: HttpPort,
-    pub external_base: String,
+    /// Unix socket path to listen on instead of the TCP port
+    pub http_socket: Option<String>,
+    pub service: ServiceIdentity,
     pub database_url: String,
+    /// Size of the write pool; SQLite has a single writer, so 1 avoids lock waits
+    pub database_max_connections: PoolSize,
+    /// Size of the read-only pool used by feed requests and the feed cache
+    pub database_read_connections: PoolSize,
+    pub database_busy_timeout: TaskInterval,
     pub certificate_bundles: CertificateBundles,
+    pub http_proxies: ProxyRules,
+    pub http_ip_family: IpFamily,
+    pub http_host_overrides: HostOverrides,
     pub user_agent: String,
     pub cleanup_task_enable: TaskEnable,
     pub cleanup_task_interval: TaskInterval,
     pub cleanup_task_max_age: TaskInterval,
+    pub backup_task_enable: TaskEnable,
+    pub backup_task_interval: TaskInterval,
+    /// Directory for VACUUM INTO backups; unset = maintenance only
+    pub backup_dir: Option<String>,
+    pub backup_retain: RetainCount,
     pub timeline_feeds: Option<TimelineFeeds>,
+    /// Entries skipped because TIMELINE_FEEDS_LENIENT is set and they are invalid
+    pub timeline_feed_errors: Vec<String>,
     pub timeline_consumer_enable: TaskEnable,
     pub poll_interval: TaskInterval,
+    pub handle_refresh_interval: TaskInterval,
+    /// Store the text, author and embed type of indexed posts, e.g. for search
+    pub post_metadata_enable: TaskEnable,
+    /// Skip timeline posts whose CID is not a valid record CID
+    pub post_cid_validation: TaskEnable,
+    pub vmc_task_enable: TaskEnable,
+    pub vmc_task_interval: TaskInterval,
+    pub vmc_cache_max_age: TaskInterval,
+    pub plc_hostname: String,
+    pub admin_token: Option<String>,
+    pub feed_cache_size: CacheSize,
+    pub feed_cache_ttl: TaskInterval,
+    pub rate_limit_per_minute: RequestsPerMinute,
+    pub rate_limit_enable: TaskEnable,
+    pub rate_limit_forwarded_header: Option<String>,
+    /// PEM certificate chain and private key; when both are set the server speaks HTTPS
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    pub serve_log: ServeLogTarget,
+    /// Serve feeds from a read-only database without running any background tasks
+    pub read_replica: TaskEnable,
 }
 
 impl Config {
     pub fn new() -> Result<Self> {
-        let http_port: HttpPort = default_env("HTTP_PORT", "4050").try_into()?;
-        let external_base = require_env("EXTERNAL_BASE")?;
+        let env = ConfigSource::load()?;
+        let http_bind_addr: BindAddr = env.with_default("HTTP_BIND_ADDR", "0.0.0.0")?.try_into()?;
+        let http_port: HttpPort = env.with_default("HTTP_PORT", "4050")?.try_into()?;
+        let http_socket = env.optional("HTTP_SOCKET")?;
+        let http_socket = if http_socket.is_empty() {
+            None
+        } else {
+            Some(http_socket)
+        };
+        let external_base = env.require("EXTERNAL_BASE")?;
+        let feed_service_endpoint = env.optional("DID_FEED_SERVICE_ENDPOINT")?;
+        let feed_service_endpoint = if feed_service_endpoint.is_empty() {
+            default_feed_service_endpoint(&external_base)
+        } else {
+            feed_service_endpoint
+        };
+        let service_did = env.optional("SERVICE_DID")?;
+        let service_did = if service_did.is_empty() {
+            did_web(&external_base)
+        } else if service_did.starts_with("did:web:") || service_did.starts_with("did:plc:") {
+            service_did
+        } else {
+            return Err(anyhow!("SERVICE_DID must be a did:web or did:plc: {}", service_did));
+        };
+        let service = ServiceIdentity {
+            service_did,
+            feed_service_id: env.with_default("DID_FEED_SERVICE_ID", "#bsky_fg")?,
+            feed_service_endpoint,
+            services: parse_did_services(&env.optional("DID_SERVICES")?)?,
+            also_known_as: env.optional("DID_ALSO_KNOWN_AS")?
+                .split(';')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect(),
+            privacy_policy_url: Some(env.optional("PRIVACY_POLICY_URL")?).filter(|url| !url.is_empty()),
+            terms_of_service_url: Some(env.optional("TERMS_OF_SERVICE_URL")?).filter(|url| !url.is_empty()),
+            external_base,
+        };
+        if !service.feed_service_id.starts_with('#') {
+            return Err(anyhow!("DID_FEED_SERVICE_ID must start with #"));
+        }
 
-        let database_url = default_env("DATABASE_URL", "sqlite://development.db");
+        let database_url = env.with_default("DATABASE_URL", "sqlite://development.db")?;
+        let database_max_connections: PoolSize =
+            env.with_default("DATABASE_MAX_CONNECTIONS", "1")?.try_into()?;
+        let database_read_connections: PoolSize =
+            env.with_default("DATABASE_READ_CONNECTIONS", "8")?.try_into()?;
+        let database_busy_timeout: TaskInterval =
+            env.with_default("DATABASE_BUSY_TIMEOUT", "5s")?.try_into()?;
 
         let certificate_bundles: CertificateBundles =
-            optional_env("CERTIFICATE_BUNDLES").try_into()?;
+            env.optional("CERTIFICATE_BUNDLES")?.try_into()?;
+        let http_proxies: ProxyRules = env.optional("HTTP_PROXIES")?.try_into()?;
+        let http_ip_family: IpFamily = env.with_default("HTTP_IP_FAMILY", "any")?.try_into()?;
+        let http_host_overrides: HostOverrides =
+            env.optional("HTTP_HOST_OVERRIDES")?.try_into()?;
 
-        let user_agent = require_env("USER_AGENT")?;
+        let user_agent = env.require("USER_AGENT")?;
 
         let cleanup_task_enable: TaskEnable =
-            default_env("CLEANUP_TASK_ENABLE", "true").try_into()?;
+            env.with_default("CLEANUP_TASK_ENABLE", "true")?.try_into()?;
 
         let cleanup_task_interval: TaskInterval =
-            default_env("CLEANUP_TASK_INTERVAL", "1h").try_into()?;
+            env.with_default("CLEANUP_TASK_INTERVAL", "1h")?.try_into()?;
 
         let cleanup_task_max_age: TaskInterval =
-            default_env("CLEANUP_TASK_MAX_AGE", "48h").try_into()?;
+            env.with_default("CLEANUP_TASK_MAX_AGE", "48h")?.try_into()?;
+
+        let backup_task_enable: TaskEnable =
+            env.with_default("BACKUP_TASK_ENABLE", "false")?.try_into()?;
+        let backup_task_interval: TaskInterval =
+            env.with_default("BACKUP_TASK_INTERVAL", "24h")?.try_into()?;
+        let backup_dir = env.optional("BACKUP_DIR")?;
+        let backup_dir = if backup_dir.is_empty() {
+            None
+        } else {
+            Some(backup_dir)
+        };
+        let backup_retain: RetainCount = env.with_default("BACKUP_RETAIN", "7")?.try_into()?;
 
         // Timeline Filter configuration
-        let timeline_feeds_path = optional_env("TIMELINE_FEEDS");
-        let timeline_feeds: Option<TimelineFeeds> = if timeline_feeds_path.is_empty() {
-            None
+        let timeline_feeds_path = env.optional("TIMELINE_FEEDS")?;
+        let timeline_feeds_lenient: TaskEnable =
+            env.with_default("TIMELINE_FEEDS_LENIENT", "false")?.try_into()?;
+        let cleanup_max_age = Some(*cleanup_task_max_age.as_ref());
+        let (timeline_feeds, timeline_feed_errors) = if timeline_feeds_path.is_empty() {
+            (None, vec![])
+        } else if *timeline_feeds_lenient.as_ref() {
+            let (feeds, errors) = TimelineFeeds::load_lenient(&timeline_feeds_path, cleanup_max_age)?;
+            (Some(feeds), errors)
         } else {
             // Pass cleanup_max_age for validation
-            Some(TimelineFeeds::load_from_path(&timeline_feeds_path, Some(*cleanup_task_max_age.as_ref()))?)
+            (Some(TimelineFeeds::load_from_path(&timeline_feeds_path, cleanup_max_age)?), vec![])
         };
 
         let timeline_consumer_enable: TaskEnable =
-            default_env("TIMELINE_CONSUMER_ENABLE", "true").try_into()?;
+            env.with_default("TIMELINE_CONSUMER_ENABLE", "true")?.try_into()?;
 
         let poll_interval: TaskInterval =
-            default_env("POLL_INTERVAL", "30s").try_into()?;
+            env.with_default("POLL_INTERVAL", "30s")?.try_into()?;
+
+        let handle_refresh_interval: TaskInterval =
+            env.with_default("HANDLE_REFRESH_INTERVAL", "6h")?.try_into()?;
+
+        let post_metadata_enable: TaskEnable =
+            env.with_default("POST_METADATA_ENABLE", "false")?.try_into()?;
+
+        let post_cid_validation: TaskEnable =
+            env.with_default("POST_CID_VALIDATION", "false")?.try_into()?;
+
+        let vmc_task_enable: TaskEnable = env.with_default("VMC_TASK_ENABLE", "true")?.try_into()?;
+
+        let vmc_task_interval: TaskInterval =
+            env.with_default("VMC_TASK_INTERVAL", "1h")?.try_into()?;
+
+        let vmc_cache_max_age: TaskInterval =
+            env.with_default("VMC_CACHE_MAX_AGE", "24h")?.try_into()?;
+
+        let plc_hostname = env.with_default("PLC_HOSTNAME", "plc.directory")?;
+
+        let admin_token = env.optional("ADMIN_TOKEN")?;
+        let admin_token = if admin_token.is_empty() {
+            None
+        } else {
+            Some(admin_token)
+        };
+
+        let feed_cache_size: CacheSize = env.with_default("FEED_CACHE_SIZE", "500")?.try_into()?;
+        let feed_cache_ttl: TaskInterval = env.with_default("FEED_CACHE_TTL", "5m")?.try_into()?;
+
+        let rate_limit_enable: TaskEnable =
+            env.with_default("RATE_LIMIT_ENABLE", "true")?.try_into()?;
+        let rate_limit_per_minute: RequestsPerMinute =
+            env.with_default("RATE_LIMIT_PER_MINUTE", "300")?.try_into()?;
+        let rate_limit_forwarded_header = env.optional("RATE_LIMIT_FORWARDED_HEADER")?;
+        let rate_limit_forwarded_header = if rate_limit_forwarded_header.is_empty() {
+            None
+        } else {
+            Some(rate_limit_forwarded_header)
+        };
+
+        let tls_cert_path = env.optional("TLS_CERT_PATH")?;
+        let tls_key_path = env.optional("TLS_KEY_PATH")?;
+        let (tls_cert_path, tls_key_path) = match (tls_cert_path.is_empty(), tls_key_path.is_empty()) {
+            (true, true) => (None, None),
+            (false, false) => (Some(tls_cert_path), Some(tls_key_path)),
+            _ => return Err(anyhow!("TLS_CERT_PATH and TLS_KEY_PATH must be set together")),
+        };
+        if http_socket.is_some() && tls_cert_path.is_some() {
+            return Err(anyhow!("TLS_CERT_PATH cannot be used with HTTP_SOCKET"));
+        }
+
+        let serve_log: ServeLogTarget = env.optional("SERVE_LOG")?.into();
+
+        let read_replica: TaskEnable = env.with_default("READ_REPLICA", "false")?.try_into()?;
+        if *read_replica.as_ref() && serve_log == ServeLogTarget::Database {
+            return Err(anyhow!("SERVE_LOG=database cannot be used with READ_REPLICA"));
+        }
 
         Ok(Self {
             version: version()?,
+            http_bind_addr,
             http_port,
-            external_base,
+            http_socket,
+            service,
             database_url,
+            database_max_connections,
+            database_read_connections,
+            database_busy_timeout,
             certificate_bundles,
+            http_proxies,
+            http_ip_family,
+            http_host_overrides,
             user_agent,
             cleanup_task_enable,
             cleanup_task_interval,
             cleanup_task_max_age,
+            backup_task_enable,
+            backup_task_interval,
+            backup_dir,
+            backup_retain,
             timeline_feeds,
+            timeline_feed_errors,
             timeline_consumer_enable,
             poll_interval,
+            handle_refresh_interval,
+            post_metadata_enable,
+            post_cid_validation,
+            vmc_task_enable,
+            vmc_task_interval,
+            vmc_cache_max_age,
+            plc_hostname,
+            admin_token,
+            feed_cache_size,
+            feed_cache_ttl,
+            rate_limit_per_minute,
+            rate_limit_enable,
+            rate_limit_forwarded_header,
+            tls_cert_path,
+            tls_key_path,
+            serve_log,
+            read_replica,
         })
     }
 }
 
-fn require_env(name: &str) -> Result<String> {
-    std::env::var(name)
-        .map_err(|err| anyhow::Error::new(err).context(anyhow!("{} must be set", name)))
+/// Settings from CONFIG_FILE, keyed by environment variable name
+///
+/// Environment variables take precedence over the file.
+#[derive(Default)]
+struct ConfigSource(HashMap<String, String>);
+
+impl ConfigSource {
+    fn load() -> Result<Self> {
+        match std::env::var("CONFIG_FILE") {
+            Ok(path) if !path.is_empty() => Self::from_file(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Read a TOML file, or JSON when the path ends in .json
+    fn from_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path))?;
+        let values: serde_json::Map<String, serde_json::Value> = if path.ends_with(".json") {
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse config file: {}", path))?
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("Failed to parse config file: {}", path))?
+        };
+        Self::from_values(values)
+    }
+
+    /// Keys are matched case-insensitively, so `http_port` sets HTTP_PORT
+    fn from_values(values: serde_json::Map<String, serde_json::Value>) -> Result<Self> {
+        let mut settings = HashMap::new();
+        for (key, value) in values {
+            let value = match value {
+                serde_json::Value::String(value) => value,
+                serde_json::Value::Number(value) => value.to_string(),
+                serde_json::Value::Bool(value) => value.to_string(),
+                _ => anyhow::bail!("Config file setting {} must be a string, number or boolean", key),
+            };
+            settings.insert(key.to_uppercase(), value);
+        }
+        Ok(Self(settings))
+    }
+
+    /// A setting, or the contents of the file named by its `_FILE` variant
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        let file_name = format!("{}_FILE", name);
+        if let Ok(value) = std::env::var(name) {
+            return Ok(Some(value));
+        }
+        if let Ok(path) = std::env::var(&file_name) {
+            return read_secret_file(&path).map(Some);
+        }
+        if let Some(value) = self.0.get(name) {
+            return Ok(Some(value.clone()));
+        }
+        match self.0.get(&file_name) {
+            Some(path) => read_secret_file(path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn require(&self, name: &str) -> Result<String> {
+        self.get(name)?.ok_or_else(|| anyhow!("{} must be set", name))
+    }
+
+    fn optional(&self, name: &str) -> Result<String> {
+        Ok(self.get(name)?.unwrap_or_default())
+    }
+
+    fn with_default(&self, name: &str, default_value: &str) -> Result<String> {
+        Ok(self.get(name)?.unwrap_or(default_value.to_string()))
+    }
+}
+
+/// Read a secret mounted as a file, e.g. by Docker or Kubernetes
+pub(crate) fn read_secret_file(path: &str) -> Result<String> {
+    let value = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read secret file: {}", path))?;
+    Ok(value.trim().to_string())
 }
 
-fn optional_env(name: &str) -> String {
-    std::env::var(name).unwrap_or("".to_string())
+/// Feed service endpoint of a public URL, https unless a scheme is given
+fn default_feed_service_endpoint(external_base: &str) -> String {
+    if external_base.starts_with("http://") || external_base.starts_with("https://") {
+        external_base.to_string()
+    } else {
+        format!("https://{}", external_base)
+    }
+}
+
+/// did:web identity of a public URL
+fn did_web(external_base: &str) -> String {
+    let hostname = external_base
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    format!("did:web:{}", hostname)
 }
 
-fn default_env(name: &str, default_value: &str) -> String {
-    std::env::var(name).unwrap_or(default_value.to_string())
+/// Parse DID_SERVICES: `id,type,endpoint` entries separated by `;`
+fn parse_did_services(value: &str) -> Result<Vec<DidService>> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let parts: Vec<&str> = entry.split(',').map(str::trim).collect();
+            let [id, service_type, endpoint] = parts.as_slice() else {
+                return Err(anyhow!("DID_SERVICES entry must be id,type,endpoint: {}", entry));
+            };
+            if !id.starts_with('#') {
+                return Err(anyhow!("DID_SERVICES id must start with #: {}", id));
+            }
+            if !endpoint.starts_with("https://") && !endpoint.starts_with("http://") {
+                return Err(anyhow!("DID_SERVICES endpoint must be an http(s) URL: {}", endpoint));
+            }
+            Ok(DidService {
+                id: id.to_string(),
+                service_type: service_type.to_string(),
+                endpoint: endpoint.to_string(),
+            })
+        })
+        .collect()
 }
 
 pub fn version() -> Result<String> {
@@ -125,6 +522,137 @@ impl AsRef<u16> for HttpPort {
     }
 }
 
+impl TryFrom<String> for BindAddr {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse::<IpAddr>().map(Self).map_err(|err| {
+            anyhow::Error::new(err).context(anyhow!("parsing HTTP_BIND_ADDR into an IP address failed"))
+        })
+    }
+}
+
+impl AsRef<IpAddr> for BindAddr {
+    fn as_ref(&self) -> &IpAddr {
+        &self.0
+    }
+}
+
+impl Config {
+    /// HTTP client builder with the CA bundles, user agent, proxies and DNS settings applied
+    pub fn http_client_builder(&self) -> Result<reqwest::ClientBuilder> {
+        let mut client_builder = reqwest::Client::builder();
+        for ca_certificate in self.certificate_bundles.as_ref() {
+            tracing::info!("Loading CA certificate: {:?}", ca_certificate);
+            let cert = std::fs::read(ca_certificate)?;
+            let cert = reqwest::Certificate::from_pem(&cert)?;
+            client_builder = client_builder.add_root_certificate(cert);
+        }
+
+        // Without rules, reqwest follows HTTPS_PROXY, ALL_PROXY and NO_PROXY
+        if !self.http_proxies.0.is_empty() {
+            let rules = self.http_proxies.clone();
+            client_builder = client_builder.proxy(reqwest::Proxy::custom(move |url| {
+                rules.proxy_for(url.host_str().unwrap_or_default()).cloned()
+            }));
+        }
+
+        // Binding the unspecified address of one family limits connections to it
+        client_builder = match self.http_ip_family {
+            IpFamily::Any => client_builder,
+            IpFamily::V4 => client_builder.local_address(IpAddr::from(Ipv4Addr::UNSPECIFIED)),
+            IpFamily::V6 => client_builder.local_address(IpAddr::from(Ipv6Addr::UNSPECIFIED)),
+        };
+
+        // The port comes from the request URL, so 0 here is ignored
+        for (host, ip) in &self.http_host_overrides.0 {
+            client_builder = client_builder.resolve(host, SocketAddr::new(*ip, 0));
+        }
+
+        Ok(client_builder.user_agent(self.user_agent.clone()))
+    }
+}
+
+impl TryFrom<String> for IpFamily {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "" | "any" => Ok(Self::Any),
+            "ipv4" => Ok(Self::V4),
+            "ipv6" => Ok(Self::V6),
+            _ => Err(anyhow!("HTTP_IP_FAMILY must be any, ipv4 or ipv6: {}", value)),
+        }
+    }
+}
+
+impl From<String> for ServeLogTarget {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "" => Self::Disabled,
+            "database" => Self::Database,
+            _ => Self::File(value),
+        }
+    }
+}
+
+impl TryFrom<String> for HostOverrides {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut overrides = Vec::new();
+        for entry in value.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (host, ip) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow!("HTTP_HOST_OVERRIDES entry must be host=ip: {}", entry))?;
+            let ip = ip
+                .trim()
+                .parse::<IpAddr>()
+                .with_context(|| format!("invalid IP address in HTTP_HOST_OVERRIDES: {}", ip))?;
+            overrides.push((host.trim().to_ascii_lowercase(), ip));
+        }
+        Ok(Self(overrides))
+    }
+}
+
+impl ProxyRules {
+    /// The proxy for a destination host, or None to connect directly
+    pub fn proxy_for(&self, host: &str) -> Option<&reqwest::Url> {
+        self.0
+            .iter()
+            .find(|(pattern, _)| {
+                pattern == "*"
+                    || host == pattern
+                    || host
+                        .strip_suffix(pattern.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+            .and_then(|(_, proxy)| proxy.as_ref())
+    }
+}
+
+impl TryFrom<String> for ProxyRules {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut rules = Vec::new();
+        for rule in value.split(';').map(str::trim).filter(|rule| !rule.is_empty()) {
+            let (host, proxy) = rule
+                .split_once('=')
+                .ok_or_else(|| anyhow!("HTTP_PROXIES entry must be host=proxy: {}", rule))?;
+            let proxy = match proxy.trim() {
+                "direct" => None,
+                proxy => {
+                    let url = reqwest::Url::parse(proxy)
+                        .with_context(|| format!("invalid proxy URL in HTTP_PROXIES: {}", proxy))?;
+                    if !["http", "https", "socks5", "socks5h"].contains(&url.scheme()) {
+                        return Err(anyhow!("unsupported proxy scheme in HTTP_PROXIES: {}", proxy));
+                    }
+                    Some(url)
+                }
+            };
+            rules.push((host.trim().to_ascii_lowercase(), proxy));
+        }
+        Ok(Self(rules))
+    }
+}
+
 impl TryFrom<String> for CertificateBundles {
     type Error = anyhow::Error;
     fn try_from(value: String) -> Result<Self, Self::Error> {
@@ -149,6 +677,79 @@ impl AsRef<Vec<String>> for CertificateBundles {
     }
 }
 
+impl TryFrom<String> for PoolSize {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.parse::<u32>().map_err(|err| {
+            anyhow::Error::new(err).context(anyhow!("parsing pool size into u32 failed"))
+        })?;
+        if value == 0 {
+            return Err(anyhow!("pool size must be greater than 0"));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl AsRef<u32> for PoolSize {
+    fn as_ref(&self) -> &u32 {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for RetainCount {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.parse::<usize>().map_err(|err| {
+            anyhow::Error::new(err).context(anyhow!("parsing retain count into usize failed"))
+        })?;
+        if value == 0 {
+            return Err(anyhow!("retain count must be greater than 0"));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl AsRef<usize> for RetainCount {
+    fn as_ref(&self) -> &usize {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for CacheSize {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.parse::<usize>().map_err(|err| {
+            anyhow::Error::new(err).context(anyhow!("parsing cache size into usize failed"))
+        })?;
+        Ok(Self(value))
+    }
+}
+
+impl AsRef<usize> for CacheSize {
+    fn as_ref(&self) -> &usize {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for RequestsPerMinute {
+    type Error = anyhow::Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.parse::<u32>().map_err(|err| {
+            anyhow::Error::new(err).context(anyhow!("parsing requests per minute into u32 failed"))
+        })?;
+        if value == 0 {
+            return Err(anyhow!("requests per minute must be greater than 0"));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl AsRef<u32> for RequestsPerMinute {
+    fn as_ref(&self) -> &u32 {
+        &self.0
+    }
+}
+
 impl AsRef<bool> for TaskEnable {
     fn as_ref(&self) -> &bool {
         &self.0
@@ -180,3 +781,80 @@ impl TryFrom<String> for TaskInterval {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_overrides() {
+        let overrides: HostOverrides = "pds.example.com=10.0.0.5; staging.example.com=::1"
+            .to_string()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            overrides.0,
+            vec![
+                ("pds.example.com".to_string(), IpAddr::from([10, 0, 0, 5])),
+                ("staging.example.com".to_string(), IpAddr::from(Ipv6Addr::LOCALHOST)),
+            ]
+        );
+        assert!(HostOverrides::try_from("pds.example.com=staging".to_string()).is_err());
+        assert_eq!(IpFamily::try_from("ipv6".to_string()).unwrap(), IpFamily::V6);
+        assert!(IpFamily::try_from("v6".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_proxy_rules() {
+        let rules: ProxyRules =
+            "bsky.social=direct; plc.directory=http://proxy:3128;*=socks5h://proxy:1080"
+                .to_string()
+                .try_into()
+                .unwrap();
+        assert!(rules.proxy_for("bsky.social").is_none());
+        assert!(rules.proxy_for("morel.us-east.host.bsky.network").is_some());
+        assert_eq!(rules.proxy_for("plc.directory").unwrap().as_str(), "http://proxy:3128/");
+        assert_eq!(rules.proxy_for("pds.bsky.social").map(|url| url.scheme()), None);
+        assert_eq!(rules.proxy_for("notbsky.social").unwrap().scheme(), "socks5h");
+
+        assert!(ProxyRules::try_from(String::new()).unwrap().proxy_for("bsky.social").is_none());
+        assert!(ProxyRules::try_from("bsky.social".to_string()).is_err());
+        assert!(ProxyRules::try_from("*=ftp://proxy".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_config_source_from_toml() {
+        let values = toml::from_str(
+            "external_base = \"feeds.example.com\"\nHTTP_PORT = 8080\npost_metadata_enable = true\n",
+        )
+        .unwrap();
+        let source = ConfigSource::from_values(values).unwrap();
+        assert_eq!(source.0["EXTERNAL_BASE"], "feeds.example.com");
+        assert_eq!(source.0["HTTP_PORT"], "8080");
+        assert_eq!(source.0["POST_METADATA_ENABLE"], "true");
+
+        let nested = toml::from_str("[database]\nurl = \"sqlite://x.db\"\n").unwrap();
+        assert!(ConfigSource::from_values(nested).is_err());
+    }
+
+    #[test]
+    fn test_did_web() {
+        assert_eq!(did_web("https://feeds.example.com/"), "did:web:feeds.example.com");
+        assert_eq!(did_web("feeds.example.com"), "did:web:feeds.example.com");
+    }
+
+    #[test]
+    fn test_parse_did_services() {
+        assert_eq!(parse_did_services("").unwrap(), vec![]);
+        assert_eq!(
+            parse_did_services("#atproto_labeler,AtprotoLabeler,https://labeler.example.com; ").unwrap(),
+            vec![DidService {
+                id: "#atproto_labeler".to_string(),
+                service_type: "AtprotoLabeler".to_string(),
+                endpoint: "https://labeler.example.com".to_string(),
+            }]
+        );
+        assert!(parse_did_services("#atproto_labeler,AtprotoLabeler").is_err());
+        assert!(parse_did_services("atproto_labeler,AtprotoLabeler,https://labeler.example.com").is_err());
+        assert!(parse_did_services("#atproto_labeler,AtprotoLabeler,labeler.example.com").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Inter-service authentication
+//!
+//! The AppView signs getFeedSkeleton requests with a JWT issued by the viewer's
+//! DID. The signature is checked against the DID's atproto signing key from the
+//! verification method cache.
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{Duration, Utc};
+use ecdsa::signature::Verifier;
+use serde::Deserialize;
+
+use crate::vmc::VerificationMethodCache;
+
+/// Multicodec prefix of a compressed secp256k1 public key
+const MULTICODEC_SECP256K1: [u8; 2] = [0xe7, 0x01];
+
+/// Multicodec prefix of a compressed P-256 public key
+const MULTICODEC_P256: [u8; 2] = [0x80, 0x24];
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceClaims {
+    iss: String,
+    aud: String,
+    exp: i64,
+    /// Lexicon method the token is bound to, if any
+    #[serde(default)]
+    lxm: Option<String>,
+}
+
+/// A decoded but not yet verified JWT
+struct ServiceJwt<'a> {
+    alg: String,
+    claims: ServiceClaims,
+    signing_input: &'a str,
+    signature: Vec<u8>,
+}
+
+/// A well-formed JWT issued by another DID than the one expected
+#[derive(Debug, thiserror::Error)]
+#[error("JWT issued by {0}")]
+pub struct WrongIssuer(pub String);
+
+/// Keys resolved more recently than this are not re-resolved after a bad signature
+const MIN_REFRESH_AGE: Duration = Duration::seconds(60);
+
+/// Verify a service JWT issued by `issuer` for `audience` and `method`
+///
+/// Tokens from any other issuer are rejected before a key is looked up, so
+/// clients can't make this service resolve DIDs of their choosing. A signature
+/// that fails against the cached key is retried once with a freshly resolved
+/// key, so rotated keys are picked up without waiting for the cache, unless the
+/// cached key is less than a minute old.
+pub async fn verify_service_jwt(
+    token: &str,
+    issuer: &str,
+    audience: &str,
+    method: &str,
+    cache: &VerificationMethodCache,
+) -> Result<()> {
+    let jwt = decode_jwt(token)?;
+    check_claims(&jwt.claims, audience, method, Utc::now().timestamp())?;
+    if issuer_did(&jwt.claims.iss) != issuer {
+        return Err(WrongIssuer(jwt.claims.iss).into());
+    }
+
+    let (multikey, resolved_at) = cache.get_entry(issuer).await?;
+    if verify_signature(&jwt, &multikey).is_err() {
+        if Utc::now() - resolved_at < MIN_REFRESH_AGE {
+            anyhow::bail!("JWT signature is invalid");
+        }
+        let multikey = cache.refresh(issuer).await?;
+        verify_signature(&jwt, &multikey).context("JWT signature is invalid")?;
+    }
+
+    Ok(())
+}
+
+/// The issuer of a well-formed, unexpired JWT for `audience` and `method`
+///
+/// The signature is not checked, so the DID is only what the client claims.
+/// Nothing is resolved, which keeps this cheap enough for every request.
+pub fn claimed_issuer(token: &str, audience: &str, method: &str) -> Option<String> {
+    let jwt = decode_jwt(token).ok()?;
+    check_claims(&jwt.claims, audience, method, Utc::now().timestamp()).ok()?;
+    Some(issuer_did(&jwt.claims.iss).to_string())
+}
+
+fn decode_jwt(token: &str) -> Result<ServiceJwt<'_>> {
+    let (signing_input, signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| anyhow!("malformed JWT"))?;
+    let (header, claims) = signing_input
+        .split_once('.')
+        .ok_or_else(|| anyhow!("malformed JWT"))?;
+
+    let header: JwtHeader = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)
+        .context("invalid JWT header")?;
+    let claims: ServiceClaims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims)?)
+        .context("invalid JWT claims")?;
+
+    Ok(ServiceJwt {
+        alg: header.alg,
+        claims,
+        signing_input,
+        signature: URL_SAFE_NO_PAD.decode(signature)?,
+    })
+}
+
+fn check_claims(claims: &ServiceClaims, audience: &str, method: &str, now: i64) -> Result<()> {
+    // The AppView may address a service endpoint, e.g. did:web:host#bsky_fg
+    let aud = claims.aud.split('#').next().unwrap_or_default();
+    if aud != audience {
+        anyhow::bail!("JWT audience mismatch: {}", claims.aud);
+    }
+    if claims.exp <= now {
+        anyhow::bail!("JWT expired");
+    }
+    if claims.lxm.as_deref().is_some_and(|lxm| lxm != method) {
+        anyhow::bail!("JWT is bound to another method");
+    }
+    if !claims.iss.starts_with("did:") {
+        anyhow::bail!("JWT issuer is not a DID: {}", claims.iss);
+    }
+    Ok(())
+}
+
+/// The issuer's DID, without a service fragment
+fn issuer_did(iss: &str) -> &str {
+    iss.split('#').next().unwrap_or(iss)
+}
+
+fn verify_signature(jwt: &ServiceJwt<'_>, multikey: &str) -> Result<()> {
+    let (_, key) = multibase::decode(multikey).context("invalid multikey")?;
+    let message = jwt.signing_input.as_bytes();
+
+    match (jwt.alg.as_str(), key.split_at_checked(2)) {
+        ("ES256K", Some((prefix, key))) if prefix == MULTICODEC_SECP256K1 => {
+            let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(key)?;
+            let signature = k256::ecdsa::Signature::from_slice(&jwt.signature)?;
+            let signature = signature.normalize_s().unwrap_or(signature);
+            key.verify(message, &signature)?;
+        }
+        ("ES256", Some((prefix, key))) if prefix == MULTICODEC_P256 => {
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key)?;
+            let signature = p256::ecdsa::Signature::from_slice(&jwt.signature)?;
+            key.verify(message, &signature)?;
+        }
+        (alg, _) => anyhow::bail!("JWT algorithm {} does not match the signing key", alg),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ecdsa::signature::Signer;
+    use serde_json::json;
+
+    fn sign(key: &k256::ecdsa::SigningKey, claims: serde_json::Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({"alg": "ES256K", "typ": "JWT"}).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let signing_input = format!("{}.{}", header, claims);
+        let signature: k256::ecdsa::Signature = key.sign(signing_input.as_bytes());
+        format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature.to_bytes()))
+    }
+
+    #[test]
+    fn test_verify_service_jwt() {
+        let key = k256::ecdsa::SigningKey::random(&mut rand::thread_rng());
+        let public = key.verifying_key().to_encoded_point(true);
+        let multikey = multibase::encode(
+            multibase::Base::Base58Btc,
+            [MULTICODEC_SECP256K1.as_slice(), public.as_bytes()].concat(),
+        );
+
+        let token = sign(
+            &key,
+            json!({
+                "iss": "did:plc:owner",
+                "aud": "did:web:feeds.example.com#bsky_fg",
+                "exp": 2000,
+                "lxm": "app.bsky.feed.getFeedSkeleton",
+            }),
+        );
+        let jwt = decode_jwt(&token).unwrap();
+        let method = "app.bsky.feed.getFeedSkeleton";
+        assert!(check_claims(&jwt.claims, "did:web:feeds.example.com", method, 1000).is_ok());
+        assert!(check_claims(&jwt.claims, "did:web:other.example.com", method, 1000).is_err());
+        assert!(check_claims(&jwt.claims, "did:web:feeds.example.com", method, 2000).is_err());
+        assert!(check_claims(&jwt.claims, "did:web:feeds.example.com", "app.bsky.feed.getTimeline", 1000).is_err());
+        assert!(verify_signature(&jwt, &multikey).is_ok());
+
+        // A token signed by someone else fails against the owner's key
+        let other = k256::ecdsa::SigningKey::random(&mut rand::thread_rng());
+        let forged = sign(&other, json!({"iss": "did:plc:owner", "aud": "x", "exp": 2000}));
+        assert!(verify_signature(&decode_jwt(&forged).unwrap(), &multikey).is_err());
+    }
+
+    #[test]
+    fn test_claimed_issuer() {
+        let key = k256::ecdsa::SigningKey::random(&mut rand::thread_rng());
+        let method = "app.bsky.feed.getFeedSkeleton";
+        let claims = |aud: &str, exp: i64| {
+            json!({"iss": "did:plc:viewer#bsky_appview", "aud": aud, "exp": exp, "lxm": method})
+        };
+        let exp = Utc::now().timestamp() + 60;
+
+        let token = sign(&key, claims("did:web:feeds.example.com", exp));
+        assert_eq!(
+            claimed_issuer(&token, "did:web:feeds.example.com", method).as_deref(),
+            Some("did:plc:viewer")
+        );
+        let expired = sign(&key, claims("did:web:feeds.example.com", exp - 120));
+        assert!(claimed_issuer(&expired, "did:web:feeds.example.com", method).is_none());
+        let other_audience = sign(&key, claims("did:web:other.example.com", exp));
+        assert!(claimed_issuer(&other_audience, "did:web:feeds.example.com", method).is_none());
+        assert!(claimed_issuer("not-a-jwt", "did:web:feeds.example.com", method).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_verify_service_jwt_uses_cached_key() {
+        let key = k256::ecdsa::SigningKey::random(&mut rand::thread_rng());
+        let public = key.verifying_key().to_encoded_point(true);
+        let multikey = multibase::encode(
+            multibase::Base::Base58Btc,
+            [MULTICODEC_SECP256K1.as_slice(), public.as_bytes()].concat(),
+        );
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        crate::feed_storage::verification_method_upsert(&pool, "did:plc:owner", &multikey)
+            .await
+            .unwrap();
+        // Any DID resolution would fail, so errors below come from the checks
+        let cache = VerificationMethodCache::new(
+            pool.clone(),
+            reqwest::Client::new(),
+            "plc.invalid",
+            chrono::Duration::hours(1),
+        );
+        let method = "app.bsky.feed.getFeedSkeleton";
+        let claims = |iss: &str| {
+            json!({
+                "iss": iss,
+                "aud": "did:web:feeds.example.com",
+                "exp": Utc::now().timestamp() + 60,
+                "lxm": method,
+            })
+        };
+        let verify = |token: String| {
+            let cache = cache.clone();
+            async move {
+                verify_service_jwt(&token, "did:plc:owner", "did:web:feeds.example.com", method, &cache)
+                    .await
+            }
+        };
+
+        assert!(verify(sign(&key, claims("did:plc:owner"))).await.is_ok());
+
+        // A bad signature on a freshly cached key is not re-resolved
+        let other = k256::ecdsa::SigningKey::random(&mut rand::thread_rng());
+        let err = verify(sign(&other, claims("did:plc:owner"))).await.unwrap_err();
+        assert_eq!(err.to_string(), "JWT signature is invalid");
+
+        // Other issuers are rejected without looking up or storing their key
+        let err = verify(sign(&other, claims("did:web:attacker.example.com"))).await.unwrap_err();
+        assert!(err.downcast_ref::<WrongIssuer>().is_some());
+        let cached: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM verification_method_cache")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(cached, 1);
+    }
+}
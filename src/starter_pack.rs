@@ -0,0 +1,105 @@
+//! Starter pack membership
+//!
+//! A starter pack points at an app.bsky.graph.list holding its members. Both
+//! are read through the feed owner's PDS, which proxies app.bsky requests to
+//! the AppView like it does for getTimeline.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Members requested per getList page
+const LIST_PAGE_SIZE: u32 = 100;
+
+/// Most getList pages read per starter pack; starter packs hold at most 150 members
+const LIST_MAX_PAGES: u32 = 10;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetStarterPackResponse {
+    starter_pack: StarterPackView,
+}
+
+#[derive(Deserialize)]
+struct StarterPackView {
+    list: Option<ListRef>,
+}
+
+#[derive(Deserialize)]
+struct ListRef {
+    uri: String,
+}
+
+#[derive(Deserialize)]
+struct GetListResponse {
+    cursor: Option<String>,
+    items: Vec<ListItem>,
+}
+
+#[derive(Deserialize)]
+struct ListItem {
+    subject: ListSubject,
+}
+
+#[derive(Deserialize)]
+struct ListSubject {
+    did: String,
+}
+
+/// DIDs of the members of a starter pack
+pub async fn starter_pack_members(
+    http_client: &reqwest::Client,
+    pds_url: &str,
+    access_token: &str,
+    starter_pack_uri: &str,
+) -> Result<Vec<String>> {
+    let pds_url = pds_url.trim_end_matches('/');
+
+    let response: GetStarterPackResponse = http_client
+        .get(format!("{}/xrpc/app.bsky.graph.getStarterPack", pds_url))
+        .bearer_auth(access_token)
+        .query(&[("starterPack", starter_pack_uri)])
+        .send()
+        .await
+        .context("Failed to send getStarterPack request")?
+        .error_for_status()
+        .context("getStarterPack failed")?
+        .json()
+        .await
+        .context("Failed to parse getStarterPack response")?;
+
+    let Some(list) = response.starter_pack.list else {
+        return Ok(Vec::new());
+    };
+
+    let mut members = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..LIST_MAX_PAGES {
+        let mut request = http_client
+            .get(format!("{}/xrpc/app.bsky.graph.getList", pds_url))
+            .bearer_auth(access_token)
+            .query(&[("list", list.uri.as_str())])
+            .query(&[("limit", LIST_PAGE_SIZE)]);
+        if let Some(cursor) = &cursor {
+            request = request.query(&[("cursor", cursor)]);
+        }
+
+        let page: GetListResponse = request
+            .send()
+            .await
+            .context("Failed to send getList request")?
+            .error_for_status()
+            .context("getList failed")?
+            .json()
+            .await
+            .context("Failed to parse getList response")?;
+
+        let empty = page.items.is_empty();
+        members.extend(page.items.into_iter().map(|item| item.subject.did));
+        cursor = page.cursor;
+        if empty || cursor.is_none() {
+            break;
+        }
+    }
+
+    Ok(members)
+}
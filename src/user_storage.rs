@@ -1,8 +1,13 @@
+use std::fmt;
+
 use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 
 use crate::feed_storage::StoragePool;
-use crate::feed_config::{FilterConfig, TimelineFeed, TimelineFeeds};
+use crate::redact::REDACTED;
+use crate::feed_config::{
+    default_backfill_limit, AggregateFeed, AuthorDiversity, FilterConfig, OAuthConfig, TimelineFeed, TimelineFeeds,
+};
 
 /// Synchronize timeline feeds configuration from YAML to database
 /// This should be called on startup to ensure DB matches config file
@@ -14,9 +19,144 @@ pub async fn sync_config_to_db(pool: &StoragePool, feeds: &TimelineFeeds) -> Res
 
     for feed in &feeds.timeline_feeds {
         sync_user_config(pool, feed).await?;
-        sync_user_filters(pool, &feed.did, &feed.filters).await?;
+        sync_user_variants(pool, feed).await?;
+        for (feed_uri, filters) in feed.feed_targets() {
+            sync_feed_filters(pool, &feed.did, feed_uri, filters).await?;
+        }
+    }
+
+    sync_aggregate_feeds(pool, &feeds.aggregate_feeds).await?;
+    sync_feed_diversity(pool, feeds).await?;
+
+    Ok(())
+}
+
+/// Replace the stored config errors with those of the current startup
+pub async fn replace_config_errors(pool: &StoragePool, errors: &[String]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM timeline_config_errors")
+        .execute(tx.as_mut())
+        .await?;
+
+    let now = Utc::now().to_rfc3339();
+    for error in errors {
+        sqlx::query("INSERT INTO timeline_config_errors (error, created_at) VALUES (?, ?)")
+            .bind(error)
+            .bind(&now)
+            .execute(tx.as_mut())
+            .await?;
+    }
+
+    tx.commit().await.context("Failed to store config errors")
+}
+
+/// Config entries skipped on the last startup
+pub async fn get_config_errors(pool: &StoragePool) -> Result<Vec<String>> {
+    sqlx::query_scalar("SELECT error FROM timeline_config_errors ORDER BY rowid")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch config errors")
+}
+
+/// Replace the stored author diversity limits with those in the config
+async fn sync_feed_diversity(pool: &StoragePool, feeds: &TimelineFeeds) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM timeline_feed_diversity")
+        .execute(tx.as_mut())
+        .await?;
+
+    let configured = feeds
+        .timeline_feeds
+        .iter()
+        .flat_map(|feed| feed.feed_diversity())
+        .chain(
+            feeds
+                .aggregate_feeds
+                .iter()
+                .map(|aggregate| (&aggregate.feed_uri, &aggregate.diversity)),
+        )
+        .filter(|(_, diversity)| diversity.is_enabled());
+
+    for (feed_uri, diversity) in configured {
+        sqlx::query(
+            r#"
+            INSERT INTO timeline_feed_diversity (feed_uri, max_consecutive_per_author, max_per_author_per_page)
+            VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(feed_uri)
+        .bind(diversity.max_consecutive_per_author.map(i64::from))
+        .bind(diversity.max_per_author_per_page.map(i64::from))
+        .execute(tx.as_mut())
+        .await
+        .with_context(|| format!("Failed to sync author diversity for {}", feed_uri))?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Sync aggregate feeds to database, removing aggregates no longer configured
+async fn sync_aggregate_feeds(pool: &StoragePool, aggregates: &[AggregateFeed]) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    let existing = sqlx::query_as::<_, (String,)>("SELECT feed_uri FROM timeline_aggregate_feeds")
+        .fetch_all(tx.as_mut())
+        .await?;
+
+    for (feed_uri,) in existing {
+        if aggregates.iter().any(|aggregate| aggregate.feed_uri == feed_uri) {
+            continue;
+        }
+        sqlx::query("DELETE FROM timeline_aggregate_sources WHERE feed_uri = ?")
+            .bind(&feed_uri)
+            .execute(tx.as_mut())
+            .await?;
+        sqlx::query("DELETE FROM timeline_aggregate_feeds WHERE feed_uri = ?")
+            .bind(&feed_uri)
+            .execute(tx.as_mut())
+            .await?;
+    }
+
+    for aggregate in aggregates {
+        sqlx::query(
+            r#"
+            INSERT INTO timeline_aggregate_feeds (feed_uri, name, description, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(feed_uri) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&aggregate.feed_uri)
+        .bind(&aggregate.name)
+        .bind(&aggregate.description)
+        .bind(&now)
+        .bind(&now)
+        .execute(tx.as_mut())
+        .await
+        .with_context(|| format!("Failed to sync aggregate feed: {}", aggregate.feed_uri))?;
+
+        sqlx::query("DELETE FROM timeline_aggregate_sources WHERE feed_uri = ?")
+            .bind(&aggregate.feed_uri)
+            .execute(tx.as_mut())
+            .await?;
+        for source in &aggregate.sources {
+            sqlx::query(
+                "INSERT OR IGNORE INTO timeline_aggregate_sources (feed_uri, source_did) VALUES (?, ?)",
+            )
+            .bind(&aggregate.feed_uri)
+            .bind(source)
+            .execute(tx.as_mut())
+            .await?;
+        }
     }
 
+    tx.commit().await?;
     Ok(())
 }
 
@@ -25,27 +165,40 @@ async fn sync_user_config(pool: &StoragePool, feed: &TimelineFeed) -> Result<()>
     let now = Utc::now().to_rfc3339();
     let poll_interval_seconds = feed
         .poll_interval_duration()
-        .map(|d| d.num_seconds() as i64)
+        .map(|d| d.num_seconds())
         .unwrap_or(30);
 
+    // Live tokens are rotated by every refresh, so compare against the tokens
+    // the config supplied last time rather than the ones in use
+    let synced_tokens = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+        "SELECT config_access_token, config_refresh_token FROM timeline_user_config WHERE did = ?",
+    )
+    .bind(&feed.did)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("Failed to load synced tokens for {}", feed.did))?;
+    let tokens_changed = synced_tokens.is_none_or(|(access, refresh)| {
+        access.as_deref() != Some(feed.oauth.access_token.as_str()) || refresh != feed.oauth.refresh_token
+    });
+
     sqlx::query(
         r#"
         INSERT INTO timeline_user_config (
             did, feed_uri, name, description,
             access_token, refresh_token, token_expires_at, pds_url,
-            poll_interval_seconds, max_posts_per_poll,
+            poll_interval_seconds, max_posts_per_poll, private,
+            config_access_token, config_refresh_token,
             created_at, updated_at
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(did) DO UPDATE SET
             feed_uri = excluded.feed_uri,
             name = excluded.name,
             description = excluded.description,
-            access_token = excluded.access_token,
-            refresh_token = excluded.refresh_token,
-            token_expires_at = excluded.token_expires_at,
             pds_url = excluded.pds_url,
             poll_interval_seconds = excluded.poll_interval_seconds,
             max_posts_per_poll = excluded.max_posts_per_poll,
+            private = excluded.private,
+            managed_by = 'config',
             updated_at = excluded.updated_at
         "#,
     )
@@ -59,12 +212,42 @@ async fn sync_user_config(pool: &StoragePool, feed: &TimelineFeed) -> Result<()>
     .bind(&feed.oauth.pds_url)
     .bind(poll_interval_seconds)
     .bind(feed.max_posts_per_poll as i64)
+    .bind(feed.private)
+    .bind(&feed.oauth.access_token)
+    .bind(&feed.oauth.refresh_token)
     .bind(&now)
     .bind(&now)
     .execute(pool)
     .await
     .with_context(|| format!("Failed to sync user config for {}", feed.did))?;
 
+    // New tokens in the config replace the live ones and resume a suspended
+    // feed; a plain restart keeps the refreshed tokens and the failure state
+    if tokens_changed {
+        sqlx::query(
+            r#"
+            UPDATE timeline_user_config
+            SET access_token = ?,
+                refresh_token = ?,
+                token_expires_at = ?,
+                config_access_token = ?,
+                config_refresh_token = ?
+            WHERE did = ?
+            "#,
+        )
+        .bind(&feed.oauth.access_token)
+        .bind(&feed.oauth.refresh_token)
+        .bind(&feed.oauth.expires_at)
+        .bind(&feed.oauth.access_token)
+        .bind(&feed.oauth.refresh_token)
+        .bind(&feed.did)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to sync tokens for {}", feed.did))?;
+
+        clear_poll_failures(pool, &feed.did).await?;
+    }
+
     Ok(())
 }
 
@@ -84,6 +267,7 @@ pub async fn update_tokens(
         SET access_token = ?,
             refresh_token = ?,
             token_expires_at = ?,
+            refresh_claimed_at = NULL,
             updated_at = ?
         WHERE did = ?
         "#,
@@ -100,32 +284,140 @@ pub async fn update_tokens(
     Ok(())
 }
 
-/// Sync a user's filters to database
-async fn sync_user_filters(pool: &StoragePool, user_did: &str, filters: &FilterConfig) -> Result<()> {
-    // Delete existing filters for this user
-    sqlx::query("DELETE FROM timeline_user_filters WHERE user_did = ?")
+/// Claim the right to refresh a user's tokens
+///
+/// Returns false while another poller holds an unexpired claim. A claim is
+/// released by [`update_tokens`] or [`release_token_refresh`], or lapses after
+/// `lease` so a crashed poller does not block refreshes forever.
+pub async fn claim_token_refresh(
+    pool: &StoragePool,
+    user_did: &str,
+    now: DateTime<Utc>,
+    lease: Duration,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE timeline_user_config
+        SET refresh_claimed_at = ?
+        WHERE did = ? AND (refresh_claimed_at IS NULL OR refresh_claimed_at < ?)
+        "#,
+    )
+    .bind(now.to_rfc3339())
+    .bind(user_did)
+    .bind((now - lease).to_rfc3339())
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to claim token refresh for {}", user_did))?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Give up a token refresh claim without storing new tokens
+pub async fn release_token_refresh(pool: &StoragePool, user_did: &str) -> Result<()> {
+    sqlx::query("UPDATE timeline_user_config SET refresh_claimed_at = NULL WHERE did = ?")
         .bind(user_did)
         .execute(pool)
+        .await
+        .with_context(|| format!("Failed to release token refresh for {}", user_did))?;
+
+    Ok(())
+}
+
+/// Sync a user's feed variants to database, removing variants no longer configured
+async fn sync_user_variants(pool: &StoragePool, feed: &TimelineFeed) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    let existing = sqlx::query_as::<_, (String,)>(
+        "SELECT feed_uri FROM timeline_user_feeds WHERE user_did = ?",
+    )
+    .bind(&feed.did)
+    .fetch_all(tx.as_mut())
+    .await?;
+
+    for (feed_uri,) in existing {
+        if feed.variants.iter().any(|variant| variant.feed_uri == feed_uri) {
+            continue;
+        }
+        sqlx::query("DELETE FROM timeline_user_filters WHERE feed_uri = ?")
+            .bind(&feed_uri)
+            .execute(tx.as_mut())
+            .await?;
+        sqlx::query("DELETE FROM timeline_user_feeds WHERE feed_uri = ?")
+            .bind(&feed_uri)
+            .execute(tx.as_mut())
+            .await?;
+    }
+
+    for variant in &feed.variants {
+        sqlx::query(
+            r#"
+            INSERT INTO timeline_user_feeds (feed_uri, user_did, name, description, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(feed_uri) DO UPDATE SET
+                user_did = excluded.user_did,
+                name = excluded.name,
+                description = excluded.description,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&variant.feed_uri)
+        .bind(&feed.did)
+        .bind(&variant.name)
+        .bind(&variant.description)
+        .bind(&now)
+        .bind(&now)
+        .execute(tx.as_mut())
+        .await
+        .with_context(|| format!("Failed to sync feed variant: {}", variant.feed_uri))?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Sync the filters of one feed to database
+pub async fn sync_feed_filters(
+    pool: &StoragePool,
+    user_did: &str,
+    feed_uri: &str,
+    filters: &FilterConfig,
+) -> Result<()> {
+    // Delete existing filters for this feed
+    sqlx::query("DELETE FROM timeline_user_filters WHERE feed_uri = ?")
+        .bind(feed_uri)
+        .execute(pool)
         .await?;
 
-    // Insert blocked reposters
-    for blocked_did in &filters.blocked_reposters {
+    // Insert blocked reposters, muted threads and toggles
+    let hide_all_reposts = filters.hide_all_reposts.then(|| "true".to_string());
+    let max_reposts_per_day = filters.max_reposts_per_day.map(|max| max.to_string());
+    let rows = filters
+        .blocked_reposters
+        .iter()
+        .map(|value| ("blocked_reposter", value))
+        .chain(filters.muted_threads.iter().map(|value| ("muted_thread", value)))
+        .chain(hide_all_reposts.iter().map(|value| ("hide_all_reposts", value)))
+        .chain(max_reposts_per_day.iter().map(|value| ("max_reposts_per_day", value)));
+    for (filter_type, filter_value) in rows {
         let now = Utc::now().to_rfc3339();
         sqlx::query(
             r#"
-            INSERT INTO timeline_user_filters (user_did, filter_type, filter_value, created_at)
-            VALUES (?, 'blocked_reposter', ?, ?)
+            INSERT INTO timeline_user_filters (user_did, feed_uri, filter_type, filter_value, created_at)
+            VALUES (?, ?, ?, ?, ?)
             "#,
         )
         .bind(user_did)
-        .bind(blocked_did)
+        .bind(feed_uri)
+        .bind(filter_type)
+        .bind(filter_value)
         .bind(&now)
         .execute(pool)
         .await
         .with_context(|| {
             format!(
-                "Failed to insert blocked_reposter filter for {} -> {}",
-                user_did, blocked_did
+                "Failed to insert {} filter for {} -> {}",
+                filter_type, feed_uri, filter_value
             )
         })?;
     }
@@ -140,7 +432,7 @@ pub async fn get_user_config(pool: &StoragePool, user_did: &str) -> Result<Optio
         SELECT
             did, feed_uri, name, description,
             access_token, refresh_token, token_expires_at, pds_url,
-            poll_interval_seconds, max_posts_per_poll
+            poll_interval_seconds, max_posts_per_poll, managed_by
         FROM timeline_user_config
         WHERE did = ?
         "#,
@@ -152,20 +444,47 @@ pub async fn get_user_config(pool: &StoragePool, user_did: &str) -> Result<Optio
     Ok(result)
 }
 
-/// Load user filters from database
+/// Load the filters of a user's primary feed from database
 pub async fn get_user_filters(pool: &StoragePool, user_did: &str) -> Result<UserFilters> {
     let filters = sqlx::query_as::<_, FilterRow>(
-        "SELECT filter_type, filter_value FROM timeline_user_filters WHERE user_did = ?",
+        r#"
+        SELECT f.filter_type, f.filter_value
+        FROM timeline_user_filters f
+        JOIN timeline_user_config c ON c.did = f.user_did AND c.feed_uri = f.feed_uri
+        WHERE f.user_did = ?
+        "#,
     )
     .bind(user_did)
     .fetch_all(pool)
     .await?;
 
+    Ok(filters_from_rows(filters))
+}
+
+/// Load the filters of a single feed (primary or variant) from database
+pub async fn get_feed_filters(pool: &StoragePool, feed_uri: &str) -> Result<UserFilters> {
+    let filters = sqlx::query_as::<_, FilterRow>(
+        "SELECT filter_type, filter_value FROM timeline_user_filters WHERE feed_uri = ?",
+    )
+    .bind(feed_uri)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(filters_from_rows(filters))
+}
+
+fn filters_from_rows(filters: Vec<FilterRow>) -> UserFilters {
     let mut blocked_reposters = Vec::new();
+    let mut muted_threads = Vec::new();
+    let mut hide_all_reposts = false;
+    let mut max_reposts_per_day = None;
 
     for filter in filters {
         match filter.filter_type.as_str() {
             "blocked_reposter" => blocked_reposters.push(filter.filter_value),
+            "muted_thread" => muted_threads.push(filter.filter_value),
+            "hide_all_reposts" => hide_all_reposts = filter.filter_value == "true",
+            "max_reposts_per_day" => max_reposts_per_day = filter.filter_value.parse().ok(),
             _ => {
                 tracing::warn!(
                     filter_type = %filter.filter_type,
@@ -175,7 +494,33 @@ pub async fn get_user_filters(pool: &StoragePool, user_did: &str) -> Result<User
         }
     }
 
-    Ok(UserFilters { blocked_reposters })
+    UserFilters {
+        blocked_reposters,
+        muted_threads,
+        hide_all_reposts,
+        max_reposts_per_day,
+    }
+}
+
+/// Load the author diversity limits of a feed, unlimited when none are configured
+pub async fn get_feed_diversity(pool: &StoragePool, feed_uri: &str) -> Result<AuthorDiversity> {
+    let row = sqlx::query_as::<_, (Option<i64>, Option<i64>)>(
+        r#"
+        SELECT max_consecutive_per_author, max_per_author_per_page
+        FROM timeline_feed_diversity
+        WHERE feed_uri = ?
+        "#,
+    )
+    .bind(feed_uri)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row
+        .map(|(max_consecutive, max_per_page)| AuthorDiversity {
+            max_consecutive_per_author: max_consecutive.map(|value| value as u32),
+            max_per_author_per_page: max_per_page.map(|value| value as u32),
+        })
+        .unwrap_or_default())
 }
 
 /// Check if enough time has passed to poll this user's timeline (for new posts)
@@ -284,6 +629,213 @@ pub async fn get_total_posts_indexed(pool: &StoragePool, user_did: &str) -> Resu
     Ok(result.flatten().unwrap_or(0))
 }
 
+/// Timestamp of the newest post indexed into a feed, if any
+pub async fn get_newest_indexed_at(pool: &StoragePool, feed_uri: &str) -> Result<Option<i64>> {
+    let result = sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(indexed_at) FROM feed_content WHERE feed_id = ?",
+    )
+    .bind(feed_uri)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result)
+}
+
+/// Record a gap in a user's timeline, to be filled from the cursor down to until_indexed_at
+pub async fn save_poll_gap(
+    pool: &StoragePool,
+    user_did: &str,
+    cursor: &str,
+    until_indexed_at: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO timeline_poll_gaps (user_did, cursor, until_indexed_at, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(user_did)
+    .bind(cursor)
+    .bind(until_indexed_at)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to save poll gap for {}", user_did))?;
+
+    Ok(())
+}
+
+/// The oldest open gap in a user's timeline
+pub async fn get_poll_gap(pool: &StoragePool, user_did: &str) -> Result<Option<PollGap>> {
+    let gap = sqlx::query_as::<_, PollGap>(
+        r#"
+        SELECT id, cursor, until_indexed_at
+        FROM timeline_poll_gaps
+        WHERE user_did = ?
+        ORDER BY id
+        LIMIT 1
+        "#,
+    )
+    .bind(user_did)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(gap)
+}
+
+/// Move a gap's cursor forward, or close it when cursor is None
+pub async fn update_poll_gap(pool: &StoragePool, gap_id: i64, cursor: Option<&str>) -> Result<()> {
+    match cursor {
+        Some(cursor) => {
+            sqlx::query("UPDATE timeline_poll_gaps SET cursor = ? WHERE id = ?")
+                .bind(cursor)
+                .bind(gap_id)
+                .execute(pool)
+                .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM timeline_poll_gaps WHERE id = ?")
+                .bind(gap_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rate limit budget last reported by a PDS
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct PdsRateLimit {
+    pub pds_host: String,
+    pub rate_limit: i64,
+    pub remaining: i64,
+    /// Unix time in seconds when the window resets
+    pub reset_at: Option<i64>,
+}
+
+/// Store the latest rate limit headers seen from a PDS
+pub async fn pds_rate_limit_upsert(pool: &StoragePool, rate_limit: &PdsRateLimit) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO pds_rate_limits (pds_host, rate_limit, remaining, reset_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(pds_host) DO UPDATE SET
+            rate_limit = excluded.rate_limit,
+            remaining = excluded.remaining,
+            reset_at = excluded.reset_at,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&rate_limit.pds_host)
+    .bind(rate_limit.rate_limit)
+    .bind(rate_limit.remaining)
+    .bind(rate_limit.reset_at)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to store rate limit of {}", rate_limit.pds_host))?;
+    Ok(())
+}
+
+/// Latest rate limit budget of every PDS polled so far
+pub async fn pds_rate_limit_list(pool: &StoragePool) -> Result<Vec<PdsRateLimit>> {
+    sqlx::query_as::<_, PdsRateLimit>(
+        "SELECT pds_host, rate_limit, remaining, reset_at FROM pds_rate_limits ORDER BY pds_host",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch PDS rate limits")
+}
+
+/// Record a failed poll, returning the number of consecutive failures
+pub async fn record_poll_failure(
+    pool: &StoragePool,
+    user_did: &str,
+    error: &str,
+    backoff: impl Fn(i64) -> Duration,
+) -> Result<i64> {
+    let failures = sqlx::query_scalar::<_, i64>(
+        "SELECT consecutive_failures FROM timeline_poll_errors WHERE user_did = ?",
+    )
+    .bind(user_did)
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or(0)
+        + 1;
+
+    let now = Utc::now();
+    sqlx::query(
+        r#"
+        INSERT INTO timeline_poll_errors (user_did, consecutive_failures, last_error, last_failure_at, retry_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(user_did) DO UPDATE SET
+            consecutive_failures = excluded.consecutive_failures,
+            last_error = excluded.last_error,
+            last_failure_at = excluded.last_failure_at,
+            retry_at = excluded.retry_at
+        "#,
+    )
+    .bind(user_did)
+    .bind(failures)
+    .bind(error)
+    .bind(now.to_rfc3339())
+    .bind((now + backoff(failures)).to_rfc3339())
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to record poll failure for {}", user_did))?;
+
+    Ok(failures)
+}
+
+/// Stop polling a user whose session was revoked, until new tokens arrive
+pub async fn mark_reauth_required(pool: &StoragePool, user_did: &str, error: &str) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        r#"
+        INSERT INTO timeline_poll_errors (user_did, consecutive_failures, last_error, last_failure_at, retry_at, reauth_required)
+        VALUES (?, 1, ?, ?, ?, 1)
+        ON CONFLICT(user_did) DO UPDATE SET
+            consecutive_failures = consecutive_failures + 1,
+            last_error = excluded.last_error,
+            last_failure_at = excluded.last_failure_at,
+            retry_at = excluded.retry_at,
+            reauth_required = 1
+        "#,
+    )
+    .bind(user_did)
+    .bind(error)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to mark {} as needing re-authentication", user_did))?;
+
+    Ok(())
+}
+
+/// Forget a user's poll failures, after a successful poll or new tokens
+pub async fn clear_poll_failures(pool: &StoragePool, user_did: &str) -> Result<()> {
+    sqlx::query("DELETE FROM timeline_poll_errors WHERE user_did = ?")
+        .bind(user_did)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Current failure state of a user's polls, if the last poll failed
+pub async fn get_poll_error(pool: &StoragePool, user_did: &str) -> Result<Option<PollError>> {
+    let error = sqlx::query_as::<_, PollError>(
+        r#"
+        SELECT consecutive_failures, last_error, retry_at, reauth_required
+        FROM timeline_poll_errors
+        WHERE user_did = ?
+        "#,
+    )
+    .bind(user_did)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(error)
+}
+
 /// Get feed statistics for a user
 pub async fn get_feed_stats(pool: &StoragePool, feed_uri: &str) -> Result<FeedStats> {
     // Count total posts and reposts
@@ -462,29 +1014,392 @@ pub async fn get_poll_stats(pool: &StoragePool, user_did: &str) -> Result<Option
 
 // Database models
 
-#[derive(Debug, Clone, sqlx::FromRow)]
-pub struct UserConfig {
-    pub did: String,
-    pub feed_uri: String,
-    pub name: String,
-    pub description: String,
-    pub access_token: String,
-    pub refresh_token: Option<String>,
-    pub token_expires_at: Option<String>,
-    pub pds_url: String,
-    pub poll_interval_seconds: i64,
-    pub max_posts_per_poll: i64,
-}
-
-#[derive(Debug, Clone, sqlx::FromRow)]
-struct FilterRow {
-    filter_type: String,
-    filter_value: String,
-}
+/// Find the DID owning a feed URI, primary feed or variant
+pub async fn get_feed_owner(pool: &StoragePool, feed_uri: &str) -> Result<Option<String>> {
+    let row = sqlx::query_as::<_, (String,)>(
+        r#"
+        SELECT did FROM timeline_user_config WHERE feed_uri = ?
+        UNION ALL
+        SELECT user_did FROM timeline_user_feeds WHERE feed_uri = ?
+        "#,
+    )
+    .bind(feed_uri)
+    .bind(feed_uri)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to look up feed owner")?;
+
+    Ok(row.map(|(did,)| did))
+}
+
+/// Owner of a feed, a primary feed or variant, whose timeline is marked private
+pub async fn get_private_feed_owner(pool: &StoragePool, feed_uri: &str) -> Result<Option<String>> {
+    let row = sqlx::query_as::<_, (String,)>(
+        r#"
+        SELECT did FROM timeline_user_config
+        WHERE private = 1
+          AND (feed_uri = ? OR did IN (SELECT user_did FROM timeline_user_feeds WHERE feed_uri = ?))
+        "#,
+    )
+    .bind(feed_uri)
+    .bind(feed_uri)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to look up private feed owner")?;
+
+    Ok(row.map(|(did,)| did))
+}
+
+/// Check whether a feed URI is served here: a user feed, variant or aggregate
+pub async fn feed_exists(pool: &StoragePool, feed_uri: &str) -> Result<bool> {
+    if get_feed_owner(pool, feed_uri).await?.is_some() {
+        return Ok(true);
+    }
+
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM timeline_aggregate_feeds WHERE feed_uri = ?",
+    )
+    .bind(feed_uri)
+    .fetch_one(pool)
+    .await
+    .context("Failed to look up aggregate feed")?;
+
+    Ok(count > 0)
+}
+
+/// Create or update a feed owned by the database (self-service web flow)
+///
+/// Feeds managed by the YAML config are never overwritten.
+pub async fn upsert_web_feed(pool: &StoragePool, config: &UserConfig) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO timeline_user_config (
+            did, feed_uri, name, description,
+            access_token, refresh_token, token_expires_at, pds_url,
+            poll_interval_seconds, max_posts_per_poll, managed_by,
+            created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'web', ?, ?)
+        ON CONFLICT(did) DO UPDATE SET
+            access_token = excluded.access_token,
+            refresh_token = excluded.refresh_token,
+            token_expires_at = excluded.token_expires_at,
+            pds_url = excluded.pds_url,
+            updated_at = excluded.updated_at
+        WHERE timeline_user_config.managed_by = 'web'
+        "#,
+    )
+    .bind(&config.did)
+    .bind(&config.feed_uri)
+    .bind(&config.name)
+    .bind(&config.description)
+    .bind(&config.access_token)
+    .bind(&config.refresh_token)
+    .bind(&config.token_expires_at)
+    .bind(&config.pds_url)
+    .bind(config.poll_interval_seconds)
+    .bind(config.max_posts_per_poll)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to save self-service feed for {}", config.did))?;
+
+    // Logging in again resumes a feed suspended after failed polls
+    if result.rows_affected() > 0 {
+        clear_poll_failures(pool, &config.did).await?;
+    }
+
+    Ok(())
+}
+
+/// Load every self-service feed as a TimelineFeed the consumer can poll
+pub async fn get_web_feeds(pool: &StoragePool) -> Result<Vec<TimelineFeed>> {
+    let rows = sqlx::query_as::<_, UserConfig>(
+        r#"
+        SELECT
+            did, feed_uri, name, description,
+            access_token, refresh_token, token_expires_at, pds_url,
+            poll_interval_seconds, max_posts_per_poll, managed_by
+        FROM timeline_user_config
+        WHERE managed_by = 'web'
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load self-service feeds")?;
+
+    let mut feeds = Vec::with_capacity(rows.len());
+    for row in rows {
+        let stored = get_feed_filters(pool, &row.feed_uri).await?;
+        let filters = FilterConfig {
+            blocked_reposters: stored.blocked_reposters.into_iter().collect(),
+            muted_threads: stored.muted_threads.into_iter().collect(),
+            hide_all_reposts: stored.hide_all_reposts,
+            max_reposts_per_day: stored.max_reposts_per_day,
+            ..Default::default()
+        };
+
+        feeds.push(TimelineFeed {
+            did: row.did,
+            feed_uri: row.feed_uri,
+            name: row.name,
+            description: row.description,
+            oauth: OAuthConfig {
+                access_token: row.access_token,
+                refresh_token: row.refresh_token,
+                expires_at: row.token_expires_at,
+                pds_url: row.pds_url,
+            },
+            filters,
+            poll_interval: None,
+            max_posts_per_poll: row.max_posts_per_poll as u32,
+            backfill_limit: default_backfill_limit(),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
+        });
+    }
+
+    Ok(feeds)
+}
+
+/// Store a new web session for a user
+pub async fn session_create(
+    pool: &StoragePool,
+    session_id: &str,
+    did: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO web_sessions (session_id, did, created_at, expires_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(session_id)
+    .bind(did)
+    .bind(Utc::now().to_rfc3339())
+    .bind(expires_at.to_rfc3339())
+    .execute(pool)
+    .await
+    .context("Failed to create web session")?;
+
+    Ok(())
+}
+
+/// Look up the DID of a session that has not expired
+pub async fn session_get_did(
+    pool: &StoragePool,
+    session_id: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<String>> {
+    let row = sqlx::query_as::<_, (String,)>(
+        "SELECT did FROM web_sessions WHERE session_id = ? AND expires_at > ?",
+    )
+    .bind(session_id)
+    .bind(now.to_rfc3339())
+    .fetch_optional(pool)
+    .await
+    .context("Failed to load web session")?;
+
+    Ok(row.map(|(did,)| did))
+}
+
+/// Delete a web session (logout)
+pub async fn session_delete(pool: &StoragePool, session_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM web_sessions WHERE session_id = ?")
+        .bind(session_id)
+        .execute(pool)
+        .await
+        .context("Failed to delete web session")?;
+
+    Ok(())
+}
+
+/// Delete expired web sessions, returning how many were removed
+pub async fn session_remove_expired(pool: &StoragePool, now: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM web_sessions WHERE expires_at <= ?")
+        .bind(now.to_rfc3339())
+        .execute(pool)
+        .await
+        .context("Failed to remove expired web sessions")?;
+
+    Ok(result.rows_affected())
+}
+
+/// Feeds and row count removed by [`delete_user_data`]
+#[derive(Debug, Clone)]
+pub struct DeletedUser {
+    pub feed_uris: Vec<String>,
+    pub rows_deleted: u64,
+}
+
+/// Statements run for each of the user's feed URIs
+///
+/// Post metadata is shared between feeds, so it goes before the feed content
+/// and only for posts no other feed still holds.
+const DELETE_FEED_STATEMENTS: &[&str] = &[
+    "DELETE FROM post_metadata WHERE uri IN (SELECT uri FROM feed_content WHERE feed_id = ?1) AND uri NOT IN (SELECT uri FROM feed_content WHERE feed_id != ?1)",
+    "DELETE FROM webhook_dead_letters WHERE EXISTS (SELECT 1 FROM json_each(payload, '$.posts') WHERE json_extract(value, '$.feed') = ?)",
+    "DELETE FROM feed_content WHERE feed_id = ?",
+    "DELETE FROM feed_pins WHERE feed_id = ?",
+    "DELETE FROM timeline_feed_diversity WHERE feed_uri = ?",
+    "DELETE FROM feed_serve_log WHERE feed_uri = ?",
+    "DELETE FROM denylist WHERE feed_id = ?",
+];
+
+/// Statements run for the user's DID, config last so foreign keys still resolve
+const DELETE_USER_STATEMENTS: &[&str] = &[
+    "DELETE FROM post_metadata WHERE uri IN (SELECT uri FROM feed_content WHERE source_did = ?1) AND uri NOT IN (SELECT uri FROM feed_content WHERE source_did IS NOT ?1)",
+    "DELETE FROM webhook_dead_letters WHERE EXISTS (SELECT 1 FROM json_each(payload, '$.posts') WHERE json_extract(value, '$.source') = ?)",
+    "DELETE FROM feed_content WHERE source_did = ?",
+    "DELETE FROM feed_serve_log WHERE viewer_did = ?1 OR viewer_did_claimed = ?1",
+    "DELETE FROM timeline_aggregate_sources WHERE source_did = ?",
+    "DELETE FROM timeline_user_filters WHERE user_did = ?",
+    "DELETE FROM timeline_poll_cursor WHERE user_did = ?",
+    "DELETE FROM timeline_poll_backfill WHERE user_did = ?",
+    "DELETE FROM timeline_poll_gaps WHERE user_did = ?",
+    "DELETE FROM timeline_poll_errors WHERE user_did = ?",
+    "DELETE FROM web_sessions WHERE did = ?",
+    "DELETE FROM timeline_user_feeds WHERE user_did = ?",
+    "DELETE FROM timeline_user_config WHERE did = ?",
+];
+
+/// Delete everything stored for a timeline user in one transaction
+///
+/// Removes the config and tokens, filters, poll state, sessions, and the
+/// content, post metadata, pins, serve log and webhook dead letters of the
+/// user's feed and its variants. Returns
+/// `None` when the user is unknown.
+pub async fn delete_user_data(pool: &StoragePool, user_did: &str) -> Result<Option<DeletedUser>> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let feed_uris: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT feed_uri FROM timeline_user_config WHERE did = ?
+        UNION
+        SELECT feed_uri FROM timeline_user_feeds WHERE user_did = ?
+        "#,
+    )
+    .bind(user_did)
+    .bind(user_did)
+    .fetch_all(tx.as_mut())
+    .await
+    .with_context(|| format!("Failed to load feeds of {}", user_did))?;
+
+    if feed_uris.is_empty() {
+        return Ok(None);
+    }
+
+    let mut rows_deleted = 0;
+    for feed_uri in &feed_uris {
+        for statement in DELETE_FEED_STATEMENTS {
+            let result = sqlx::query(statement)
+                .bind(feed_uri)
+                .execute(tx.as_mut())
+                .await
+                .with_context(|| format!("Failed to delete data of {}", feed_uri))?;
+            rows_deleted += result.rows_affected();
+        }
+    }
+    for statement in DELETE_USER_STATEMENTS {
+        let result = sqlx::query(statement)
+            .bind(user_did)
+            .execute(tx.as_mut())
+            .await
+            .with_context(|| format!("Failed to delete data of {}", user_did))?;
+        rows_deleted += result.rows_affected();
+    }
+
+    tx.commit().await.context("Failed to commit user deletion")?;
+
+    Ok(Some(DeletedUser {
+        feed_uris,
+        rows_deleted,
+    }))
+}
+
+#[derive(Clone, sqlx::FromRow)]
+pub struct UserConfig {
+    pub did: String,
+    pub feed_uri: String,
+    pub name: String,
+    pub description: String,
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub token_expires_at: Option<String>,
+    pub pds_url: String,
+    pub poll_interval_seconds: i64,
+    pub max_posts_per_poll: i64,
+    /// 'config' for feeds from the YAML file, 'web' for self-service feeds
+    pub managed_by: String,
+}
+
+impl fmt::Debug for UserConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserConfig")
+            .field("did", &self.did)
+            .field("feed_uri", &self.feed_uri)
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("access_token", &REDACTED)
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| REDACTED))
+            .field("token_expires_at", &self.token_expires_at)
+            .field("pds_url", &self.pds_url)
+            .field("poll_interval_seconds", &self.poll_interval_seconds)
+            .field("max_posts_per_poll", &self.max_posts_per_poll)
+            .field("managed_by", &self.managed_by)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct FilterRow {
+    filter_type: String,
+    filter_value: String,
+}
 
 #[derive(Debug, Clone)]
 pub struct UserFilters {
     pub blocked_reposters: Vec<String>,
+    pub muted_threads: Vec<String>,
+    pub hide_all_reposts: bool,
+    pub max_reposts_per_day: Option<u32>,
+}
+
+/// Consecutive poll failures after which a user is no longer polled until new tokens arrive
+pub const POLL_SUSPEND_AFTER: i64 = 10;
+
+/// Consecutive poll failures of a user
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PollError {
+    pub consecutive_failures: i64,
+    pub last_error: String,
+    pub retry_at: String,
+    /// The PDS rejected the refresh token, so only new tokens can help
+    pub reauth_required: bool,
+}
+
+impl PollError {
+    /// Whether polling stopped until the user's tokens are replaced
+    pub fn is_suspended(&self) -> bool {
+        self.reauth_required || self.consecutive_failures >= POLL_SUSPEND_AFTER
+    }
+
+    /// Whether the backoff after the last failure has passed
+    pub fn can_retry(&self, now: DateTime<Utc>) -> bool {
+        DateTime::parse_from_rfc3339(&self.retry_at)
+            .map(|retry_at| now >= retry_at)
+            .unwrap_or(true)
+    }
+}
+
+/// A stretch of timeline skipped by a new-posts poll
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PollGap {
+    pub id: i64,
+    pub cursor: String,
+    pub until_indexed_at: i64,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -494,10 +1409,221 @@ pub struct PollStats {
     pub total_posts_indexed: i64,
 }
 
+/// Get all feed URIs: primary feeds, their variants and aggregate feeds
+pub async fn get_all_feed_uris(pool: &StoragePool) -> Result<Vec<String>> {
+    let rows = sqlx::query_as::<_, (String,)>(
+        r#"
+        SELECT feed_uri FROM (
+            SELECT feed_uri, created_at FROM timeline_user_config
+            UNION ALL
+            SELECT feed_uri, created_at FROM timeline_user_feeds
+            UNION ALL
+            SELECT feed_uri, created_at FROM timeline_aggregate_feeds
+        )
+        ORDER BY created_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch feed URIs")?;
+
+    Ok(rows.into_iter().map(|(uri,)| uri).collect())
+}
+
+/// Display name and description of a served feed
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FeedDescription {
+    pub feed_uri: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// Name and description of every feed, newest first, as in get_all_feed_uris
+pub async fn get_feed_descriptions(pool: &StoragePool) -> Result<Vec<FeedDescription>> {
+    sqlx::query_as::<_, FeedDescription>(
+        r#"
+        SELECT feed_uri, name, description FROM (
+            SELECT feed_uri, name, description, created_at FROM timeline_user_config
+            UNION ALL
+            SELECT feed_uri, name, description, created_at FROM timeline_user_feeds
+            UNION ALL
+            SELECT feed_uri, name, description, created_at FROM timeline_aggregate_feeds
+        )
+        ORDER BY created_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch feed descriptions")
+}
+
+/// Content and polling state of one served feed, for the admin status page
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FeedStatus {
+    pub feed_uri: String,
+    /// "primary", "variant" or "aggregate"
+    pub kind: String,
+    /// Account whose timeline feeds this feed; None for aggregates
+    pub owner_did: Option<String>,
+    pub post_count: i64,
+    /// Newest and oldest indexed_at in microseconds
+    pub newest_indexed_at: Option<i64>,
+    pub oldest_indexed_at: Option<i64>,
+    /// When a post was last inserted into this feed
+    pub last_indexed: Option<String>,
+    pub last_poll_at: Option<String>,
+    pub backfill_last_poll_at: Option<String>,
+    /// A backfill cursor is stored, so older posts remain to be fetched
+    pub backfill_pending: Option<bool>,
+    pub total_posts_indexed: Option<i64>,
+    /// Consecutive failed polls of the owner and the last error, if failing
+    pub consecutive_failures: Option<i64>,
+    pub last_error: Option<String>,
+    /// The owner's session was revoked and they need to log in again
+    pub reauth_required: Option<bool>,
+}
+
+/// Get the status of every served feed
+pub async fn get_feed_status(pool: &StoragePool) -> Result<Vec<FeedStatus>> {
+    sqlx::query_as::<_, FeedStatus>(
+        r#"
+        SELECT
+            f.feed_uri,
+            f.kind,
+            f.owner_did,
+            (SELECT COUNT(*) FROM feed_content c WHERE c.feed_id = f.feed_uri) AS post_count,
+            (SELECT MAX(indexed_at) FROM feed_content c WHERE c.feed_id = f.feed_uri) AS newest_indexed_at,
+            (SELECT MIN(indexed_at) FROM feed_content c WHERE c.feed_id = f.feed_uri) AS oldest_indexed_at,
+            (SELECT MAX(updated_at) FROM feed_content c WHERE c.feed_id = f.feed_uri) AS last_indexed,
+            p.last_poll_at,
+            b.last_poll_at AS backfill_last_poll_at,
+            p.last_cursor IS NOT NULL AS backfill_pending,
+            p.total_posts_indexed,
+            e.consecutive_failures,
+            e.last_error,
+            e.reauth_required
+        FROM (
+            SELECT feed_uri, 'primary' AS kind, did AS owner_did FROM timeline_user_config
+            UNION ALL
+            SELECT feed_uri, 'variant', user_did FROM timeline_user_feeds
+            UNION ALL
+            SELECT feed_uri, 'aggregate', NULL FROM timeline_aggregate_feeds
+        ) f
+        LEFT JOIN timeline_poll_cursor p ON p.user_did = f.owner_did
+        LEFT JOIN timeline_poll_backfill b ON b.user_did = f.owner_did
+        LEFT JOIN timeline_poll_errors e ON e.user_did = f.owner_did
+        ORDER BY f.feed_uri
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch feed status")
+}
+
+/// A post in a timeline feed, as served by getFeedSkeleton
+#[derive(Debug, Clone)]
+pub struct FeedPost {
+    pub uri: String,
+    pub repost_uri: Option<String>,
+    pub indexed_at: i64,
+}
+
+impl FeedPost {
+    /// Cursor pointing just past this post
+    pub fn cursor(&self) -> String {
+        format!("{}::{}", self.indexed_at, self.uri)
+    }
+
+    /// Whether this post sorts after the cursor position (indexed_at DESC, uri DESC)
+    pub fn is_after(&self, cursor: &FeedCursor) -> bool {
+        (self.indexed_at, self.uri.as_str()) < (cursor.indexed_at, cursor.uri.as_str())
+    }
+}
+
+/// Position in a feed, keyed on the last post a client has seen
+///
+/// Keying on (indexed_at, uri) instead of an offset keeps pagination stable
+/// while new posts and backfilled posts are inserted into the feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedCursor {
+    pub indexed_at: i64,
+    pub uri: String,
+}
+
+impl FeedCursor {
+    /// Parse a cursor of the form `<indexed_at>::<uri>`
+    pub fn parse(value: &str) -> Option<Self> {
+        let (indexed_at, uri) = value.split_once("::")?;
+        let indexed_at = indexed_at.parse::<i64>().ok()?;
+        if !uri.starts_with("at://") {
+            return None;
+        }
+        Some(Self {
+            indexed_at,
+            uri: uri.to_string(),
+        })
+    }
+}
+
+/// Get posts for a timeline feed (for getFeedSkeleton endpoint)
+/// Returns posts ordered by indexed_at DESC, starting after the cursor if given
+pub async fn get_feed_posts(
+    pool: &StoragePool,
+    feed_uri: &str,
+    limit: u32,
+    cursor: Option<&FeedCursor>,
+) -> Result<Vec<FeedPost>> {
+    // Timeline Filter stores posts in feed_content table with feed_id = feed_uri
+    let rows = match cursor {
+        Some(cursor) => {
+            sqlx::query_as::<_, (String, Option<String>, i64)>(
+                r#"
+                SELECT uri, repost_uri, indexed_at
+                FROM feed_content
+                WHERE feed_id = ? AND (indexed_at, uri) < (?, ?)
+                ORDER BY indexed_at DESC, uri DESC
+                LIMIT ?
+                "#,
+            )
+            .bind(feed_uri)
+            .bind(cursor.indexed_at)
+            .bind(&cursor.uri)
+            .bind(limit as i64)
+            .fetch_all(pool)
+            .await
+        }
+        None => {
+            sqlx::query_as::<_, (String, Option<String>, i64)>(
+                r#"
+                SELECT uri, repost_uri, indexed_at
+                FROM feed_content
+                WHERE feed_id = ?
+                ORDER BY indexed_at DESC, uri DESC
+                LIMIT ?
+                "#,
+            )
+            .bind(feed_uri)
+            .bind(limit as i64)
+            .fetch_all(pool)
+            .await
+        }
+    }
+    .context("Failed to fetch timeline posts")?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(uri, repost_uri, indexed_at)| FeedPost {
+            uri,
+            repost_uri,
+            indexed_at,
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::feed_config::{FilterConfig, OAuthConfig, TimelineFeed};
+    use crate::feed_config::{FilterConfig, OAuthConfig, TimelineFeed, TimelineFeedVariant};
     use sqlx::SqlitePool;
 
     async fn setup_test_pool() -> SqlitePool {
@@ -525,6 +1651,10 @@ mod tests {
             poll_interval: Some("30s".to_string()),
             max_posts_per_poll: 50,
             backfill_limit: Some(500),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         sync_user_config(&pool, &feed).await.unwrap();
@@ -556,6 +1686,10 @@ mod tests {
             poll_interval: None,
             max_posts_per_poll: 50,
             backfill_limit: Some(500),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
 
         sync_user_config(&pool, &feed).await.unwrap();
@@ -568,14 +1702,114 @@ mod tests {
         filters
             .blocked_reposters
             .insert("did:plc:blocked2".to_string());
+        filters
+            .muted_threads
+            .insert("at://did:plc:op/app.bsky.feed.post/root".to_string());
+        filters.hide_all_reposts = true;
+        filters.max_reposts_per_day = Some(5);
 
-        sync_user_filters(&pool, "did:plc:test123", &filters)
+        sync_feed_filters(&pool, "did:plc:test123", &feed.feed_uri, &filters)
             .await
             .unwrap();
 
         let loaded = get_user_filters(&pool, "did:plc:test123").await.unwrap();
         assert_eq!(loaded.blocked_reposters.len(), 2);
         assert!(loaded.blocked_reposters.contains(&"did:plc:blocked1".to_string()));
+        assert_eq!(loaded.muted_threads, vec!["at://did:plc:op/app.bsky.feed.post/root".to_string()]);
+        assert!(loaded.hide_all_reposts);
+        assert_eq!(loaded.max_reposts_per_day, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_variants() {
+        let pool = setup_test_pool().await;
+
+        let mut quiet_filters = FilterConfig::default();
+        quiet_filters
+            .blocked_reposters
+            .insert("did:plc:noisy".to_string());
+
+        let mut feed = TimelineFeed {
+            did: "did:plc:test123".to_string(),
+            feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/main".to_string(),
+            name: "Main".to_string(),
+            description: "Everything".to_string(),
+            oauth: OAuthConfig {
+                access_token: "test_token".to_string(),
+                refresh_token: None,
+                expires_at: None,
+                pds_url: "https://bsky.social".to_string(),
+            },
+            filters: FilterConfig::default(),
+            poll_interval: None,
+            max_posts_per_poll: 50,
+            backfill_limit: Some(500),
+            variants: vec![TimelineFeedVariant {
+                feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/quiet".to_string(),
+                name: "Quiet".to_string(),
+                description: "No noisy reposts".to_string(),
+                filters: quiet_filters,
+                diversity: AuthorDiversity {
+                    max_consecutive_per_author: Some(2),
+                    max_per_author_per_page: None,
+                },
+                webhook_url: None,
+            }],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: true,
+        };
+
+        let feeds = TimelineFeeds {
+            timeline_feeds: vec![feed.clone()],
+            aggregate_feeds: vec![],
+        };
+        sync_config_to_db(&pool, &feeds).await.unwrap();
+
+        assert_eq!(get_all_feed_uris(&pool).await.unwrap().len(), 2);
+        let descriptions = get_feed_descriptions(&pool).await.unwrap();
+        assert!(descriptions
+            .iter()
+            .any(|d| d.feed_uri == feed.variants[0].feed_uri && d.name == "Quiet" && d.description == "No noisy reposts"));
+        let quiet_diversity = get_feed_diversity(&pool, &feed.variants[0].feed_uri).await.unwrap();
+        assert_eq!(quiet_diversity.max_consecutive_per_author, Some(2));
+        assert!(!get_feed_diversity(&pool, &feed.feed_uri).await.unwrap().is_enabled());
+        update_poll_state(&pool, "did:plc:test123", Some("cursor"), 3, 1).await.unwrap();
+        let status = get_feed_status(&pool).await.unwrap();
+        assert_eq!(status.len(), 2);
+        assert!(status.iter().all(|feed| feed.backfill_pending == Some(true)));
+        assert_eq!(status[0].total_posts_indexed, Some(3));
+        assert!(get_user_filters(&pool, "did:plc:test123")
+            .await
+            .unwrap()
+            .blocked_reposters
+            .is_empty());
+        let quiet = get_feed_filters(&pool, "at://did:plc:feedgen/app.bsky.feed.generator/quiet")
+            .await
+            .unwrap();
+        assert_eq!(quiet.blocked_reposters, vec!["did:plc:noisy".to_string()]);
+        assert_eq!(
+            get_private_feed_owner(&pool, &feed.variants[0].feed_uri).await.unwrap(),
+            Some("did:plc:test123".to_string())
+        );
+
+        // Removing the variant from config drops it and its filters
+        feed.variants.clear();
+        let feeds = TimelineFeeds {
+            timeline_feeds: vec![feed],
+            aggregate_feeds: vec![],
+        };
+        sync_config_to_db(&pool, &feeds).await.unwrap();
+
+        assert_eq!(get_all_feed_uris(&pool).await.unwrap().len(), 1);
+        let quiet = get_feed_filters(&pool, "at://did:plc:feedgen/app.bsky.feed.generator/quiet")
+            .await
+            .unwrap();
+        assert!(quiet.blocked_reposters.is_empty());
+        assert!(get_private_feed_owner(&pool, "at://did:plc:feedgen/app.bsky.feed.generator/quiet")
+            .await
+            .unwrap()
+            .is_none());
     }
 
     #[tokio::test]
@@ -598,9 +1832,25 @@ mod tests {
             poll_interval: None,
             max_posts_per_poll: 50,
             backfill_limit: Some(500),
+            variants: vec![],
+            diversity: AuthorDiversity::default(),
+            webhook_url: None,
+            private: false,
         };
         sync_user_config(&pool, &feed).await.unwrap();
 
+        // One refresher at a time, until the tokens are stored or the lease lapses
+        let now = Utc::now();
+        let lease = Duration::seconds(30);
+        assert!(claim_token_refresh(&pool, "did:plc:test123", now, lease).await.unwrap());
+        assert!(!claim_token_refresh(&pool, "did:plc:test123", now, lease).await.unwrap());
+        assert!(claim_token_refresh(&pool, "did:plc:test123", now + Duration::minutes(1), lease)
+            .await
+            .unwrap());
+        update_tokens(&pool, "did:plc:test123", "access", Some("refresh"), None).await.unwrap();
+        assert!(claim_token_refresh(&pool, "did:plc:test123", now, lease).await.unwrap());
+        release_token_refresh(&pool, "did:plc:test123").await.unwrap();
+
         // Should poll when no record exists
         let should = should_poll(&pool, "did:plc:test123", Duration::seconds(30))
             .await
@@ -624,56 +1874,333 @@ mod tests {
         let stats = stats.unwrap();
         assert_eq!(stats.posts_indexed, 10);
         assert_eq!(stats.total_posts_indexed, 10);
+
+        // Gaps are repaired oldest first and closed with a None cursor
+        save_poll_gap(&pool, "did:plc:test123", "gap1", 100).await.unwrap();
+        save_poll_gap(&pool, "did:plc:test123", "gap2", 200).await.unwrap();
+        let gap = get_poll_gap(&pool, "did:plc:test123").await.unwrap().unwrap();
+        assert_eq!(gap.cursor, "gap1");
+        update_poll_gap(&pool, gap.id, Some("gap1-next")).await.unwrap();
+        assert_eq!(get_poll_gap(&pool, "did:plc:test123").await.unwrap().unwrap().cursor, "gap1-next");
+        update_poll_gap(&pool, gap.id, None).await.unwrap();
+        let gap = get_poll_gap(&pool, "did:plc:test123").await.unwrap().unwrap();
+        assert_eq!(gap.until_indexed_at, 200);
+
+        // Failures count up until a successful poll or new tokens clear them
+        for _ in 0..2 {
+            record_poll_failure(&pool, "did:plc:test123", "401", |_| Duration::hours(1))
+                .await
+                .unwrap();
+        }
+        let poll_error = get_poll_error(&pool, "did:plc:test123").await.unwrap().unwrap();
+        assert_eq!(poll_error.consecutive_failures, 2);
+        assert!(!poll_error.can_retry(Utc::now()));
+        assert!(!poll_error.is_suspended());
+        let mut feed = feed;
+        feed.oauth.access_token = "new_token".to_string();
+        sync_user_config(&pool, &feed).await.unwrap();
+        assert!(get_poll_error(&pool, "did:plc:test123").await.unwrap().is_none());
+
+        // A revoked session suspends polling at once, until new tokens again
+        mark_reauth_required(&pool, "did:plc:test123", "ExpiredToken").await.unwrap();
+        let poll_error = get_poll_error(&pool, "did:plc:test123").await.unwrap().unwrap();
+        assert!(poll_error.reauth_required);
+        assert!(poll_error.is_suspended());
+        assert_eq!(get_feed_status(&pool).await.unwrap()[0].reauth_required, Some(true));
+
+        // Restarting with the same tokens keeps the feed suspended
+        sync_user_config(&pool, &feed).await.unwrap();
+        assert!(get_poll_error(&pool, "did:plc:test123").await.unwrap().is_some());
+        feed.oauth.refresh_token = Some("new_refresh_token".to_string());
+        sync_user_config(&pool, &feed).await.unwrap();
+        assert!(get_poll_error(&pool, "did:plc:test123").await.unwrap().is_none());
     }
-}
 
-/// Get all feed URIs from timeline_user_config
-/// Get all feed URIs from timeline_user_config
-pub async fn get_all_feed_uris(pool: &StoragePool) -> Result<Vec<String>> {
-    let rows = sqlx::query_as::<_, (String,)>(
-        "SELECT feed_uri FROM timeline_user_config ORDER BY created_at DESC"
-    )
-    .fetch_all(pool)
-    .await
-    .context("Failed to fetch feed URIs")?;
+    #[tokio::test]
+    async fn test_restart_keeps_refreshed_tokens_and_revocation() {
+        let pool = setup_test_pool().await;
 
-    Ok(rows.into_iter().map(|(uri,)| uri).collect())
-}
+        let feeds = TimelineFeeds {
+            timeline_feeds: vec![TimelineFeed {
+                did: "did:plc:test123".to_string(),
+                feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/test".to_string(),
+                name: "Test Feed".to_string(),
+                description: "A test feed".to_string(),
+                oauth: OAuthConfig {
+                    access_token: "config_access".to_string(),
+                    refresh_token: Some("config_refresh".to_string()),
+                    expires_at: None,
+                    pds_url: "https://bsky.social".to_string(),
+                },
+                filters: FilterConfig::default(),
+                poll_interval: None,
+                max_posts_per_poll: 50,
+                backfill_limit: Some(500),
+                variants: vec![],
+                diversity: AuthorDiversity::default(),
+                webhook_url: None,
+                private: false,
+            }],
+            aggregate_feeds: vec![],
+        };
+        sync_config_to_db(&pool, &feeds).await.unwrap();
 
-/// Get posts for a timeline feed (for getFeedSkeleton endpoint)
-/// Returns posts ordered by indexed_at DESC with pagination support
-pub struct FeedPost {
-    pub uri: String,
-    pub repost_uri: Option<String>,
-}
+        // A refresh rotates the tokens, then the refresh token gets revoked
+        update_tokens(&pool, "did:plc:test123", "live_access", Some("live_refresh"), None)
+            .await
+            .unwrap();
+        mark_reauth_required(&pool, "did:plc:test123", "ExpiredToken").await.unwrap();
+
+        // Restarting with the unchanged config neither resumes nor rewinds the tokens
+        sync_config_to_db(&pool, &feeds).await.unwrap();
+        let poll_error = get_poll_error(&pool, "did:plc:test123").await.unwrap().unwrap();
+        assert!(poll_error.reauth_required);
+        let stored = get_user_config(&pool, "did:plc:test123").await.unwrap().unwrap();
+        assert_eq!(stored.access_token, "live_access");
+        assert_eq!(stored.refresh_token.as_deref(), Some("live_refresh"));
+
+        // New tokens in the config replace the live ones and resume polling
+        let mut feeds = feeds;
+        feeds.timeline_feeds[0].oauth.refresh_token = Some("new_refresh".to_string());
+        sync_config_to_db(&pool, &feeds).await.unwrap();
+        assert!(get_poll_error(&pool, "did:plc:test123").await.unwrap().is_none());
+        let stored = get_user_config(&pool, "did:plc:test123").await.unwrap().unwrap();
+        assert_eq!(stored.access_token, "config_access");
+        assert_eq!(stored.refresh_token.as_deref(), Some("new_refresh"));
+    }
 
-pub async fn get_feed_posts(
-    pool: &StoragePool,
-    feed_uri: &str,
-    limit: u32,
-    cursor: Option<String>,
-) -> Result<Vec<FeedPost>> {
-    // Parse cursor as offset (simple pagination)
-    let offset = cursor
-        .and_then(|c| c.parse::<i64>().ok())
-        .unwrap_or(0);
+    #[tokio::test]
+    async fn test_sync_aggregate_feeds() {
+        let pool = setup_test_pool().await;
 
-    // Timeline Filter stores posts in feed_content table with feed_id = feed_uri
-    let rows = sqlx::query_as::<_, (String, Option<String>)>(
-        r#"
-        SELECT uri, repost_uri
-        FROM feed_content
-        WHERE feed_id = ?
-        ORDER BY indexed_at DESC
-        LIMIT ? OFFSET ?
-        "#,
-    )
-    .bind(feed_uri)
-    .bind(limit as i64)
-    .bind(offset)
-    .fetch_all(pool)
-    .await
-    .context("Failed to fetch timeline posts")?;
+        let mut feeds = TimelineFeeds {
+            timeline_feeds: vec![],
+            aggregate_feeds: vec![AggregateFeed {
+                feed_uri: "at://did:plc:feedgen/app.bsky.feed.generator/team".to_string(),
+                name: "Team".to_string(),
+                description: "Everyone".to_string(),
+                sources: vec!["did:plc:alice".to_string(), "did:plc:bob".to_string()],
+                diversity: AuthorDiversity::default(),
+                webhook_url: None,
+            }],
+        };
+        sync_config_to_db(&pool, &feeds).await.unwrap();
+
+        assert!(feed_exists(&pool, "at://did:plc:feedgen/app.bsky.feed.generator/team")
+            .await
+            .unwrap());
+        assert_eq!(get_all_feed_uris(&pool).await.unwrap().len(), 1);
+        // Aggregates have no owner, so the filter API cannot touch them
+        assert!(get_feed_owner(&pool, "at://did:plc:feedgen/app.bsky.feed.generator/team")
+            .await
+            .unwrap()
+            .is_none());
+
+        let status = get_feed_status(&pool).await.unwrap();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].kind, "aggregate");
+        assert_eq!(status[0].post_count, 0);
+        assert!(status[0].last_poll_at.is_none());
+
+        feeds.aggregate_feeds.clear();
+        sync_config_to_db(&pool, &feeds).await.unwrap();
+        assert!(!feed_exists(&pool, "at://did:plc:feedgen/app.bsky.feed.generator/team")
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_web_feed_and_sessions() {
+        let pool = setup_test_pool().await;
+
+        let config = UserConfig {
+            did: "did:plc:web123".to_string(),
+            feed_uri: "at://did:plc:web123/app.bsky.feed.generator/timeline".to_string(),
+            name: "Filtered Timeline".to_string(),
+            description: "A self-service feed".to_string(),
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            token_expires_at: None,
+            pds_url: "https://bsky.social".to_string(),
+            poll_interval_seconds: 30,
+            max_posts_per_poll: 50,
+            managed_by: "web".to_string(),
+        };
+        upsert_web_feed(&pool, &config).await.unwrap();
+
+        let mut filters = FilterConfig::default();
+        filters.blocked_reposters.insert("did:plc:noisy".to_string());
+        sync_feed_filters(&pool, &config.did, &config.feed_uri, &filters)
+            .await
+            .unwrap();
+
+        let web_feeds = get_web_feeds(&pool).await.unwrap();
+        assert_eq!(web_feeds.len(), 1);
+        assert!(web_feeds[0].filters.is_reposter_blocked("did:plc:noisy"));
+
+        let now = Utc::now();
+        session_create(&pool, "session-1", &config.did, now + Duration::days(1))
+            .await
+            .unwrap();
+        session_create(&pool, "session-2", &config.did, now - Duration::days(1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            session_get_did(&pool, "session-1", now).await.unwrap().as_deref(),
+            Some("did:plc:web123")
+        );
+        assert!(session_get_did(&pool, "session-2", now).await.unwrap().is_none());
+        assert_eq!(session_remove_expired(&pool, now).await.unwrap(), 1);
+
+        session_delete(&pool, "session-1").await.unwrap();
+        assert!(session_get_did(&pool, "session-1", now).await.unwrap().is_none());
+    }
 
-    Ok(rows.into_iter().map(|(uri, repost_uri)| FeedPost { uri, repost_uri }).collect())
+    #[tokio::test]
+    async fn test_delete_user_data() {
+        use crate::feed_storage::{
+            feed_content_upsert, post_metadata_upsert,
+            model::{FeedContent, PostMetadata},
+        };
+
+        let pool = setup_test_pool().await;
+        let user = |did: &str, feed_uri: &str| UserConfig {
+            did: did.to_string(),
+            feed_uri: feed_uri.to_string(),
+            name: "Filtered Timeline".to_string(),
+            description: "A self-service feed".to_string(),
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            token_expires_at: None,
+            pds_url: "https://bsky.social".to_string(),
+            poll_interval_seconds: 30,
+            max_posts_per_poll: 50,
+            managed_by: "web".to_string(),
+        };
+        let leaving = user("did:plc:leaving", "at://did:plc:feedgen/app.bsky.feed.generator/leaving");
+        let staying = user("did:plc:staying", "at://did:plc:feedgen/app.bsky.feed.generator/staying");
+
+        for config in [&leaving, &staying] {
+            upsert_web_feed(&pool, config).await.unwrap();
+            let mut filters = FilterConfig::default();
+            filters.blocked_reposters.insert("did:plc:noisy".to_string());
+            sync_feed_filters(&pool, &config.did, &config.feed_uri, &filters)
+                .await
+                .unwrap();
+            update_poll_state(&pool, &config.did, Some("cursor"), 1, 0).await.unwrap();
+            session_create(&pool, &config.did, &config.did, Utc::now() + Duration::days(1))
+                .await
+                .unwrap();
+            let uri = format!("at://did:plc:author/app.bsky.feed.post/{}", config.did);
+            feed_content_upsert(
+                &pool,
+                &FeedContent {
+                    feed_id: config.feed_uri.clone(),
+                    uri: uri.clone(),
+                    indexed_at: 1,
+                    score: 1,
+                    is_repost: false,
+                    repost_uri: None,
+                    source_did: Some(config.did.clone()),
+                    cid: None,
+                },
+            )
+            .await
+            .unwrap();
+            post_metadata_upsert(
+                &pool,
+                &PostMetadata {
+                    uri: uri.clone(),
+                    text: "hello".to_string(),
+                    author_did: Some("did:plc:author".to_string()),
+                    author_handle: None,
+                    embed_type: None,
+                },
+            )
+            .await
+            .unwrap();
+            let payload = serde_json::json!({"posts": [{"feed": config.feed_uri, "uri": uri, "source": config.did}]});
+            sqlx::query(
+                "INSERT INTO webhook_dead_letters (url, payload, error, created_at) VALUES ('https://hook.example.com', ?, 'timeout', '2025-10-17T00:00:00Z')",
+            )
+            .bind(payload.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let count = |table: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {}", table))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let deleted = delete_user_data(&pool, &leaving.did).await.unwrap().unwrap();
+        assert_eq!(deleted.feed_uris, vec![leaving.feed_uri.clone()]);
+        // Post metadata, dead letter, content, filter, poll cursor, session and config
+        assert_eq!(deleted.rows_deleted, 7);
+        assert_eq!(count("post_metadata").await, 1);
+        assert_eq!(count("webhook_dead_letters").await, 1);
+        assert!(get_user_config(&pool, &leaving.did).await.unwrap().is_none());
+        assert!(!feed_exists(&pool, &leaving.feed_uri).await.unwrap());
+        assert!(get_feed_filters(&pool, &leaving.feed_uri).await.unwrap().blocked_reposters.is_empty());
+        assert!(get_feed_posts(&pool, &leaving.feed_uri, 10, None).await.unwrap().is_empty());
+
+        // Other users are untouched, and deleting again finds nothing
+        assert!(get_user_config(&pool, &staying.did).await.unwrap().is_some());
+        assert_eq!(get_feed_posts(&pool, &staying.feed_uri, 10, None).await.unwrap().len(), 1);
+        assert!(session_get_did(&pool, &staying.did, Utc::now()).await.unwrap().is_some());
+        assert!(delete_user_data(&pool, &leaving.did).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_feed_posts_cursor_is_stable() {
+        use crate::feed_storage::{feed_content_upsert, model::FeedContent};
+
+        let pool = setup_test_pool().await;
+        let feed_uri = "at://did:plc:feedgen/app.bsky.feed.generator/test";
+        let insert = |indexed_at: i64| {
+            let pool = pool.clone();
+            async move {
+                feed_content_upsert(
+                    &pool,
+                    &FeedContent {
+                        feed_id: feed_uri.to_string(),
+                        uri: format!("at://did:plc:author/app.bsky.feed.post/{}", indexed_at),
+                        indexed_at,
+                        score: 1,
+                        is_repost: false,
+                        repost_uri: None,
+                        source_did: None,
+                        cid: None,
+                    },
+                )
+                .await
+                .unwrap();
+            }
+        };
+
+        for indexed_at in [10, 20, 30, 40] {
+            insert(indexed_at).await;
+        }
+
+        let first = get_feed_posts(&pool, feed_uri, 2, None).await.unwrap();
+        assert_eq!(first[1].indexed_at, 30);
+
+        // A new post and a backfilled post land while the client pages
+        insert(50).await;
+        insert(5).await;
+
+        let cursor = FeedCursor::parse(&first[1].cursor()).unwrap();
+        let second = get_feed_posts(&pool, feed_uri, 10, Some(&cursor)).await.unwrap();
+        let indexed: Vec<i64> = second.iter().map(|post| post.indexed_at).collect();
+        assert_eq!(indexed, vec![20, 10, 5]);
+
+        assert!(FeedCursor::parse("25").is_none());
+        assert!(FeedCursor::parse("abc::at://x").is_none());
+    }
 }
@@ -0,0 +1,142 @@
+//! Verification Method Cache
+//!
+//! Resolves the atproto signing key for a DID (plc.directory or did:web) and
+//! caches it in the verification_method_cache table, so request JWTs can be
+//! verified without a DID document fetch per request.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use tokio_util::sync::CancellationToken;
+
+use crate::feed_storage::{
+    verification_method_get, verification_method_stale, verification_method_upsert, StoragePool,
+};
+use crate::identity::{did_document_signing_key, resolve_did_document};
+
+#[derive(Clone)]
+pub struct VerificationMethodCache {
+    pool: StoragePool,
+    http_client: reqwest::Client,
+    plc_hostname: String,
+    max_age: Duration,
+    /// Store resolved keys; off on read replicas, whose database is read-only
+    store: bool,
+}
+
+impl VerificationMethodCache {
+    pub fn new(
+        pool: StoragePool,
+        http_client: reqwest::Client,
+        plc_hostname: &str,
+        max_age: Duration,
+    ) -> Self {
+        Self {
+            pool,
+            http_client,
+            plc_hostname: plc_hostname.to_string(),
+            max_age,
+            store: true,
+        }
+    }
+
+    /// Resolve keys missing from the cache without storing them
+    pub fn read_only(mut self) -> Self {
+        self.store = false;
+        self
+    }
+
+    /// Get the signing key for a DID, resolving it if not cached or stale
+    pub async fn get(&self, did: &str) -> Result<String> {
+        Ok(self.get_entry(did).await?.0)
+    }
+
+    /// Get the signing key for a DID and when it was resolved
+    pub async fn get_entry(&self, did: &str) -> Result<(String, DateTime<Utc>)> {
+        let min_updated_at = Utc::now() - self.max_age;
+        if let Some(entry) = verification_method_get(&self.pool, did, min_updated_at).await? {
+            return Ok(entry);
+        }
+
+        Ok((self.refresh(did).await?, Utc::now()))
+    }
+
+    /// Resolve the current DID document, bypassing the cache
+    pub async fn resolve_document(&self, did: &str) -> Result<serde_json::Value> {
+        resolve_did_document(&self.http_client, &self.plc_hostname, did).await
+    }
+
+    /// Resolve the DID document and store its signing key
+    pub async fn refresh(&self, did: &str) -> Result<String> {
+        let document = self.resolve_document(did).await?;
+        let multikey = did_document_signing_key(&document)
+            .ok_or_else(|| anyhow!("DID document has no atproto verification method: {}", did))?;
+
+        if self.store {
+            verification_method_upsert(&self.pool, did, &multikey)
+                .await
+                .context("failed to cache verification method")?;
+        }
+
+        Ok(multikey)
+    }
+}
+
+/// Background task that re-resolves cached verification methods before they go stale
+pub struct VerificationMethodCacheTask {
+    cache: VerificationMethodCache,
+    cancellation_token: CancellationToken,
+}
+
+impl VerificationMethodCacheTask {
+    pub fn new(cache: VerificationMethodCache, cancellation_token: CancellationToken) -> Self {
+        Self {
+            cache,
+            cancellation_token,
+        }
+    }
+
+    pub async fn run_background(&self, interval: chrono::Duration) -> Result<()> {
+        let interval = interval.to_std()?;
+
+        let sleeper = tokio::time::sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+
+                    if let Err(err) = self.main().await {
+                        tracing::error!("VerificationMethodCacheTask task failed: {}", err);
+                    }
+
+
+                sleeper.as_mut().reset(tokio::time::Instant::now() + interval);
+            }
+            }
+        }
+        Ok(())
+    }
+
+    /// Refresh every entry older than the cache max age
+    ///
+    /// Entries that fail to refresh are kept so a resolver outage does not
+    /// invalidate keys that were good a moment ago.
+    pub async fn main(&self) -> Result<()> {
+        let updated_before = Utc::now() - self.cache.max_age;
+        let stale = verification_method_stale(&self.cache.pool, updated_before).await?;
+
+        for did in stale {
+            if self.cancellation_token.is_cancelled() {
+                break;
+            }
+            if let Err(err) = self.cache.refresh(&did).await {
+                tracing::warn!(did = %did, error = ?err, "failed to refresh verification method");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::feed_storage::StoragePool;
+
+/// How often buffered posts are sent
+pub const WEBHOOK_FLUSH_INTERVAL: chrono::Duration = chrono::Duration::seconds(5);
+
+/// Posts buffered per URL before a batch is sent early
+const WEBHOOK_BATCH_SIZE: usize = 50;
+
+/// Posts waiting for the webhook task; new posts are dropped beyond this
+const WEBHOOK_QUEUE_SIZE: usize = 1000;
+
+/// Delivery attempts per batch before it goes to the dead-letter table
+const WEBHOOK_ATTEMPTS: u32 = 3;
+
+/// Longest text snippet sent per post, in characters
+const WEBHOOK_TEXT_LIMIT: usize = 280;
+
+/// A newly indexed post, as delivered to a feed's webhook
+#[derive(Clone, Debug, Serialize)]
+pub struct WebhookPost {
+    pub feed: String,
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repost_uri: Option<String>,
+    pub author: String,
+    /// Account whose timeline the post came from
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    pub indexed_at: i64,
+}
+
+impl WebhookPost {
+    /// Cut the post text down to the snippet sent to webhooks
+    pub fn snippet(text: &str) -> String {
+        text.chars().take(WEBHOOK_TEXT_LIMIT).collect()
+    }
+}
+
+/// Handle for queueing posts to webhooks, cheap to clone
+///
+/// Queueing never waits: when the webhook task falls behind, posts are dropped
+/// rather than slowing down polling.
+#[derive(Clone)]
+pub struct WebhookQueue(Option<mpsc::Sender<(String, WebhookPost)>>);
+
+impl WebhookQueue {
+    /// A queue that discards everything
+    pub fn disabled() -> Self {
+        Self(None)
+    }
+
+    /// Queue a post for delivery to a webhook URL
+    pub fn push(&self, url: &str, post: WebhookPost) {
+        let Some(sender) = &self.0 else {
+            return;
+        };
+        if let Err(err) = sender.try_send((url.to_string(), post)) {
+            tracing::warn!(url = %url, error = %err, "Webhook queue full, dropping post");
+        }
+    }
+}
+
+/// Delivers newly indexed posts to feed webhooks in batches
+///
+/// Posts are collected per URL and sent as `{"posts": [...]}` once a batch is
+/// full or the flush interval passes. A batch that still fails after retries is
+/// stored in `webhook_dead_letters`.
+pub struct WebhookTask {
+    pool: StoragePool,
+    http_client: reqwest::Client,
+    receiver: mpsc::Receiver<(String, WebhookPost)>,
+    pending: HashMap<String, Vec<WebhookPost>>,
+    cancellation_token: CancellationToken,
+}
+
+impl WebhookTask {
+    pub fn new(
+        pool: StoragePool,
+        http_client: reqwest::Client,
+        cancellation_token: CancellationToken,
+    ) -> (Self, WebhookQueue) {
+        let (sender, receiver) = mpsc::channel(WEBHOOK_QUEUE_SIZE);
+        let task = Self {
+            pool,
+            http_client,
+            receiver,
+            pending: HashMap::new(),
+            cancellation_token,
+        };
+        (task, WebhookQueue(Some(sender)))
+    }
+
+    pub async fn run_background(mut self, interval: chrono::Duration) -> Result<()> {
+        let interval = interval.to_std()?;
+
+        let sleeper = tokio::time::sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            received = self.receiver.recv() => {
+                let Some((url, post)) = received else {
+                    break;
+                };
+                let batch = self.pending.entry(url.clone()).or_default();
+                batch.push(post);
+                if batch.len() >= WEBHOOK_BATCH_SIZE {
+                    let batch = self.pending.remove(&url).unwrap_or_default();
+                    self.deliver(&url, batch).await;
+                }
+            },
+            () = &mut sleeper => {
+                self.flush().await;
+                sleeper.as_mut().reset(tokio::time::Instant::now() + interval);
+            }
+            }
+        }
+
+        // Deliver what is left before shutting down
+        self.flush().await;
+        Ok(())
+    }
+
+    async fn flush(&mut self) {
+        for (url, batch) in std::mem::take(&mut self.pending) {
+            self.deliver(&url, batch).await;
+        }
+    }
+
+    async fn deliver(&self, url: &str, posts: Vec<WebhookPost>) {
+        if posts.is_empty() {
+            return;
+        }
+        let payload = json!({ "posts": posts });
+
+        let mut last_error = String::new();
+        for attempt in 1..=WEBHOOK_ATTEMPTS {
+            match self.send(url, &payload).await {
+                Ok(()) => {
+                    tracing::debug!(url = %url, posts = posts.len(), "Webhook delivered");
+                    return;
+                }
+                Err(err) => {
+                    tracing::warn!(url = %url, attempt, error = ?err, "Webhook delivery failed");
+                    last_error = format!("{:#}", err);
+                }
+            }
+            if attempt < WEBHOOK_ATTEMPTS {
+                tokio::time::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+            }
+        }
+
+        if let Err(err) = webhook_dead_letter_insert(&self.pool, url, &payload, &last_error).await {
+            tracing::error!(url = %url, error = ?err, "Failed to store undelivered webhook batch");
+        }
+    }
+
+    async fn send(&self, url: &str, payload: &serde_json::Value) -> Result<()> {
+        self.http_client
+            .post(url)
+            .json(payload)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Store a batch that could not be delivered
+async fn webhook_dead_letter_insert(
+    pool: &StoragePool,
+    url: &str,
+    payload: &serde_json::Value,
+    error: &str,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO webhook_dead_letters (url, payload, error, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(url)
+    .bind(payload.to_string())
+    .bind(error)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .context("failed to insert webhook dead letter")?;
+    Ok(())
+}